@@ -0,0 +1,104 @@
+//! Applies a scenario's anomalies to the simulated signals.
+
+use data_layer::scenario::{Anomaly, Scenario};
+
+/// Combined effect of the anomalies active at one moment.
+#[derive(Debug, Clone, Copy)]
+pub struct AnomalyEffects {
+    /// Added to resting HR (bpm).
+    pub resting_hr_bpm: f32,
+    /// Added to the HR target on top of resting (bpm).
+    pub hr_target_bpm: f32,
+    /// Multiplier on HRV.
+    pub hrv_factor: f32,
+    /// Added to the skin temperature target (°C).
+    pub temp_c: f32,
+    /// Irregular RR intervals: erratic beat-to-beat HR and inflated RMSSD.
+    pub irregular_rr: bool,
+    /// Motion artefact severity in [0, 1].
+    pub motion: f32,
+    /// The sensor produces no samples.
+    pub dropout: bool,
+}
+
+impl Default for AnomalyEffects {
+    fn default() -> Self {
+        Self {
+            resting_hr_bpm: 0.0,
+            hr_target_bpm: 0.0,
+            hrv_factor: 1.0,
+            temp_c: 0.0,
+            irregular_rr: false,
+            motion: 0.0,
+            dropout: false,
+        }
+    }
+}
+
+/// Scenario events resolved to absolute times.
+#[derive(Debug, Clone)]
+pub struct AnomalyInjector {
+    /// `(start, end, anomaly)` in Unix seconds, `[start, end)`.
+    events: Vec<(i64, i64, Anomaly)>,
+}
+
+impl AnomalyInjector {
+    /// HR rise per °C of fever (bpm).
+    const FEVER_BPM_PER_C: f32 = 10.0;
+
+    /// Resolve `scenario` for a simulation starting at `sim_start_ts` (Unix seconds).
+    pub fn new(scenario: &Scenario, sim_start_ts: i64) -> Self {
+        let events = scenario
+            .events
+            .iter()
+            .map(|e| {
+                let row = e.to_event_row(sim_start_ts);
+                (row.start_ts, row.end_ts, e.anomaly)
+            })
+            .collect();
+        Self { events }
+    }
+
+    /// Effects at `ts_unix_sec`; sleep bradycardia only applies while `asleep`.
+    pub fn effects_at(&self, ts_unix_sec: i64, asleep: bool) -> AnomalyEffects {
+        let mut fx = AnomalyEffects::default();
+        for &(start, end, anomaly) in &self.events {
+            if ts_unix_sec < start || ts_unix_sec >= end {
+                continue;
+            }
+            match anomaly {
+                Anomaly::Tachycardia { extra_bpm } => fx.hr_target_bpm += extra_bpm,
+                Anomaly::SleepBradycardia { drop_bpm } => {
+                    if asleep {
+                        fx.hr_target_bpm -= drop_bpm;
+                    }
+                }
+                Anomaly::AtrialFibrillation { extra_bpm } => {
+                    fx.hr_target_bpm += extra_bpm;
+                    fx.irregular_rr = true;
+                }
+                Anomaly::Fever { peak_c } => {
+                    let rise = peak_c * envelope(ts_unix_sec, start, end);
+                    fx.temp_c += rise;
+                    fx.hr_target_bpm += Self::FEVER_BPM_PER_C * rise;
+                }
+                Anomaly::Illness { resting_hr_rise_bpm } => {
+                    let rise = resting_hr_rise_bpm * envelope(ts_unix_sec, start, end);
+                    fx.resting_hr_bpm += rise;
+                    fx.hrv_factor *= (1.0 - 0.02 * rise).max(0.3);
+                }
+                Anomaly::Dropout => fx.dropout = true,
+                Anomaly::MotionArtifact { severity } => fx.motion = fx.motion.max(severity),
+            }
+        }
+        fx
+    }
+}
+
+/// Trapezoid in [0, 1]: ramps up over the first quarter of `[start, end)` and down over the last.
+fn envelope(ts: i64, start: i64, end: i64) -> f32 {
+    let ramp = ((end - start) as f32 / 4.0).max(1.0);
+    let up = (ts - start) as f32 / ramp;
+    let down = (end - ts) as f32 / ramp;
+    up.min(down).clamp(0.0, 1.0)
+}
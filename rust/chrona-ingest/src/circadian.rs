@@ -0,0 +1,37 @@
+//! Circadian modulation of the resting baselines used by the simulator.
+
+use data_layer::profile::UserProfile;
+use std::f32::consts::TAU;
+
+/// Time constant of the skin-temperature rise after sleep onset (seconds).
+const SLEEP_ONSET_TAU_SECS: f32 = 40.0 * 60.0;
+
+/// Offsets applied to a profile's baselines at one moment.
+#[derive(Debug, Clone, Copy)]
+pub struct CircadianOffsets {
+    /// Added to resting HR (bpm).
+    pub hr_bpm: f32,
+    /// Added to baseline skin temperature (°C).
+    pub skin_temp_c: f32,
+    /// Multiplier on baseline EDA.
+    pub eda_factor: f32,
+}
+
+/// Circadian offsets at `minute_of_day`, given the seconds since sleep onset if asleep.
+///
+/// One 24 h cosine per signal, aligned to the core-temperature trough: HR and EDA bottom
+/// out there while distal skin temperature peaks. Falling asleep adds a further skin
+/// warming that saturates within about an hour.
+pub fn offsets(profile: &UserProfile, minute_of_day: f32, secs_asleep: Option<i64>) -> CircadianOffsets {
+    let params = &profile.circadian;
+    let phase = TAU * (minute_of_day - profile.circadian_trough_min() as f32) / 1440.0;
+    let at_trough = phase.cos();
+    let onset_rise = secs_asleep.map_or(0.0, |secs| {
+        params.sleep_onset_temp_rise_c * (1.0 - (-(secs as f32) / SLEEP_ONSET_TAU_SECS).exp())
+    });
+    CircadianOffsets {
+        hr_bpm: -params.hr_amplitude_bpm * at_trough,
+        skin_temp_c: params.skin_temp_amplitude_c * at_trough + onset_rise,
+        eda_factor: 1.0 - params.eda_amplitude * at_trough,
+    }
+}
@@ -0,0 +1,198 @@
+//! Weekly exercise sessions planned from a profile's frequency, types and intensity preferences.
+
+use data_layer::profile::{ActivityKind, ExerciseType, IntensityDistribution, UserProfile, WeeklySchedule, MINUTES_PER_DAY};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+const SECS_PER_DAY: i64 = 86_400;
+/// Minimum gap between two sessions on the same day (minutes).
+const SESSION_GAP_MIN: i64 = 60;
+
+/// One planned exercise session, as Unix seconds `[start, end)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExerciseSession {
+    pub start: i64,
+    pub end: i64,
+    /// Activity during the main part of the session.
+    pub kind: ActivityKind,
+    pub warmup_secs: i64,
+    pub cooldown_secs: i64,
+}
+
+impl ExerciseSession {
+    pub fn contains(&self, ts_unix_sec: i64) -> bool {
+        ts_unix_sec >= self.start && ts_unix_sec < self.end
+    }
+
+    /// Effort in [0, 1] at `ts_unix_sec`: ramps up over the warm-up and down over the cool-down.
+    pub fn effort_at(&self, ts_unix_sec: i64) -> f32 {
+        let up = (ts_unix_sec - self.start) as f32 / self.warmup_secs.max(1) as f32;
+        let down = (self.end - ts_unix_sec) as f32 / self.cooldown_secs.max(1) as f32;
+        up.min(down).clamp(0.0, 1.0)
+    }
+}
+
+/// Places `exercise_frequency_per_week` sessions across each simulated week.
+///
+/// Running, cycling and gym blocks in the schedule are training slots and are used first,
+/// spreading sessions over as many days as possible. Remaining sessions go into free
+/// awake time, favouring mornings, lunchtime and early evenings. Slots left unused are
+/// spent sitting. Each week's plan depends only on the seed and week index.
+#[derive(Debug, Clone)]
+pub struct ExercisePlanner {
+    schedule: WeeklySchedule,
+    types: Vec<ExerciseType>,
+    per_week: usize,
+    intensity: IntensityDistribution,
+    sleep_start_min: i64,
+    sleep_duration_min: i64,
+    sleep_jitter_min: i64,
+    seed: u64,
+}
+
+impl ExercisePlanner {
+    pub fn from_profile(profile: &UserProfile, seed: u64) -> Self {
+        Self {
+            schedule: profile.activity_schedule.clone(),
+            types: profile.exercise_types.clone(),
+            per_week: profile.exercise_frequency_per_week,
+            intensity: profile.exercise_intensity_pref,
+            sleep_start_min: profile.typical_sleep_start_min as i64,
+            sleep_duration_min: profile.typical_sleep_duration_min as i64,
+            sleep_jitter_min: profile.sleep_jitter_min as i64,
+            seed,
+        }
+    }
+
+    /// Week index (Monday-based, counted from the epoch) containing `ts_unix_sec`.
+    pub fn week_of(ts_unix_sec: i64) -> i64 {
+        // 1970-01-01 was a Thursday
+        (ts_unix_sec.div_euclid(SECS_PER_DAY) + 3).div_euclid(7)
+    }
+
+    /// Sessions of week `week`, in start order.
+    pub fn sessions_for_week(&self, week: i64) -> Vec<ExerciseSession> {
+        let mut rng = ChaCha8Rng::seed_from_u64(self.seed ^ (week as u64).wrapping_mul(0xA24B_AED4_963E_E407));
+        let monday = week * 7 - 3;
+
+        // (weekday index, block start minute, block duration, scheduled kind)
+        let mut slots: Vec<(usize, i64, i64, ActivityKind)> = Vec::new();
+        for d in 0..7 {
+            let day_start = (monday + d as i64) * SECS_PER_DAY;
+            for block in &self.schedule.for_timestamp(day_start).blocks {
+                if block.kind.is_exercise() {
+                    slots.push((d, block.start_minute as i64, block.duration_min as i64, block.kind));
+                }
+            }
+        }
+
+        let mut per_day = [0usize; 7];
+        let mut sessions = Vec::new();
+        for _ in 0..self.per_week {
+            let fewest = per_day.iter().copied().min().unwrap_or(0);
+            let open: Vec<usize> = (0..slots.len()).filter(|&i| per_day[slots[i].0] == fewest).collect();
+            if !open.is_empty() {
+                let (d, start, duration, kind) = slots.swap_remove(open[rng.random_range(0..open.len())]);
+                let exercise_type = ExerciseType::from_activity(kind).unwrap_or(ExerciseType::Running);
+                let day_start = (monday + d as i64) * SECS_PER_DAY;
+                sessions.push(self.session(&mut rng, exercise_type, day_start + start * 60, duration));
+                per_day[d] += 1;
+                continue;
+            }
+            // No slot left on the least-busy days: try free time there, then on any day
+            let mut days: Vec<usize> = (0..7).collect();
+            days.sort_by_key(|&d| (per_day[d], rng.random::<u32>()));
+            for d in days {
+                let day_start = (monday + d as i64) * SECS_PER_DAY;
+                if let Some(session) = self.free_session(&mut rng, day_start, &sessions) {
+                    sessions.push(session);
+                    per_day[d] += 1;
+                    break;
+                }
+            }
+        }
+        sessions.sort_by_key(|s| s.start);
+        sessions
+    }
+
+    /// Session of `exercise_type` starting at `start`, with intensity from the profile's preferences.
+    fn session(&self, rng: &mut ChaCha8Rng, exercise_type: ExerciseType, start: i64, duration_min: i64) -> ExerciseSession {
+        let kind = exercise_type.activity(self.intensity.pick(rng.random()));
+        let duration = duration_min * 60;
+        let ramp = |rng: &mut ChaCha8Rng| (rng.random_range(5..=10) * 60).min(duration / 4);
+        ExerciseSession {
+            start,
+            end: start + duration,
+            kind,
+            warmup_secs: ramp(rng),
+            cooldown_secs: ramp(rng),
+        }
+    }
+
+    /// Place a session in free awake time on the day starting at `day_start`, if any fits.
+    fn free_session(&self, rng: &mut ChaCha8Rng, day_start: i64, planned: &[ExerciseSession]) -> Option<ExerciseSession> {
+        if self.types.is_empty() {
+            return None;
+        }
+        let exercise_type = self.types[rng.random_range(0..self.types.len())];
+        let duration = match exercise_type {
+            ExerciseType::Running => rng.random_range(30..=60),
+            ExerciseType::Cycling => rng.random_range(45..=90),
+            ExerciseType::Gym => rng.random_range(45..=75),
+            ExerciseType::BriskWalking => rng.random_range(30..=50),
+        };
+
+        let schedule = self.schedule.for_timestamp(day_start);
+        let day = MINUTES_PER_DAY as i64;
+        let free: Vec<bool> = (0..day)
+            .map(|m| {
+                let ts = day_start + m * 60;
+                matches!(
+                    schedule.activity_at_minute(m as u16),
+                    ActivityKind::Sitting | ActivityKind::Standing | ActivityKind::WalkingSlow
+                ) && !self.near_sleep(m)
+                    && !planned.iter().any(|s| {
+                        ts >= s.start - SESSION_GAP_MIN * 60 && ts < s.end + SESSION_GAP_MIN * 60
+                    })
+            })
+            .collect();
+
+        // Favour the hour after waking, lunchtime and early evening
+        let wake = self.sleep_start_min + self.sleep_duration_min;
+        let anchors = [wake + 60, 735, self.sleep_start_min - 240];
+        let weights: Vec<f64> = (0..day - duration)
+            .map(|m| {
+                if !free[m as usize..(m + duration) as usize].iter().all(|&f| f) {
+                    return 0.0;
+                }
+                0.05 + anchors
+                    .iter()
+                    .map(|a| (1.0 - circular_distance(m, *a) as f64 / 90.0).max(0.0))
+                    .sum::<f64>()
+            })
+            .collect();
+        let total: f64 = weights.iter().sum();
+        if total <= 0.0 {
+            return None;
+        }
+        let mut pick = rng.random::<f64>() * total;
+        let start = weights.iter().position(|w| {
+            pick -= w;
+            pick < 0.0
+        })?;
+        Some(self.session(rng, exercise_type, day_start + start as i64 * 60, duration))
+    }
+
+    /// True within the typical sleep window, padded by the jitter, a wind-down before
+    /// bedtime and a little time after waking.
+    fn near_sleep(&self, minute: i64) -> bool {
+        let from = self.sleep_start_min - self.sleep_jitter_min - 120;
+        let length = self.sleep_duration_min + 2 * self.sleep_jitter_min + 150;
+        (minute - from).rem_euclid(MINUTES_PER_DAY as i64) < length
+    }
+}
+
+fn circular_distance(a: i64, b: i64) -> i64 {
+    let d = (a - b).rem_euclid(MINUTES_PER_DAY as i64);
+    d.min(MINUTES_PER_DAY as i64 - d)
+}
@@ -0,0 +1,14 @@
+//! Synthetic sensor data generation for Chrona.
+//!
+//! Every random draw is derived from the session seed, so a stored session can be
+//! regenerated exactly from its profile, simulation date and seed.
+
+pub mod anomaly;
+pub mod circadian;
+pub mod exercise;
+pub mod llm_context;
+pub mod physiology;
+pub mod population;
+pub mod simulator;
+pub mod sleep;
+pub mod stress;
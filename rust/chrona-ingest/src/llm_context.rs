@@ -13,6 +13,7 @@ pub struct LlmSensorContext {
     pub accel_mag_g: f32,
     pub stress_level: f32,
     pub exercise_flag: bool,
+    pub sleep_stage: Option<String>,
 }
 
 impl LlmSensorContext {
@@ -26,12 +27,13 @@ impl LlmSensorContext {
             accel_mag_g: row.accel_mag_g,
             stress_level: row.stress_level,
             exercise_flag: row.exercise_flag,
+            sleep_stage: row.sleep_stage.clone(),
         }
     }
 
     /// Render a concise context string for the LLM.
     pub fn to_llm_context(&self) -> String {
-        format!(
+        let mut context = format!(
             "Activity: {}. HR: {:.0} bpm. HRV: {:.0} ms. EDA: {:.2} μS. Temp: {:.2}°C. Accel: {:.2}g. Stress: {:.2}. Exercise: {}.",
             self.activity,
             self.hr,
@@ -41,6 +43,10 @@ impl LlmSensorContext {
             self.accel_mag_g,
             self.stress_level,
             if self.exercise_flag { "yes" } else { "no" }
-        )
+        );
+        if let Some(stage) = &self.sleep_stage {
+            context.push_str(&format!(" Sleep stage: {}.", stage));
+        }
+        context
     }
 }
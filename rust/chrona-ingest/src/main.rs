@@ -1,109 +1,394 @@
-//! Example: ingest synthetic sensor data into SQLite.
+//! Command-line generator for synthetic Chrona sensor data.
 
-use data_layer::profile::presets;
-use data_layer::sqlite;
+use anyhow::{bail, Context};
+use chrona_ingest::population::{plan_population, sensor_rows, SyntheticUser};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use data_layer::import::{import_dir, LabelScheme};
+use data_layer::parquet::dataset::date_of;
+use data_layer::parquet::{write_events_parquet, SensorDataset, SensorParquetWriter};
+use data_layer::profile::{presets, UserProfile};
+use data_layer::query::SensorDataRow;
+use data_layer::scenario::Scenario;
+use data_layer::sqlite::{self, SensorBatchWriter};
+use rayon::prelude::*;
 use rusqlite::Connection;
-mod simulator;
-use simulator::SensorSimulator;
-use data_layer::parquet::write_sensor_data_parquet;
-use std::time::{SystemTime, UNIX_EPOCH};
-mod llm_context;
-use data_layer::query::query_latest_sensor_data;
-use llm_context::LlmSensorContext;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// Rows accumulated before each SQLite transaction / Parquet append.
+const CHUNK_ROWS: usize = 3600;
+const SECS_PER_DAY: u64 = 86_400;
+
+#[derive(Parser)]
+#[command(name = "chrona-ingest", about = "Generate synthetic wearable sensor data for Chrona")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Simulate a population of users and write their data to the chosen backends.
+    Generate(GenerateArgs),
+    /// List the built-in profile presets.
+    Presets,
+    /// Import recorded WESAD / PPG-DaLiA wrist data (E4 CSV layout) as 1 Hz sensor rows.
+    Import(ImportArgs),
+    /// Validate profile and scenario files without generating anything.
+    Check {
+        /// Profile file (.toml or .json); repeatable.
+        #[arg(long = "profile", value_name = "FILE")]
+        profiles: Vec<PathBuf>,
+        /// Scenario file (.toml or .json); repeatable.
+        #[arg(long = "scenario", value_name = "FILE")]
+        scenarios: Vec<PathBuf>,
+    },
+}
+
+#[derive(Args)]
+struct GenerateArgs {
+    /// Profile file (.toml or .json) to use as an archetype; repeatable.
+    #[arg(long = "profile", value_name = "FILE")]
+    profiles: Vec<PathBuf>,
+    /// Built-in preset to use as an archetype; repeatable. All presets are used when
+    /// neither --profile nor --preset is given.
+    #[arg(long = "preset", value_name = "NAME")]
+    presets: Vec<String>,
+    /// Synthetic users per archetype. User 0 keeps the archetype's parameters; the
+    /// others get randomized physiology.
+    #[arg(long, default_value_t = 1)]
+    users: usize,
+    /// Days to simulate per user, from midnight UTC of --start-date.
+    #[arg(long, default_value_t = 1, conflicts_with = "seconds")]
+    days: u64,
+    /// Simulate this many seconds instead of whole days.
+    #[arg(long)]
+    seconds: Option<u64>,
+    /// First simulated day (YYYY-MM-DD).
+    #[arg(long, default_value = "2025-12-19")]
+    start_date: String,
+    /// Output rows per second: 1, or 1/N to keep every N-th second. The simulation
+    /// itself always steps once per second.
+    #[arg(long, value_name = "HZ", default_value_t = 1.0)]
+    sample_rate: f64,
+    /// Base seed; each user's seed is derived from it.
+    #[arg(long, default_value_t = 42)]
+    seed: u64,
+    /// Output backends, comma-separated or repeated.
+    #[arg(long = "backend", value_enum, value_delimiter = ',', default_values_t = [Backend::Sqlite, Backend::Parquet])]
+    backends: Vec<Backend>,
+    /// Output directory, created if missing.
+    #[arg(long, short, default_value = ".")]
+    out: PathBuf,
+    /// Worker threads (defaults to one per core).
+    #[arg(long, short)]
+    jobs: Option<usize>,
+    /// Anomaly scenario file (.toml or .json) applied to every user.
+    #[arg(long, value_name = "FILE")]
+    scenario: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct ImportArgs {
+    /// Recording directory holding BVP.csv, EDA.csv, TEMP.csv, ACC.csv and optionally
+    /// HR.csv, IBI.csv and labels.csv; repeatable. The directory name becomes the user id.
+    #[arg(required = true, value_name = "DIR")]
+    dirs: Vec<PathBuf>,
+    /// Label vocabulary of labels.csv.
+    #[arg(long, value_enum)]
+    scheme: Scheme,
+    /// Output backends, comma-separated or repeated.
+    #[arg(long = "backend", value_enum, value_delimiter = ',', default_values_t = [Backend::Sqlite, Backend::Parquet])]
+    backends: Vec<Backend>,
+    /// Output directory, created if missing.
+    #[arg(long, short, default_value = ".")]
+    out: PathBuf,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Scheme {
+    /// WESAD: baseline, stress, amusement, meditation.
+    Wesad,
+    /// PPG-DaLiA: daily activities.
+    Dalia,
+}
+
+impl From<Scheme> for LabelScheme {
+    fn from(scheme: Scheme) -> Self {
+        match scheme {
+            Scheme::Wesad => LabelScheme::Wesad,
+            Scheme::Dalia => LabelScheme::Dalia,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Backend {
+    /// `test_sensor_data.sqlite3`: sessions, sensor rows, rollups and events.
+    Sqlite,
+    /// One `sensor_data_<session>_<user>.parquet` file per user.
+    Parquet,
+    /// Partitioned dataset under `dataset/user=<id>/date=<yyyy-mm-dd>/`.
+    Dataset,
+}
 
 fn main() -> anyhow::Result<()> {
-    // Open or create SQLite DB
-    let conn = Connection::open("test_sensor_data.sqlite3")?;
-    sqlite::init_db(&conn)?;
-
-    // List of preset profiles to cycle through
-    let profiles = vec![
-        ("BusinessProfessional", presets::business_professional()),
-        ("CollegeStudent", presets::college_student()),
-        ("ProAthlete", presets::pro_athlete()),
-        ("ShiftWorker", presets::shift_worker()),
-        ("RemoteWorker", presets::remote_worker()),
-    ];
-
-    for (profile_name, profile) in profiles {
-        let user_id = profile_name;
-        let simulation_date = "2025-12-19";
-        let seed = 42;
-        let session_id = sqlite::insert_user_session(&conn, user_id, profile_name, simulation_date, seed)?;
-
-        // Start time: midnight UTC
-        let start_ts = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(0);
-        let start_ts_ms = start_ts.duration_since(UNIX_EPOCH)?.as_millis() as i64;
-        let mut sim = SensorSimulator::new(profile, start_ts_ms);
-
-        // Prepare in-memory vectors for Parquet
-        let mut session_ids = Vec::with_capacity(60);
-        let mut ts_unix_secs = Vec::with_capacity(60);
-        let mut hrs = Vec::with_capacity(60);
-        let mut hrv_rmssds = Vec::with_capacity(60);
-        let mut eda_muss = Vec::with_capacity(60);
-        let mut temp_cs = Vec::with_capacity(60);
-        let mut accel_mag_gs = Vec::with_capacity(60);
-        let mut activities = Vec::with_capacity(60);
-        let mut stress_levels = Vec::with_capacity(60);
-        let mut exercise_flags = Vec::with_capacity(60);
-
-        // Ingest 60 seconds of data as a demo
-        for _ in 0..60 {
-            let samples = sim.poll_all();
-            let ts_unix_sec = sim.ts_ms / 1000;
-            // SQLite insert (legacy/optional)
-            sqlite::insert_sensor_data(
-                &conn,
-                session_id,
-                ts_unix_sec,
-                samples[0].value, // hr
-                samples[1].value, // hrv_rmssd
-                samples[2].value, // eda_mus
-                samples[3].value, // temp_c
-                samples[4].value, // accel_mag_g
-                "sitting",       // activity (placeholder)
-                0.25,             // stress_level (placeholder)
-                false,            // exercise_flag (placeholder)
-            )?;
-            // Parquet batch
-            session_ids.push(session_id);
-            ts_unix_secs.push(ts_unix_sec);
-            hrs.push(samples[0].value);
-            hrv_rmssds.push(samples[1].value);
-            eda_muss.push(samples[2].value);
-            temp_cs.push(samples[3].value);
-            accel_mag_gs.push(samples[4].value);
-            activities.push("sitting".to_string());
-            stress_levels.push(0.25);
-            exercise_flags.push(false);
+    match Cli::parse().command {
+        Command::Generate(args) => generate(args),
+        Command::Import(args) => import(args),
+        Command::Presets => {
+            for (name, _) in presets::FILES {
+                let profile = presets::by_name(name).expect("listed preset");
+                println!("{:<24} {:?}, resting HR {} bpm", name, profile.fitness_level, profile.resting_hr);
+            }
+            Ok(())
         }
-        // Write to Parquet
-        let parquet_path = format!("sensor_data_{}_{}.parquet", session_id, profile_name);
-        write_sensor_data_parquet(
-            &parquet_path,
-            &session_ids,
-            &ts_unix_secs,
-            &hrs,
-            &hrv_rmssds,
-            &eda_muss,
-            &temp_cs,
-            &accel_mag_gs,
-            &activities,
-            &stress_levels,
-            &exercise_flags,
-        )?;
-        println!("Wrote 60 seconds of synthetic data to Parquet for session_id {} (profile: {})", session_id, profile_name);
-
-        // Query the latest row and build LLM context
-        let latest = query_latest_sensor_data(&conn, session_id, 1)?;
-        if let Some(row) = latest.first() {
-            let ctx = LlmSensorContext::from_sensor_row(row);
-            let llm_prompt = ctx.to_llm_context();
-            println!("LLM prompt context for {}: {}", profile_name, llm_prompt);
-        } else {
-            println!("No sensor data found for session {}", profile_name);
+        Command::Check { profiles, scenarios } => {
+            let mut failed = 0;
+            for path in &profiles {
+                report(path, UserProfile::load(path).map(|_| ()), &mut failed);
+            }
+            for path in &scenarios {
+                report(path, Scenario::load(path).map(|_| ()), &mut failed);
+            }
+            if failed > 0 {
+                bail!("{} of {} files failed validation", failed, profiles.len() + scenarios.len());
+            }
+            Ok(())
+        }
+    }
+}
+
+fn report(path: &Path, result: Result<(), impl std::fmt::Display>, failed: &mut usize) {
+    match result {
+        Ok(()) => println!("ok      {}", path.display()),
+        Err(e) => {
+            println!("invalid {}: {}", path.display(), e);
+            *failed += 1;
+        }
+    }
+}
+
+/// Everything a worker needs to simulate one user and write its output.
+struct Run<'a> {
+    start_date: &'a str,
+    seconds: u64,
+    stride: u64,
+    scenario: Option<&'a Scenario>,
+    out: &'a Path,
+    sqlite: Option<&'a Mutex<Connection>>,
+    parquet: bool,
+    dataset: Option<&'a SensorDataset>,
+}
+
+fn generate(args: GenerateArgs) -> anyhow::Result<()> {
+    let stride = sample_stride(args.sample_rate)?;
+    let seconds = args.seconds.unwrap_or(args.days * SECS_PER_DAY);
+    let scenario = args.scenario.as_ref().map(Scenario::load).transpose()?;
+
+    let mut archetypes = Vec::new();
+    for path in &args.profiles {
+        let profile = UserProfile::load(path).with_context(|| format!("loading profile {}", path.display()))?;
+        let name = path.file_stem().and_then(|s| s.to_str()).unwrap_or("profile").to_string();
+        archetypes.push((name, profile));
+    }
+    for name in &args.presets {
+        let Some(profile) = presets::by_name(name) else {
+            bail!("unknown preset {:?}; run `chrona-ingest presets` to list them", name);
+        };
+        archetypes.push((name.clone(), profile));
+    }
+    if archetypes.is_empty() {
+        archetypes = presets::FILES
+            .iter()
+            .map(|(name, _)| (name.to_string(), presets::by_name(name).expect("listed preset")))
+            .collect();
+    }
+    let users = plan_population(&archetypes, args.users, args.seed);
+
+    std::fs::create_dir_all(&args.out)?;
+    let sqlite = if args.backends.contains(&Backend::Sqlite) {
+        let conn = Connection::open(args.out.join("test_sensor_data.sqlite3"))?;
+        sqlite::init_db(&conn)?;
+        Some(Mutex::new(conn))
+    } else {
+        None
+    };
+    let dataset = if args.backends.contains(&Backend::Dataset) {
+        Some(SensorDataset::open(args.out.join("dataset"))?)
+    } else {
+        None
+    };
+
+    // Sessions are registered up front so their ids follow the population order
+    let mut session_ids = Vec::with_capacity(users.len());
+    for (i, user) in users.iter().enumerate() {
+        session_ids.push(match &sqlite {
+            Some(conn) => sqlite::insert_user_session(
+                &conn.lock().expect("sqlite lock"),
+                &user.user_id,
+                &user.archetype,
+                &args.start_date,
+                user.seed as i64,
+            )?,
+            None => i as i64 + 1,
+        });
+    }
+
+    let run = Run {
+        start_date: &args.start_date,
+        seconds,
+        stride,
+        scenario: scenario.as_ref(),
+        out: &args.out,
+        sqlite: sqlite.as_ref(),
+        parquet: args.backends.contains(&Backend::Parquet),
+        dataset: dataset.as_ref(),
+    };
+    let pool = rayon::ThreadPoolBuilder::new().num_threads(args.jobs.unwrap_or(0)).build()?;
+    println!(
+        "Generating {} users × {} s from {} on {} threads",
+        users.len(),
+        seconds,
+        args.start_date,
+        pool.current_num_threads()
+    );
+    let started = Instant::now();
+    let rows: usize = pool.install(|| {
+        users
+            .par_iter()
+            .zip(&session_ids)
+            .map(|(user, &session_id)| run.user(user, session_id))
+            .sum::<anyhow::Result<usize>>()
+    })?;
+    let secs = started.elapsed().as_secs_f64();
+    println!("Wrote {} rows in {:.1} s ({:.0} rows/s)", rows, secs, rows as f64 / secs.max(1e-9));
+    Ok(())
+}
+
+fn import(args: ImportArgs) -> anyhow::Result<()> {
+    let scheme = LabelScheme::from(args.scheme);
+    std::fs::create_dir_all(&args.out)?;
+    let mut sqlite = if args.backends.contains(&Backend::Sqlite) {
+        let conn = Connection::open(args.out.join("test_sensor_data.sqlite3"))?;
+        sqlite::init_db(&conn)?;
+        Some(conn)
+    } else {
+        None
+    };
+    let dataset = if args.backends.contains(&Backend::Dataset) {
+        Some(SensorDataset::open(args.out.join("dataset"))?)
+    } else {
+        None
+    };
+
+    for (i, dir) in args.dirs.iter().enumerate() {
+        let rows = import_dir(dir, scheme).with_context(|| format!("importing {}", dir.display()))?;
+        let user_id = dir.file_name().and_then(|s| s.to_str()).unwrap_or("recording").to_string();
+        let start_date = date_of(rows[0].ts_unix_sec)?.to_string();
+        let session_id = match &mut sqlite {
+            Some(conn) => {
+                let session_id = sqlite::insert_user_session(conn, &user_id, scheme.as_str(), &start_date, 0)?;
+                for chunk in rows.chunks(CHUNK_ROWS) {
+                    SensorBatchWriter::new(conn, session_id).write(chunk)?;
+                }
+                session_id
+            }
+            None => i as i64 + 1,
+        };
+        if args.backends.contains(&Backend::Parquet) {
+            let path = args.out.join(format!("sensor_data_{}_{}.parquet", session_id, user_id));
+            let mut writer = SensorParquetWriter::create(path)?;
+            writer.write_all(session_id, &rows)?;
+            writer.close()?;
+        }
+        if let Some(dataset) = &dataset {
+            dataset.write(&user_id, session_id, &rows)?;
         }
+        println!("{:<28} session {:>4}: {} rows from {}", user_id, session_id, rows.len(), start_date);
     }
     Ok(())
 }
+
+/// Seconds between output rows for `rate` Hz.
+fn sample_stride(rate: f64) -> anyhow::Result<u64> {
+    let stride = (1.0 / rate).round();
+    if !(rate > 0.0 && rate <= 1.0) || ((1.0 / rate) - stride).abs() > 1e-6 {
+        bail!("--sample-rate must be 1 or 1/N Hz for a whole number N, got {}", rate);
+    }
+    Ok(stride as u64)
+}
+
+impl Run<'_> {
+    /// Simulate `user` and stream its rows to every backend; returns the rows written.
+    fn user(&self, user: &SyntheticUser, session_id: i64) -> anyhow::Result<usize> {
+        let mut sim = user.simulator(self.start_date, self.scenario)?;
+        let sim_start_ts = sim.ts_ms / 1000;
+        let mut parquet = if self.parquet {
+            let path = self.out.join(format!("sensor_data_{}_{}.parquet", session_id, user.user_id));
+            Some(SensorParquetWriter::create(path)?)
+        } else {
+            None
+        };
+
+        // Chunks bound memory for SQLite and Parquet; the dataset gets one part file per day
+        let mut chunk = Vec::with_capacity(CHUNK_ROWS);
+        let mut day = Vec::new();
+        let mut rows = 0;
+        let mut flush = |chunk: &mut Vec<SensorDataRow>| -> anyhow::Result<()> {
+            if let Some(conn) = self.sqlite {
+                SensorBatchWriter::new(&mut conn.lock().expect("sqlite lock"), session_id).write(chunk)?;
+            }
+            if let Some(writer) = &mut parquet {
+                writer.write_all(session_id, chunk)?;
+            }
+            rows += chunk.len();
+            chunk.clear();
+            Ok(())
+        };
+        for row in sensor_rows(&mut sim, self.seconds, self.stride) {
+            if let Some(dataset) = self.dataset {
+                if day.first().is_some_and(|d: &SensorDataRow| !same_day(d.ts_unix_sec, row.ts_unix_sec)) {
+                    dataset.write(&user.user_id, session_id, &day)?;
+                    day.clear();
+                }
+                day.push(row.clone());
+            }
+            chunk.push(row);
+            if chunk.len() == CHUNK_ROWS {
+                flush(&mut chunk)?;
+            }
+        }
+        flush(&mut chunk)?;
+        if let (Some(dataset), false) = (self.dataset, day.is_empty()) {
+            dataset.write(&user.user_id, session_id, &day)?;
+        }
+        if let Some(writer) = parquet {
+            writer.close()?;
+        }
+
+        // Ground-truth labels for the injected anomalies that overlap the simulated span
+        if let Some(scenario) = self.scenario {
+            let sim_end_ts = sim_start_ts + self.seconds as i64;
+            let events: Vec<_> = scenario
+                .event_rows(sim_start_ts)
+                .into_iter()
+                .filter(|e| e.start_ts <= sim_end_ts && e.end_ts > sim_start_ts)
+                .collect();
+            if let Some(conn) = self.sqlite {
+                sqlite::insert_events(&mut conn.lock().expect("sqlite lock"), session_id, &events)?;
+            }
+            if self.parquet || self.dataset.is_some() {
+                let path = self.out.join(format!("events_{}_{}.parquet", session_id, user.user_id));
+                write_events_parquet(&path, session_id, &events)?;
+            }
+        }
+        println!("{:<28} session {:>4}: {} rows", user.user_id, session_id, rows);
+        Ok(rows)
+    }
+}
+
+fn same_day(a: i64, b: i64) -> bool {
+    a.div_euclid(SECS_PER_DAY as i64) == b.div_euclid(SECS_PER_DAY as i64)
+}
@@ -0,0 +1,118 @@
+//! Signal dynamics: HR on/off kinetics, HRV–HR coupling, thermal inertia and AR(1) noise.
+
+use data_layer::profile::FitnessLevel;
+use rand::Rng;
+use rand_distr::StandardNormal;
+
+/// First-order lag: moves toward its target with time constant `tau_secs`.
+#[derive(Debug, Clone, Copy)]
+pub struct FirstOrder {
+    pub value: f32,
+}
+
+impl FirstOrder {
+    pub fn new(value: f32) -> Self {
+        Self { value }
+    }
+
+    /// Advance by `dt` seconds toward `target` and return the new value.
+    pub fn step(&mut self, target: f32, tau_secs: f32, dt: f32) -> f32 {
+        self.value += (target - self.value) * (1.0 - (-dt / tau_secs).exp());
+        self.value
+    }
+}
+
+/// Time constants (seconds) of the two-component HR response.
+#[derive(Debug, Clone, Copy)]
+pub struct HrKineticsParams {
+    /// Share of the response carried by the slow component.
+    pub slow_share: f32,
+    pub fast_on: f32,
+    pub fast_off: f32,
+    pub slow_on: f32,
+    pub slow_off: f32,
+}
+
+impl HrKineticsParams {
+    /// Fitter users recover faster; onset kinetics are the same for everyone.
+    pub fn for_fitness(level: FitnessLevel) -> Self {
+        let (fast_off, slow_off) = match level {
+            FitnessLevel::Sedentary => (95.0, 600.0),
+            FitnessLevel::Low => (80.0, 480.0),
+            FitnessLevel::Moderate => (65.0, 360.0),
+            FitnessLevel::Active => (55.0, 240.0),
+            FitnessLevel::VeryActive => (45.0, 180.0),
+        };
+        Self { slow_share: 0.5, fast_on: 15.0, fast_off, slow_on: 90.0, slow_off }
+    }
+}
+
+/// HR deviation from resting, following its target through a fast and a slow component.
+///
+/// Each component uses its on-constant while rising and its off-constant while falling,
+/// which gives a quick rise at exercise onset and a biphasic recovery afterwards: a steep
+/// first-minute drop followed by a slow tail.
+#[derive(Debug, Clone, Copy)]
+pub struct HrKinetics {
+    pub params: HrKineticsParams,
+    fast: FirstOrder,
+    slow: FirstOrder,
+}
+
+impl HrKinetics {
+    pub fn new(params: HrKineticsParams) -> Self {
+        Self { params, fast: FirstOrder::new(0.0), slow: FirstOrder::new(0.0) }
+    }
+
+    /// Current HR deviation from resting (bpm).
+    pub fn value(&self) -> f32 {
+        self.fast.value + self.slow.value
+    }
+
+    /// Advance by `dt` seconds toward `target` (bpm above resting) and return the new deviation.
+    pub fn step(&mut self, target: f32, dt: f32) -> f32 {
+        let p = self.params;
+        let fast_target = target * (1.0 - p.slow_share);
+        let slow_target = target * p.slow_share;
+        let fast_tau = if fast_target > self.fast.value { p.fast_on } else { p.fast_off };
+        let slow_tau = if slow_target > self.slow.value { p.slow_on } else { p.slow_off };
+        self.fast.step(fast_target, fast_tau, dt);
+        self.slow.step(slow_target, slow_tau, dt);
+        self.value()
+    }
+}
+
+/// HRV multiplier for an HR `deviation` above resting: 1 at rest, 0.1 at max HR.
+pub fn hrv_coupling(deviation: f32, resting_hr: f32, max_hr: f32) -> f32 {
+    let k = 10f32.ln() / (max_hr - resting_hr).max(1.0);
+    (-k * deviation.max(0.0)).exp()
+}
+
+/// Unit-variance Gaussian AR(1) noise: `x' = φx + √(1−φ²)·ε`.
+#[derive(Debug, Clone, Copy)]
+pub struct Ar1 {
+    pub phi: f32,
+    x: f32,
+}
+
+impl Ar1 {
+    pub fn new(phi: f32) -> Self {
+        Self { phi, x: 0.0 }
+    }
+
+    /// Next value, given the innovation `eps` ~ N(0, 1).
+    pub fn sample_with(&mut self, eps: f32) -> f32 {
+        self.x = self.phi * self.x + (1.0 - self.phi * self.phi).sqrt() * eps;
+        self.x
+    }
+
+    /// Next value, drawing the innovation from `rng`.
+    pub fn sample(&mut self, rng: &mut impl Rng) -> f32 {
+        self.sample_with(gaussian(rng))
+    }
+}
+
+/// Standard normal draw.
+pub fn gaussian(rng: &mut impl Rng) -> f32 {
+    rng.sample(StandardNormal)
+}
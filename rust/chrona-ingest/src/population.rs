@@ -0,0 +1,126 @@
+//! Synthetic populations: several users per archetype, each with their own physiology.
+
+use crate::simulator::SensorSimulator;
+use data_layer::profile::UserProfile;
+use data_layer::query::SensorDataRow;
+use data_layer::scenario::Scenario;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use rand_distr::StandardNormal;
+
+/// One simulated user: an archetype's profile with individual physiology.
+#[derive(Debug, Clone)]
+pub struct SyntheticUser {
+    /// `<archetype>-<index>`, e.g. `pro_athlete-003`.
+    pub user_id: String,
+    /// Name of the profile the user was drawn from.
+    pub archetype: String,
+    /// Index of the user within its archetype.
+    pub index: usize,
+    pub profile: UserProfile,
+    /// Session seed: drives both the profile randomization and the simulation.
+    pub seed: u64,
+}
+
+impl SyntheticUser {
+    /// Draw user `index` of `archetype`. User 0 keeps the archetype's parameters exactly;
+    /// the others get randomized physiology derived from `seed`.
+    pub fn new(archetype: &str, base: &UserProfile, index: usize, seed: u64) -> Self {
+        let profile = if index == 0 { base.clone() } else { randomize_profile(base, seed) };
+        Self {
+            user_id: format!("{}-{:03}", archetype, index),
+            archetype: archetype.to_string(),
+            index,
+            profile,
+            seed,
+        }
+    }
+
+    /// Simulator for this user starting at midnight UTC of `start_date` (YYYY-MM-DD).
+    pub fn simulator(&self, start_date: &str, scenario: Option<&Scenario>) -> anyhow::Result<SensorSimulator> {
+        let sim = SensorSimulator::for_session(self.profile.clone(), start_date, self.seed as i64)?;
+        Ok(match scenario {
+            Some(scenario) => sim.with_scenario(scenario),
+            None => sim,
+        })
+    }
+}
+
+/// `users_per_archetype` users for each `(name, profile)` archetype, in archetype order.
+///
+/// Each user's seed depends only on `seed`, the archetype's position and the user index,
+/// so adding users to a run leaves the existing ones unchanged.
+pub fn plan_population(archetypes: &[(String, UserProfile)], users_per_archetype: usize, seed: u64) -> Vec<SyntheticUser> {
+    archetypes
+        .iter()
+        .enumerate()
+        .flat_map(|(a, (name, profile))| {
+            (0..users_per_archetype).map(move |i| SyntheticUser::new(name, profile, i, user_seed(seed, a, i)))
+        })
+        .collect()
+}
+
+/// SplitMix64-style mix of the base seed with archetype and user indices.
+fn user_seed(seed: u64, archetype: usize, index: usize) -> u64 {
+    let mut z = seed
+        ^ (archetype as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+        ^ (index as u64).wrapping_mul(0xD1B5_4A32_D192_ED03);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Perturb `base`'s physiological baselines and sleep timing around the archetype.
+///
+/// Schedules, exercise habits and fitness level are kept, so the user still behaves like
+/// the archetype. The result always passes [`UserProfile::validate`].
+pub fn randomize_profile(base: &UserProfile, seed: u64) -> UserProfile {
+    // Separate stream from the simulation noise drawn with the same seed
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    rng.set_stream(2);
+    let mut normal = || rng.sample::<f32, _>(StandardNormal);
+    let mut p = base.clone();
+
+    p.age = (p.age as f32 + 4.0 * normal()).round().clamp(18.0, 90.0) as u8;
+    p.max_hr = (base.max_hr - 0.7 * (p.age as f32 - base.age as f32) + 5.0 * normal()).clamp(140.0, 220.0);
+    p.resting_hr = (base.resting_hr + 4.0 * normal()).clamp(38.0, p.max_hr - 60.0);
+    p.hrv_baseline = (base.hrv_baseline * (0.2 * normal()).exp()).clamp(10.0, 150.0);
+    p.baseline_temp = (base.baseline_temp + 0.25 * normal()).clamp(31.0, 37.5);
+    p.baseline_eda = (base.baseline_eda * (0.3 * normal()).exp()).max(0.05);
+    p.stress_sensitivity = (base.stress_sensitivity + 0.1 * normal()).clamp(0.0, 1.0);
+    p.circadian.hr_amplitude_bpm = (base.circadian.hr_amplitude_bpm * (0.2 * normal()).exp()).clamp(0.0, 20.0);
+
+    let sleep_start = base.typical_sleep_start_min as f32 + 20.0 * normal();
+    p.typical_sleep_start_min = (sleep_start.round() as i32).rem_euclid(1440) as u16;
+    p.typical_sleep_duration_min =
+        (base.typical_sleep_duration_min as f32 + 20.0 * normal()).round().clamp(240.0, 720.0) as u16;
+    p
+}
+
+/// Rows produced by stepping `sim` once per second for `seconds`, keeping every `stride`-th.
+///
+/// Seconds inside a dropout yield no row.
+pub fn sensor_rows(sim: &mut SensorSimulator, seconds: u64, stride: u64) -> impl Iterator<Item = SensorDataRow> + '_ {
+    (1..=seconds).filter_map(move |i| {
+        let readings = sim.poll_all();
+        if readings.is_empty() || i % stride != 0 {
+            return None;
+        }
+        let mut row = SensorDataRow {
+            ts_unix_sec: sim.ts_ms / 1000,
+            hr: 0.0,
+            hrv_rmssd: 0.0,
+            eda_mus: 0.0,
+            temp_c: 0.0,
+            accel_mag_g: 0.0,
+            activity: sim.activity.as_str().to_string(),
+            stress_level: sim.stress.level,
+            exercise_flag: sim.exercise_flag,
+            sleep_stage: sim.sleep_stage.map(|s| s.as_str().to_string()),
+        };
+        for reading in &readings {
+            row.set_value(reading.kind, reading.value);
+        }
+        Some(row)
+    })
+}
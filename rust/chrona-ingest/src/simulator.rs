@@ -1,61 +1,312 @@
 //! Synthetic sensor simulator driven by UserProfile and schedule.
 
-use data_layer::profile::{UserProfile, activity_hr_boost, activity_intensity, activity_accel_mean, activity_accel_noise};
-use core_types::Sample;
-use rand::Rng;
+use data_layer::scenario::Scenario;
+use data_layer::profile::{ActivityKind, SleepStage, UserProfile, activity_hr_boost, activity_intensity, activity_accel_mean, activity_accel_noise, sleep_stage_signature, stress_hr_boost};
+use core_types::{Reading, SignalKind};
+use sensors::Sensor;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use time::{Date, Month};
+use crate::anomaly::{AnomalyEffects, AnomalyInjector};
+use crate::circadian;
+use crate::exercise::{ExercisePlanner, ExerciseSession};
+use crate::physiology::{gaussian, hrv_coupling, Ar1, FirstOrder, HrKinetics, HrKineticsParams};
+use crate::sleep::{resolve_activity, SleepPlanner, SleepWindow, StagePlan};
+use crate::stress::StressProcess;
 
 pub struct SensorSimulator {
     pub profile: UserProfile,
     pub ts_ms: i64,
     pub minute_of_day: u16,
+    pub sleep: SleepPlanner,
+    /// Ground-truth sleep stage of the latest poll (`None` while awake).
+    pub sleep_stage: Option<SleepStage>,
+    /// Ground-truth activity of the latest poll.
+    pub activity: ActivityKind,
+    /// True while a planned exercise session (including warm-up and cool-down) is running.
+    pub exercise_flag: bool,
+    pub exercise: ExercisePlanner,
+    pub stress: StressProcess,
+    /// Scenario anomalies to inject, if any.
+    pub anomalies: Option<AnomalyInjector>,
+    seed: u64,
+    /// Noise and stress-event draws, on their own stream of the seed.
+    rng: ChaCha8Rng,
+    /// HR above resting, following activity, stress and sleep with on/off kinetics.
+    hr: HrKinetics,
+    /// Skin temperature, lagging behind its target (`None` until the first poll).
+    temp: Option<FirstOrder>,
+    hr_noise: Ar1,
+    hrv_noise: Ar1,
+    temp_noise: Ar1,
+    eda_noise: Ar1,
+    /// Planned sessions of the cached week.
+    exercise_cache: Option<(i64, Vec<ExerciseSession>)>,
+    /// Sleep windows of the nights around the cached UTC day.
+    sleep_cache: Option<(i64, [SleepWindow; 3])>,
+    /// Stage plan of the ongoing sleep period.
+    stage_plan: Option<StagePlan>,
 }
 
 impl SensorSimulator {
+    /// ChaCha stream for per-second draws, kept apart from the planners' per-night/week streams.
+    const NOISE_STREAM: u64 = 1;
+    /// Floor for simulated HR (bpm).
+    const MIN_HR_BPM: f32 = 30.0;
+    /// Time constant of skin temperature (seconds).
+    const TEMP_TAU_SECS: f32 = 600.0;
+
     /// Create a new SensorSimulator starting at the given timestamp (ms since epoch).
-    pub fn new(profile: UserProfile, start_ts_ms: i64) -> Self {
+    ///
+    /// `seed` drives the night-to-night sleep jitter, sleep architecture and exercise plan.
+    pub fn new(profile: UserProfile, start_ts_ms: i64, seed: u64) -> Self {
         // Compute minute of day from timestamp
         let minute_of_day = ((start_ts_ms / 60000) % 1440) as u16;
+        let sleep = SleepPlanner::from_profile(&profile, seed);
+        let exercise = ExercisePlanner::from_profile(&profile, seed);
+        let stress = StressProcess::new(&profile);
+        let hr = HrKinetics::new(HrKineticsParams::for_fitness(profile.fitness_level));
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        rng.set_stream(Self::NOISE_STREAM);
         Self {
             profile,
             ts_ms: start_ts_ms,
             minute_of_day,
+            sleep,
+            sleep_stage: None,
+            activity: ActivityKind::Sitting,
+            exercise_flag: false,
+            exercise,
+            stress,
+            anomalies: None,
+            seed,
+            rng,
+            hr,
+            temp: None,
+            hr_noise: Ar1::new(0.9),
+            hrv_noise: Ar1::new(0.8),
+            temp_noise: Ar1::new(0.99),
+            eda_noise: Ar1::new(0.95),
+            exercise_cache: None,
+            sleep_cache: None,
+            stage_plan: None,
+        }
+    }
+
+    /// Simulator for a stored session: starts at midnight UTC of `simulation_date`
+    /// (`YYYY-MM-DD`) and draws everything from the session's `seed`.
+    pub fn for_session(profile: UserProfile, simulation_date: &str, seed: i64) -> anyhow::Result<Self> {
+        let parts: Vec<&str> = simulation_date.split('-').collect();
+        let [year, month, day] = parts[..] else {
+            anyhow::bail!("invalid simulation date {:?}, expected YYYY-MM-DD", simulation_date);
+        };
+        let date = Date::from_calendar_date(year.parse()?, Month::try_from(month.parse::<u8>()?)?, day.parse()?)?;
+        let start_ts_ms = date.midnight().assume_utc().unix_timestamp() * 1000;
+        Ok(Self::new(profile, start_ts_ms, seed as u64))
+    }
+
+    /// Inject `scenario`'s anomalies, with event offsets counted from the current time.
+    pub fn with_scenario(mut self, scenario: &Scenario) -> Self {
+        self.anomalies = Some(AnomalyInjector::new(scenario, self.ts_ms / 1000));
+        self
+    }
+
+    /// True if `ts_unix_sec` falls inside one of the planned sleep windows.
+    ///
+    /// Windows may start the previous day (late bedtimes) or cross midnight, so the
+    /// previous, current and next nights are all checked.
+    fn in_sleep_window(&mut self, ts_unix_sec: i64) -> bool {
+        let day = ts_unix_sec.div_euclid(86_400);
+        let windows = match self.sleep_cache {
+            Some((cached, windows)) if cached == day => windows,
+            _ => {
+                let windows = [day - 1, day, day + 1].map(|night| self.sleep.window_for_night(night));
+                self.sleep_cache = Some((day, windows));
+                windows
+            }
+        };
+        windows.iter().any(|w| w.contains(ts_unix_sec))
+    }
+
+    /// Planned exercise session running at `ts_unix_sec`, if any.
+    fn exercise_session(&mut self, ts_unix_sec: i64) -> Option<ExerciseSession> {
+        let week = ExercisePlanner::week_of(ts_unix_sec);
+        if !matches!(&self.exercise_cache, Some((cached, _)) if *cached == week) {
+            self.exercise_cache = Some((week, self.exercise.sessions_for_week(week)));
         }
+        let (_, sessions) = self.exercise_cache.as_ref()?;
+        sessions.iter().find(|s| s.contains(ts_unix_sec)).copied()
     }
 
+    /// Activity at the current time: the day's schedule with planned exercise overlaid,
+    /// reconciled with the sleep window (see [`resolve_activity`]).
+    pub fn current_activity(&mut self) -> ActivityKind {
+        let ts = self.ts_ms / 1000;
+        let scheduled = match self.exercise_session(ts) {
+            Some(session) => session.kind,
+            None => {
+                // Weekday or weekend template, depending on the simulated date
+                let kind = self.profile.activity_schedule.activity_at(ts);
+                // Training slots the planner didn't use this week
+                if kind.is_exercise() { ActivityKind::Sitting } else { kind }
+            }
+        };
+        let fixed = self.profile.activity_schedule.is_fixed_at(ts);
+        let asleep = self.in_sleep_window(ts);
+        resolve_activity(scheduled, fixed, asleep)
+    }
+
+    /// Sleep stage for the current time, starting a new stage plan at each sleep onset.
+    fn update_sleep_stage(&mut self, activity: ActivityKind) -> Option<SleepStage> {
+        if activity != ActivityKind::Sleep {
+            self.stage_plan = None;
+            return None;
+        }
+        let ts = self.ts_ms / 1000;
+        let seed = self.seed;
+        let plan = self.stage_plan.get_or_insert_with(|| StagePlan::new(ts, seed));
+        Some(plan.stage_at(ts))
+    }
+
+    /// Signals produced by [`SensorSimulator::poll_all`].
+    pub const CHANNELS: [SignalKind; 5] = [
+        SignalKind::HeartRate,
+        SignalKind::HrvRmssd,
+        SignalKind::Eda,
+        SignalKind::SkinTemp,
+        SignalKind::AccelMag,
+    ];
+
     /// Poll all signals for the current time step (HR, HRV, EDA, temp, accel).
-    pub fn poll_all(&mut self) -> Vec<Sample> {
+    ///
+    /// Returns no samples while a dropout anomaly is active; the simulated state still advances.
+    pub fn poll_all(&mut self) -> Vec<Reading> {
         // Advance time by 1 second
         self.ts_ms += 1000;
         self.minute_of_day = ((self.ts_ms / 60000) % 1440) as u16;
 
-        let activity = self.profile.activity_schedule.activity_at_minute(self.minute_of_day);
-        let mut rng = rand::rng();
-
-        // HR
-        let hr = self.profile.resting_hr + activity_hr_boost(activity, self.profile.resting_hr, self.profile.max_hr)
-            + rng.random_range(-2.0..2.0); // add small noise
-        // HRV
-        let intensity = activity_intensity(activity);
-        let hrv = self.profile.hrv_baseline * (1.0 - 0.5 * intensity)
-            + rng.random_range(-3.0..3.0);
-        // EDA
-        let eda = self.profile.baseline_eda + self.profile.stress_sensitivity * intensity
-            + rng.random_range(-0.05..0.05);
-        // Temp
-        let temp = self.profile.baseline_temp - 0.2 * intensity
-            + rng.random_range(-0.05..0.05);
-        // Accel
-        let accel_mean = activity_accel_mean(activity);
-        let accel_noise = activity_accel_noise(activity);
-        let accel = accel_mean + rng.random_range(-accel_noise..accel_noise);
+        let activity = self.current_activity();
+        self.activity = activity;
+        // Sessions overlapping the sleep window are cut short by sleep
+        let session = self.exercise_session(self.ts_ms / 1000).filter(|_| activity != ActivityKind::Sleep);
+        self.exercise_flag = session.is_some();
+        self.sleep_stage = self.update_sleep_stage(activity);
+        let rng = &mut self.rng;
+
+        // Monday–Friday, for weekday-only stressors (day 0 was a Thursday)
+        let weekday = (self.ts_ms.div_euclid(86_400_000) + 3).rem_euclid(7) < 5;
+        self.stress.step(rng, self.minute_of_day, weekday, self.sleep_stage.is_some());
+        let stress = self.stress.level;
+
+        // Circadian baselines, phase-locked to the profile's typical sleep
+        let secs_asleep = self.stage_plan.as_ref().map(|p| self.ts_ms / 1000 - p.onset());
+        let minute = (self.ts_ms.rem_euclid(86_400_000)) as f32 / 60_000.0;
+        let circ = circadian::offsets(&self.profile, minute, secs_asleep);
+        let asleep = self.sleep_stage.is_some();
+        let fx = self
+            .anomalies
+            .as_ref()
+            .map_or_else(AnomalyEffects::default, |a| a.effects_at(self.ts_ms / 1000, asleep));
+        let resting_hr = self.profile.resting_hr + circ.hr_bpm + fx.resting_hr_bpm;
+        let baseline_temp = self.profile.baseline_temp + circ.skin_temp_c;
+        let baseline_eda = self.profile.baseline_eda * circ.eda_factor;
+
+        // Warm-up and cool-down blend from an easy walk into the session's activity
+        let effort = session.map_or(1.0, |s| s.effort_at(self.ts_ms / 1000));
+        let blend = |f: &dyn Fn(ActivityKind) -> f32| {
+            let easy = f(ActivityKind::WalkingSlow);
+            easy + (f(activity) - easy) * effort
+        };
+        let (rest, max) = (self.profile.resting_hr, self.profile.max_hr);
+        let intensity = blend(&activity_intensity);
+
+        // Targets each signal would settle at if the current state persisted
+        let (hr_target, hrv_factor, temp_target, accel_mean, accel_noise, hr_sigma);
+        if let Some(stage) = self.sleep_stage {
+            // Stage-specific HR, HRV, temperature and movement signature
+            let sig = sleep_stage_signature(stage);
+            hr_target = sig.hr_delta;
+            hrv_factor = sig.hrv_factor;
+            temp_target = baseline_temp + sig.temp_delta;
+            accel_mean = sig.accel_mean;
+            accel_noise = sig.accel_noise;
+            hr_sigma = sig.hr_noise;
+        } else {
+            hr_target = blend(&|k| activity_hr_boost(k, rest, max)) + stress_hr_boost(stress);
+            // Stress suppresses HRV beyond what its HR rise accounts for
+            hrv_factor = 1.0 - 0.4 * stress;
+            temp_target = baseline_temp - 0.2 * intensity;
+            accel_mean = blend(&activity_accel_mean);
+            accel_noise = blend(&activity_accel_noise);
+            hr_sigma = 2.0;
+        }
+        let hr_target = hr_target + fx.hr_target_bpm;
+        let hrv_factor = hrv_factor * fx.hrv_factor;
+        let temp_target = temp_target + fx.temp_c;
+        // Noise amplitudes are ± ranges; a uniform ±a has σ = a/√3
+        let to_sigma = 3f32.sqrt().recip();
+
+        let deviation = self.hr.step(hr_target, 1.0);
+        let hr_eps = self.hr_noise.sample(rng);
+        let mut hr = resting_hr + deviation + hr_sigma * to_sigma * hr_eps;
+
+        // HRV falls as HR rises, and its noise mirrors HR's so the two swing in opposition
+        let hrv_eps = -0.5 * hr_eps + 0.75f32.sqrt() * self.hrv_noise.sample(rng);
+        let mut hrv = (self.profile.hrv_baseline * hrv_factor * hrv_coupling(deviation, rest, max)
+            + 3.0 * to_sigma * hrv_eps)
+            .max(1.0);
+
+        // Skin temperature has minutes of thermal inertia
+        let temp_state = self.temp.get_or_insert(FirstOrder::new(temp_target));
+        let temp = temp_state.step(temp_target, Self::TEMP_TAU_SECS, 1.0)
+            + 0.05 * to_sigma * self.temp_noise.sample(rng);
+
+        let mut accel = (accel_mean + accel_noise * to_sigma * gaussian(rng)).max(0.0);
+
+        // EDA: tonic level rises with exertion and stress, plus phasic responses
+        let mut eda = baseline_eda + self.profile.stress_sensitivity * intensity
+            + 0.8 * stress
+            + self.stress.phasic_eda
+            + 0.05 * to_sigma * self.eda_noise.sample(rng);
+
+        // Irregular RR intervals scatter beat-to-beat HR and inflate RMSSD
+        if fx.irregular_rr {
+            hr += 0.12 * hr * gaussian(rng);
+            hrv = 60.0 + 40.0 * gaussian(rng).abs();
+        }
+        // Motion corrupts optical HR and EDA contact, and spikes the accelerometer
+        if fx.motion > 0.0 {
+            hr += 20.0 * fx.motion * gaussian(rng);
+            eda += 0.5 * fx.motion * gaussian(rng).abs();
+            accel += 2.0 * fx.motion * gaussian(rng).abs();
+        }
+        // Stacked anomalies on a low resting HR must not push HR below what a heart can sustain
+        let hr = hr.max(Self::MIN_HR_BPM);
+        if fx.dropout {
+            return Vec::new();
+        }
 
         let ts = self.ts_ms;
         vec![
-            Sample { ts_ms: ts, value: hr },
-            Sample { ts_ms: ts, value: hrv },
-            Sample { ts_ms: ts, value: eda },
-            Sample { ts_ms: ts, value: temp },
-            Sample { ts_ms: ts, value: accel },
+            Reading::new(SignalKind::HeartRate, ts, hr),
+            Reading::new(SignalKind::HrvRmssd, ts, hrv),
+            Reading::new(SignalKind::Eda, ts, eda),
+            Reading::new(SignalKind::SkinTemp, ts, temp),
+            Reading::new(SignalKind::AccelMag, ts, accel),
         ]
     }
 }
+
+impl Sensor for SensorSimulator {
+    fn name(&self) -> &str {
+        "chrona_simulator"
+    }
+
+    fn channels(&self) -> &[SignalKind] {
+        &Self::CHANNELS
+    }
+
+    fn poll(&mut self) -> Vec<Reading> {
+        self.poll_all()
+    }
+}
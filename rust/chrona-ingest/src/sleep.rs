@@ -0,0 +1,130 @@
+//! Nightly sleep windows derived from a profile's typical bedtime and duration.
+
+use data_layer::profile::{ActivityKind, SleepStage, UserProfile};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+const SECS_PER_DAY: i64 = 86_400;
+
+/// One night's sleep, as Unix seconds `[start, end)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SleepWindow {
+    pub start: i64,
+    pub end: i64,
+}
+
+impl SleepWindow {
+    pub fn contains(&self, ts_unix_sec: i64) -> bool {
+        ts_unix_sec >= self.start && ts_unix_sec < self.end
+    }
+}
+
+/// Plans one sleep window per night with seeded bedtime/duration jitter.
+///
+/// Night `n` is the one whose nominal bedtime falls on UTC day `n`. Each night's jitter
+/// depends only on the seed and `n`, so windows don't change with polling order.
+#[derive(Debug, Clone)]
+pub struct SleepPlanner {
+    start_min: u16,
+    duration_min: u16,
+    jitter_min: u16,
+    seed: u64,
+}
+
+impl SleepPlanner {
+    pub fn from_profile(profile: &UserProfile, seed: u64) -> Self {
+        Self {
+            start_min: profile.typical_sleep_start_min,
+            duration_min: profile.typical_sleep_duration_min,
+            jitter_min: profile.sleep_jitter_min,
+            seed,
+        }
+    }
+
+    /// Sleep window for night `night` (UTC day index of the nominal bedtime).
+    pub fn window_for_night(&self, night: i64) -> SleepWindow {
+        let mut rng = ChaCha8Rng::seed_from_u64(self.seed ^ (night as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+        // Triangular jitter: most nights land near the typical bedtime
+        let jitter = self.jitter_min as f64 * 60.0;
+        let bedtime_shift = (rng.random::<f64>() + rng.random::<f64>() - 1.0) * jitter;
+        let duration_shift = (rng.random::<f64>() + rng.random::<f64>() - 1.0) * jitter * 0.5;
+
+        let start = night * SECS_PER_DAY + self.start_min as i64 * 60 + bedtime_shift as i64;
+        let duration = (self.duration_min as f64 * 60.0 + duration_shift).max(3_600.0);
+        SleepWindow { start, end: start + duration as i64 }
+    }
+}
+
+/// Reconcile the scheduled activity with the sleep window.
+///
+/// The window decides sleep: inside it the user sleeps unless a fixed commitment
+/// (`fixed`, e.g. a shift or school run) keeps them up; outside it, scheduled Sleep is
+/// spent awake in bed.
+pub fn resolve_activity(scheduled: ActivityKind, fixed: bool, in_sleep_window: bool) -> ActivityKind {
+    match scheduled {
+        _ if in_sleep_window && !fixed => ActivityKind::Sleep,
+        ActivityKind::Sleep => ActivityKind::Sitting,
+        kind => kind,
+    }
+}
+
+/// Sleep architecture for one sleep period: ~90-minute cycles of light, deep and REM.
+///
+/// Early cycles are deep-heavy and later ones REM-heavy, and cycles may end in a brief
+/// awakening. Interrupted sleep starts a new plan, so cycles restart after each wake-up.
+#[derive(Debug, Clone)]
+pub struct StagePlan {
+    onset: i64,
+    /// `(end offset from onset in seconds, stage)`, ascending.
+    spans: Vec<(i64, SleepStage)>,
+}
+
+impl StagePlan {
+    /// Longest sleep period planned for; later times stay in the final stage.
+    const MAX_SECS: i64 = 16 * 3_600;
+
+    /// Plan stages for a sleep period starting at `onset` (Unix seconds).
+    pub fn new(onset: i64, seed: u64) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed ^ (onset as u64).wrapping_mul(0xD1B5_4A32_D192_ED03));
+        let mut spans = Vec::new();
+        let mut end = 0;
+        let mut push = |secs: f64, stage: SleepStage, end: &mut i64| {
+            let secs = secs as i64;
+            if secs > 0 {
+                *end += secs;
+                spans.push((*end, stage));
+            }
+        };
+        let mut cycle = 0;
+        while end < Self::MAX_SECS {
+            let length = rng.random_range(80.0..105.0) * 60.0;
+            let deep = (0.40 - 0.12 * cycle as f64).max(0.0);
+            let rem = (0.15 + 0.08 * cycle as f64).min(0.45);
+            let light = 1.0 - deep - rem;
+            push(length * light * 0.6, SleepStage::Light, &mut end);
+            push(length * deep, SleepStage::Deep, &mut end);
+            push(length * light * 0.4, SleepStage::Light, &mut end);
+            push(length * rem, SleepStage::Rem, &mut end);
+            if rng.random_bool((0.15 + 0.05 * cycle as f64).min(0.4)) {
+                push(rng.random_range(1.0..4.0) * 60.0, SleepStage::Wake, &mut end);
+            }
+            cycle += 1;
+        }
+        Self { onset, spans }
+    }
+
+    /// Sleep onset this plan was built for.
+    pub fn onset(&self) -> i64 {
+        self.onset
+    }
+
+    /// Stage at `ts_unix_sec` (at or after onset).
+    pub fn stage_at(&self, ts_unix_sec: i64) -> SleepStage {
+        let offset = ts_unix_sec - self.onset;
+        self.spans
+            .iter()
+            .find(|(end, _)| offset < *end)
+            .or(self.spans.last())
+            .map_or(SleepStage::Light, |(_, stage)| *stage)
+    }
+}
@@ -0,0 +1,177 @@
+//! Stochastic stress process: event-driven stress level and phasic EDA responses.
+
+use data_layer::profile::{ProfileType, UserProfile};
+use rand::Rng;
+use StressEventKind::*;
+
+/// Kind of stressor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StressEventKind {
+    Commute,
+    Meeting,
+    Deadline,
+    Social,
+}
+
+/// How often and how hard one kind of stressor hits a profile type.
+#[derive(Debug, Clone, Copy)]
+pub struct StressEventSpec {
+    pub kind: StressEventKind,
+    /// Expected events per hour inside the window.
+    pub rate_per_hour: f32,
+    /// Minute-of-day window `[start, end)` in which the event can start.
+    pub window: (u16, u16),
+    /// Only on Monday–Friday.
+    pub weekdays_only: bool,
+    /// Stress added while active, scaled by `0.5 + stress_sensitivity`.
+    pub amplitude: (f32, f32),
+    /// Duration range in minutes.
+    pub duration_min: (f32, f32),
+    /// Recovery time constant after the event ends (minutes).
+    pub recovery_min: f32,
+}
+
+const fn spec(
+    kind: StressEventKind,
+    rate_per_hour: f32,
+    window: (u16, u16),
+    weekdays_only: bool,
+    amplitude: (f32, f32),
+    duration_min: (f32, f32),
+    recovery_min: f32,
+) -> StressEventSpec {
+    StressEventSpec { kind, rate_per_hour, window, weekdays_only, amplitude, duration_min, recovery_min }
+}
+
+/// Rush-hour commutes, daytime meetings and occasional deadlines.
+const BUSINESS_PROFESSIONAL: &[StressEventSpec] = &[
+    spec(Commute, 0.8, (420, 540), true, (0.25, 0.45), (20.0, 45.0), 15.0),
+    spec(Commute, 0.8, (1020, 1140), true, (0.2, 0.4), (20.0, 45.0), 15.0),
+    spec(Meeting, 0.6, (540, 1020), true, (0.15, 0.35), (30.0, 60.0), 10.0),
+    spec(Deadline, 0.06, (600, 1260), true, (0.4, 0.6), (60.0, 180.0), 45.0),
+];
+
+/// Classes, exam/assignment crunches and late social evenings.
+const COLLEGE_STUDENT: &[StressEventSpec] = &[
+    spec(Meeting, 0.3, (540, 1020), true, (0.1, 0.25), (50.0, 90.0), 10.0),
+    spec(Deadline, 0.05, (600, 1440), false, (0.4, 0.7), (60.0, 240.0), 60.0),
+    spec(Social, 0.1, (1080, 1440), false, (0.1, 0.3), (30.0, 120.0), 20.0),
+];
+
+/// Team meetings and rare competition pressure.
+const PRO_ATHLETE: &[StressEventSpec] = &[
+    spec(Meeting, 0.1, (600, 960), true, (0.1, 0.2), (30.0, 60.0), 10.0),
+    spec(Deadline, 0.01, (600, 1200), false, (0.3, 0.5), (60.0, 120.0), 30.0),
+];
+
+/// Commutes around a 20:00–04:00 shift and workload peaks during it.
+const SHIFT_WORKER: &[StressEventSpec] = &[
+    spec(Commute, 0.8, (1140, 1200), false, (0.2, 0.4), (20.0, 40.0), 15.0),
+    spec(Commute, 0.8, (240, 300), false, (0.2, 0.4), (20.0, 40.0), 15.0),
+    spec(Deadline, 0.15, (1200, 1440), false, (0.35, 0.6), (30.0, 120.0), 40.0),
+    spec(Deadline, 0.15, (0, 240), false, (0.35, 0.6), (30.0, 120.0), 40.0),
+];
+
+/// No commute, but frequent video calls.
+const REMOTE_WORKER: &[StressEventSpec] = &[
+    spec(Meeting, 0.5, (540, 1020), true, (0.1, 0.25), (20.0, 60.0), 10.0),
+    spec(Deadline, 0.04, (540, 1140), true, (0.3, 0.5), (60.0, 180.0), 40.0),
+];
+
+/// Stressors by profile archetype.
+pub fn event_specs(profile_type: ProfileType) -> &'static [StressEventSpec] {
+    match profile_type {
+        ProfileType::BusinessProfessional => BUSINESS_PROFESSIONAL,
+        ProfileType::CollegeStudent => COLLEGE_STUDENT,
+        ProfileType::ProAthlete => PRO_ATHLETE,
+        ProfileType::ShiftWorker => SHIFT_WORKER,
+        ProfileType::RemoteWorker => REMOTE_WORKER,
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ActiveEvent {
+    kind: StressEventKind,
+    amplitude: f32,
+    remaining_secs: f32,
+    recovery_secs: f32,
+}
+
+/// Stress level in [0, 1] driven by stochastic events, with phasic EDA responses.
+#[derive(Debug, Clone)]
+pub struct StressProcess {
+    specs: &'static [StressEventSpec],
+    sensitivity: f32,
+    /// Current stress level (0.0–1.0).
+    pub level: f32,
+    active: Vec<ActiveEvent>,
+    /// Recovery time constant of the most recently ended event (seconds).
+    recovery_secs: f32,
+    /// Phasic skin-conductance response (μS) on top of tonic EDA.
+    pub phasic_eda: f32,
+}
+
+impl StressProcess {
+    /// Resting level while awake, before events.
+    const TONIC_AWAKE: f32 = 0.1;
+    /// Rise time constant towards the event-driven target (seconds).
+    const RISE_SECS: f32 = 120.0;
+    /// Decay time constant of a skin-conductance response (seconds).
+    const SCR_DECAY_SECS: f32 = 6.0;
+
+    pub fn new(profile: &UserProfile) -> Self {
+        Self {
+            specs: event_specs(profile.profile_type),
+            sensitivity: profile.stress_sensitivity,
+            level: Self::TONIC_AWAKE,
+            active: Vec::new(),
+            recovery_secs: 15.0 * 60.0,
+            phasic_eda: 0.0,
+        }
+    }
+
+    /// Advance one second. No new events start while asleep.
+    pub fn step(&mut self, rng: &mut impl Rng, minute_of_day: u16, weekday: bool, asleep: bool) {
+        let dt = 1.0;
+        if !asleep {
+            for spec in self.specs {
+                let (start, end) = spec.window;
+                let in_window = minute_of_day >= start && minute_of_day < end;
+                if in_window
+                    && (weekday || !spec.weekdays_only)
+                    && !self.active.iter().any(|e| e.kind == spec.kind)
+                    && rng.random::<f32>() < spec.rate_per_hour * dt / 3600.0
+                {
+                    let amplitude = rng.random_range(spec.amplitude.0..spec.amplitude.1) * (0.5 + self.sensitivity);
+                    self.active.push(ActiveEvent {
+                        kind: spec.kind,
+                        amplitude,
+                        remaining_secs: rng.random_range(spec.duration_min.0..spec.duration_min.1) * 60.0,
+                        recovery_secs: spec.recovery_min * 60.0,
+                    });
+                    // Event onset triggers a startle-like skin-conductance response
+                    self.phasic_eda += 0.3 + 0.7 * amplitude;
+                }
+            }
+        }
+        for event in &mut self.active {
+            event.remaining_secs -= dt;
+            if event.remaining_secs <= 0.0 {
+                self.recovery_secs = event.recovery_secs;
+            }
+        }
+        self.active.retain(|e| e.remaining_secs > 0.0);
+
+        let tonic = if asleep { 0.0 } else { Self::TONIC_AWAKE * (0.5 + self.sensitivity) };
+        let target = (tonic + self.active.iter().map(|e| e.amplitude).sum::<f32>()).min(1.0);
+        let tau = if target > self.level { Self::RISE_SECS } else { self.recovery_secs };
+        self.level = (self.level + (target - self.level) * dt / tau).clamp(0.0, 1.0);
+
+        // Non-specific skin-conductance responses: ~1/min at rest, several per minute under stress
+        self.phasic_eda *= (-dt / Self::SCR_DECAY_SECS).exp();
+        let scr_per_min = if asleep { 0.3 } else { 1.0 + 6.0 * self.level };
+        if rng.random::<f32>() < scr_per_min * dt / 60.0 {
+            self.phasic_eda += rng.random_range(0.05..0.2) * (1.0 + self.level);
+        }
+    }
+}
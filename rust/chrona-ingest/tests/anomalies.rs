@@ -0,0 +1,184 @@
+use core_types::Reading;
+use chrona_ingest::anomaly::AnomalyInjector;
+use chrona_ingest::simulator::SensorSimulator;
+use data_layer::profile::{presets, UserProfile};
+use data_layer::scenario::Scenario;
+
+const DATE: &str = "2025-12-17";
+
+/// Per-second samples for minutes `[from_min, to_min)`, `None` during dropouts.
+fn run(scenario: Option<&str>, from_min: usize, to_min: usize) -> Vec<Option<[f32; 5]>> {
+    run_profile(presets::business_professional(), scenario, from_min, to_min)
+}
+
+fn run_profile(profile: UserProfile, scenario: Option<&str>, from_min: usize, to_min: usize) -> Vec<Option<[f32; 5]>> {
+    let mut sim = SensorSimulator::for_session(profile, DATE, 21).unwrap();
+    if let Some(toml) = scenario {
+        sim = sim.with_scenario(&Scenario::from_toml_str(toml).unwrap());
+    }
+    let mut rows = Vec::new();
+    for i in 0..to_min * 60 {
+        let s = sim.poll_all();
+        if i >= from_min * 60 {
+            rows.push((!s.is_empty()).then(|| SensorSimulator::CHANNELS.map(|k| Reading::find(&s, k).unwrap())));
+        }
+    }
+    rows
+}
+
+fn mean(rows: &[Option<[f32; 5]>], channel: usize) -> f32 {
+    let values: Vec<f32> = rows.iter().flatten().map(|r| r[channel]).collect();
+    values.iter().sum::<f32>() / values.len() as f32
+}
+
+fn sd(rows: &[Option<[f32; 5]>], channel: usize) -> f32 {
+    let m = mean(rows, channel);
+    let values: Vec<f32> = rows.iter().flatten().map(|r| r[channel]).collect();
+    (values.iter().map(|v| (v - m).powi(2)).sum::<f32>() / values.len() as f32).sqrt()
+}
+
+#[test]
+fn tachycardia_raises_hr_inside_its_window_only() {
+    let scenario = r#"
+        [[events]]
+        kind = "tachycardia"
+        start_min = 600
+        duration_min = 20
+        extra_bpm = 40.0
+    "#;
+    let (base, sick) = (run(None, 590, 640), run(Some(scenario), 590, 640));
+    // Minutes 605..620, once HR has caught up
+    let during = 15 * 60..30 * 60;
+    let rise = mean(&sick[during.clone()], 0) - mean(&base[during], 0);
+    assert!(rise > 30.0 && rise < 50.0, "HR rise {rise}");
+    // Sample i is taken at start + i + 1 seconds, so the window opens at index 599
+    assert_eq!(sick[..599], base[..599]);
+    assert_ne!(sick[599], base[599]);
+}
+
+#[test]
+fn dropout_suppresses_samples() {
+    let scenario = r#"
+        [[events]]
+        kind = "dropout"
+        start_min = 5
+        duration_min = 2
+    "#;
+    let rows = run(Some(scenario), 0, 10);
+    let missing: Vec<usize> = rows.iter().enumerate().filter(|(_, r)| r.is_none()).map(|(i, _)| i).collect();
+    // Samples at ts = start + i + 1; the window is [300, 420) seconds
+    assert_eq!(missing.len(), 120);
+    assert_eq!((missing[0], missing[119]), (299, 418));
+}
+
+#[test]
+fn fever_raises_skin_temperature_and_hr() {
+    let scenario = r#"
+        [[events]]
+        kind = "fever"
+        start_min = 600
+        duration_min = 240
+        peak_c = 1.5
+    "#;
+    let (base, sick) = (run(None, 690, 750), run(Some(scenario), 690, 750));
+    let temp_rise = mean(&sick, 3) - mean(&base, 3);
+    assert!(temp_rise > 1.0 && temp_rise < 1.6, "temp rise {temp_rise}");
+    assert!(mean(&sick, 0) - mean(&base, 0) > 8.0);
+}
+
+#[test]
+fn atrial_fibrillation_scatters_hr_and_inflates_hrv() {
+    let scenario = r#"
+        [[events]]
+        kind = "atrial_fibrillation"
+        start_min = 600
+        duration_min = 30
+    "#;
+    let (base, sick) = (run(None, 610, 630), run(Some(scenario), 610, 630));
+    assert!(sd(&sick, 0) > 3.0 * sd(&base, 0), "HR sd {} vs {}", sd(&sick, 0), sd(&base, 0));
+    assert!(mean(&sick, 1) > 60.0 && mean(&sick, 1) > mean(&base, 1));
+}
+
+#[test]
+fn sleep_bradycardia_only_lowers_hr_while_asleep() {
+    let scenario = r#"
+        [[events]]
+        kind = "sleep_bradycardia"
+        start_min = 60
+        duration_min = 960
+        drop_bpm = 15.0
+    "#;
+    // 02:00–03:00 asleep, 14:00–15:00 awake
+    let night = mean(&run(None, 120, 180), 0) - mean(&run(Some(scenario), 120, 180), 0);
+    assert!(night > 12.0 && night < 18.0, "night drop {night}");
+    let day = mean(&run(None, 840, 900), 0) - mean(&run(Some(scenario), 840, 900), 0);
+    assert!(day.abs() < 0.5, "day drop {day}");
+}
+
+#[test]
+fn stacked_bradycardia_on_low_resting_hr_is_floored() {
+    let scenario = r#"
+        [[events]]
+        kind = "sleep_bradycardia"
+        start_min = 60
+        duration_min = 300
+        drop_bpm = 40.0
+    "#;
+    let mut profile = presets::business_professional();
+    profile.resting_hr = 40.0;
+    // 02:00–03:00 asleep: the HR target sits below zero
+    let rows = run_profile(profile, Some(scenario), 120, 180);
+    let hr: Vec<f32> = rows.iter().flatten().map(|r| r[0]).collect();
+    let min = hr.iter().copied().fold(f32::MAX, f32::min);
+    assert_eq!(min, 30.0, "floor not reached or breached: min {min}");
+    assert!(hr.iter().all(|&v| v >= 30.0));
+}
+
+#[test]
+fn illness_raises_resting_hr_and_lowers_hrv() {
+    let scenario = r#"
+        [[events]]
+        kind = "illness"
+        start_min = 600
+        duration_min = 240
+        resting_hr_rise_bpm = 10.0
+    "#;
+    // Plateau between the quarter-length ramps: minutes 660..780
+    let (base, sick) = (run(None, 690, 750), run(Some(scenario), 690, 750));
+    let rise = mean(&sick, 0) - mean(&base, 0);
+    assert!(rise > 7.0 && rise < 13.0, "HR rise {rise}");
+    assert!(mean(&sick, 1) < 0.9 * mean(&base, 1), "HRV {} vs {}", mean(&sick, 1), mean(&base, 1));
+
+    let injector = AnomalyInjector::new(&Scenario::from_toml_str(scenario).unwrap(), 0);
+    let plateau = injector.effects_at(720 * 60, false);
+    assert_eq!((plateau.resting_hr_bpm, plateau.hrv_factor), (10.0, 0.8));
+    // Half way up the 60-minute ramp
+    assert_eq!(injector.effects_at(630 * 60, false).resting_hr_bpm, 5.0);
+    assert_eq!(injector.effects_at(840 * 60, false).resting_hr_bpm, 0.0);
+}
+
+#[test]
+fn motion_artifact_corrupts_hr_eda_and_accel() {
+    let scenario = r#"
+        [[events]]
+        kind = "motion_artifact"
+        start_min = 600
+        duration_min = 30
+        severity = 1.0
+
+        [[events]]
+        kind = "motion_artifact"
+        start_min = 610
+        duration_min = 5
+        severity = 0.2
+    "#;
+    let (base, sick) = (run(None, 605, 625), run(Some(scenario), 605, 625));
+    assert!(sd(&sick, 0) > 3.0 * sd(&base, 0), "HR sd {} vs {}", sd(&sick, 0), sd(&base, 0));
+    assert!(mean(&sick, 2) > mean(&base, 2), "EDA");
+    assert!(mean(&sick, 4) > mean(&base, 4) + 1.0, "accel {} vs {}", mean(&sick, 4), mean(&base, 4));
+
+    // Overlapping events keep the worst severity
+    let injector = AnomalyInjector::new(&Scenario::from_toml_str(scenario).unwrap(), 0);
+    assert_eq!(injector.effects_at(612 * 60, false).motion, 1.0);
+    assert_eq!(injector.effects_at(640 * 60, false).motion, 0.0);
+}
@@ -0,0 +1,62 @@
+use chrona_ingest::circadian::offsets;
+use data_layer::profile::{presets, CircadianParams};
+
+fn close(a: f32, b: f32) -> bool {
+    (a - b).abs() < 1e-4
+}
+
+#[test]
+fn hr_and_skin_temp_swing_around_the_trough() {
+    let profile = presets::business_professional();
+    let CircadianParams { hr_amplitude_bpm: hr, skin_temp_amplitude_c: temp, eda_amplitude: eda, .. } = profile.circadian;
+    // Asleep 22:30 to 06:00, so the trough is at 04:00 and the peak twelve hours later
+    assert_eq!(profile.circadian_trough_min(), 240);
+
+    let nadir = offsets(&profile, 240.0, None);
+    assert!(close(nadir.hr_bpm, -hr) && close(nadir.skin_temp_c, temp) && close(nadir.eda_factor, 1.0 - eda));
+    let peak = offsets(&profile, 960.0, None);
+    assert!(close(peak.hr_bpm, hr) && close(peak.skin_temp_c, -temp) && close(peak.eda_factor, 1.0 + eda));
+    // Halfway between, the rhythm crosses the baseline
+    let between = offsets(&profile, 600.0, None);
+    assert!(close(between.hr_bpm, 0.0) && close(between.skin_temp_c, 0.0) && close(between.eda_factor, 1.0));
+
+    // Every minute of the day stays within the amplitudes, HR and skin temperature in antiphase
+    for minute in 0..1440 {
+        let o = offsets(&profile, minute as f32, None);
+        assert!(o.hr_bpm.abs() <= hr + 1e-4 && o.skin_temp_c.abs() <= temp + 1e-4);
+        assert!(close(o.hr_bpm / hr, -o.skin_temp_c / temp), "minute {minute}");
+    }
+}
+
+#[test]
+fn trough_follows_the_typical_sleep_window() {
+    let mut profile = presets::business_professional();
+    // Asleep 08:00 to 16:00 after a night shift
+    profile.typical_sleep_start_min = 480;
+    profile.typical_sleep_duration_min = 480;
+    assert_eq!(profile.circadian_trough_min(), 840);
+    assert!(close(offsets(&profile, 840.0, None).hr_bpm, -profile.circadian.hr_amplitude_bpm));
+
+    // Waking just after midnight wraps the trough into the previous day
+    profile.typical_sleep_start_min = 1200;
+    profile.typical_sleep_duration_min = 300;
+    assert_eq!(profile.circadian_trough_min(), 1380);
+    assert!(close(offsets(&profile, 1380.0, None).skin_temp_c, profile.circadian.skin_temp_amplitude_c));
+}
+
+#[test]
+fn sleep_onset_warms_the_skin_and_saturates() {
+    let profile = presets::business_professional();
+    let rise = profile.circadian.sleep_onset_temp_rise_c;
+    let awake = offsets(&profile, 60.0, None);
+    let asleep = |secs| offsets(&profile, 60.0, Some(secs));
+
+    assert!(close(asleep(0).skin_temp_c, awake.skin_temp_c));
+    let warming: Vec<_> = (0..=240).map(|m| asleep(m * 60).skin_temp_c - awake.skin_temp_c).collect();
+    assert!(warming.windows(2).all(|w| w[1] > w[0]));
+    // 40-minute time constant: about two thirds of the rise after 40 minutes, nearly all after four hours
+    assert!(close(warming[40], rise * (1.0 - (-1.0f32).exp())));
+    assert!(warming[60] > 0.75 * rise && warming[240] < rise && warming[240] > 0.99 * rise);
+    // Only skin temperature depends on sleep
+    assert_eq!((asleep(3600).hr_bpm, asleep(3600).eda_factor), (awake.hr_bpm, awake.eda_factor));
+}
@@ -0,0 +1,62 @@
+use chrona_ingest::exercise::ExercisePlanner;
+use chrona_ingest::simulator::SensorSimulator;
+use data_layer::profile::{presets, ExerciseType};
+
+const DAY: i64 = 86_400;
+
+#[test]
+fn each_week_plans_exercise_frequency_per_week_sessions() {
+    for name in ["business_professional", "college_student", "pro_athlete", "shift_worker", "remote_worker"] {
+        let profile = presets::by_name(name).unwrap();
+        for seed in [1, 9] {
+            let planner = ExercisePlanner::from_profile(&profile, seed);
+            for week in 2900..2910 {
+                let sessions = planner.sessions_for_week(week);
+                assert_eq!(sessions.len(), profile.exercise_frequency_per_week, "{name} week {week}");
+                for s in &sessions {
+                    assert!(ExerciseType::from_activity(s.kind).is_some() && s.start < s.end, "{name}: {s:?}");
+                    assert_eq!(ExercisePlanner::week_of(s.start), week, "{name}: {s:?}");
+                }
+                assert!(sessions.windows(2).all(|w| w[0].end <= w[1].start), "{name} week {week}: {sessions:?}");
+                assert_eq!(planner.sessions_for_week(week), sessions);
+            }
+        }
+    }
+}
+
+#[test]
+fn warmup_and_cooldown_ramps_are_monotonic() {
+    let planner = ExercisePlanner::from_profile(&presets::pro_athlete(), 4);
+    for session in (2900..2904).flat_map(|week| planner.sessions_for_week(week)) {
+        let effort: Vec<_> = (session.start..session.end).map(|ts| session.effort_at(ts)).collect();
+        let (warmup, cooldown) = (session.warmup_secs as usize, session.cooldown_secs as usize);
+        assert!((300..=600).contains(&warmup) && (300..=600).contains(&cooldown), "{session:?}");
+        assert!(effort[0] < 0.01 && effort[effort.len() - 1] < 0.01);
+
+        assert!(effort[..=warmup].windows(2).all(|w| w[1] > w[0]), "{session:?}");
+        assert!(effort[warmup..effort.len() - cooldown].iter().all(|&e| e == 1.0), "{session:?}");
+        assert!(effort[effort.len() - cooldown..].windows(2).all(|w| w[1] < w[0]), "{session:?}");
+    }
+}
+
+#[test]
+fn exercise_flag_is_set_only_inside_sessions() {
+    let profile = presets::business_professional();
+    let mut sim = SensorSimulator::for_session(profile.clone(), "2025-12-15", 6).unwrap();
+    let planner = ExercisePlanner::from_profile(&profile, 6);
+    let sessions = planner.sessions_for_week(ExercisePlanner::week_of(sim.ts_ms / 1000 + 1));
+
+    let mut flagged = 0;
+    for _ in 0..7 * DAY {
+        sim.poll_all();
+        let ts = sim.ts_ms / 1000;
+        let session = sessions.iter().find(|s| s.contains(ts));
+        assert_eq!(sim.exercise_flag, session.is_some(), "at {ts}");
+        if let Some(session) = session {
+            assert_eq!(sim.activity, session.kind, "at {ts}");
+            flagged += 1;
+        }
+    }
+    let planned: i64 = sessions.iter().map(|s| s.end - s.start).sum();
+    assert_eq!(flagged, planned);
+}
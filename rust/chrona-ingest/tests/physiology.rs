@@ -0,0 +1,92 @@
+use core_types::{Reading, SignalKind};
+use chrona_ingest::physiology::{hrv_coupling, Ar1, FirstOrder, HrKinetics, HrKineticsParams};
+use chrona_ingest::simulator::SensorSimulator;
+use data_layer::profile::{presets, FitnessLevel};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+/// HR deviation each second while holding `target` for `secs`.
+fn hold(hr: &mut HrKinetics, target: f32, secs: usize) -> Vec<f32> {
+    (0..secs).map(|_| hr.step(target, 1.0)).collect()
+}
+
+#[test]
+fn hr_rises_quickly_and_recovers_biphasically() {
+    let mut hr = HrKinetics::new(HrKineticsParams::for_fitness(FitnessLevel::Moderate));
+    let rise = hold(&mut hr, 100.0, 900);
+    assert!(rise.windows(2).all(|w| w[1] >= w[0]));
+    assert!(rise[0] < 10.0, "no instant jump: {}", rise[0]);
+    assert!(rise[59] > 55.0 && rise[59] < 90.0, "one minute in: {}", rise[59]);
+    assert!(rise[899] > 97.0);
+
+    let peak = hr.value();
+    let recovery = hold(&mut hr, 0.0, 900);
+    assert!(recovery.windows(2).all(|w| w[1] <= w[0]));
+    let first_minute_drop = peak - recovery[59];
+    assert!(first_minute_drop > 20.0 && first_minute_drop < rise[59], "HRR1: {first_minute_drop}");
+    // Steep first minute, then a slow tail
+    assert!(first_minute_drop > 4.0 * (recovery[299] - recovery[359]));
+    assert!(recovery[899] > 1.0);
+}
+
+#[test]
+fn fitter_users_recover_faster() {
+    let after_two_minutes = |level| {
+        let mut hr = HrKinetics::new(HrKineticsParams::for_fitness(level));
+        hold(&mut hr, 100.0, 1800);
+        hold(&mut hr, 0.0, 120)[119]
+    };
+    assert!(after_two_minutes(FitnessLevel::VeryActive) < after_two_minutes(FitnessLevel::Sedentary) - 10.0);
+}
+
+#[test]
+fn hrv_is_inversely_coupled_to_hr() {
+    assert_eq!(hrv_coupling(0.0, 60.0, 190.0), 1.0);
+    assert_eq!(hrv_coupling(-8.0, 60.0, 190.0), 1.0);
+    assert!((hrv_coupling(130.0, 60.0, 190.0) - 0.1).abs() < 1e-4);
+    let curve: Vec<f32> = (0..=130).map(|d| hrv_coupling(d as f32, 60.0, 190.0)).collect();
+    assert!(curve.windows(2).all(|w| w[1] < w[0]));
+}
+
+#[test]
+fn temperature_has_thermal_inertia() {
+    let mut temp = FirstOrder::new(33.0);
+    let trace: Vec<f32> = (0..1800).map(|_| temp.step(34.0, 600.0, 1.0)).collect();
+    assert!(trace.windows(2).all(|w| w[1] > w[0] && w[1] < 34.0));
+    assert!((trace[599] - (34.0 - (-1.0f32).exp())).abs() < 1e-3);
+}
+
+#[test]
+fn ar1_noise_has_unit_variance_and_lag_one_correlation() {
+    let mut rng = ChaCha8Rng::seed_from_u64(3);
+    let mut noise = Ar1::new(0.9);
+    let xs: Vec<f64> = (0..200_000).map(|_| noise.sample(&mut rng) as f64).collect();
+    let n = xs.len() as f64;
+    let mean = xs.iter().sum::<f64>() / n;
+    let var = xs.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n;
+    let lag1 = xs.windows(2).map(|w| (w[0] - mean) * (w[1] - mean)).sum::<f64>() / n / var;
+    assert!(mean.abs() < 0.05, "mean {mean}");
+    assert!((var - 1.0).abs() < 0.05, "variance {var}");
+    assert!((lag1 - 0.9).abs() < 0.02, "lag-1 correlation {lag1}");
+}
+
+#[test]
+fn simulated_day_is_continuous_and_hrv_tracks_hr_inversely() {
+    let mut sim = SensorSimulator::for_session(presets::pro_athlete(), "2025-12-17", 9).unwrap();
+    let (mut hr, mut hrv) = (Vec::new(), Vec::new());
+    for _ in 0..86_400 {
+        let s = sim.poll_all();
+        hr.push(Reading::find(&s, SignalKind::HeartRate).unwrap() as f64);
+        hrv.push(Reading::find(&s, SignalKind::HrvRmssd).unwrap() as f64);
+    }
+    let max_jump = hr.windows(2).map(|w| (w[1] - w[0]).abs()).fold(0.0, f64::max);
+    assert!(max_jump < 10.0, "HR jumped {max_jump} bpm in one second");
+    assert!(hr.iter().cloned().fold(0.0, f64::max) > 150.0, "expected a hard session");
+
+    let n = hr.len() as f64;
+    let (mh, mv) = (hr.iter().sum::<f64>() / n, hrv.iter().sum::<f64>() / n);
+    let cov: f64 = hr.iter().zip(&hrv).map(|(h, v)| (h - mh) * (v - mv)).sum();
+    let sd = |xs: &[f64], m: f64| xs.iter().map(|x| (x - m).powi(2)).sum::<f64>().sqrt();
+    let corr = cov / (sd(&hr, mh) * sd(&hrv, mv));
+    assert!(corr < -0.6, "HR/HRV correlation {corr}");
+}
@@ -0,0 +1,61 @@
+use chrona_ingest::population::{plan_population, randomize_profile, sensor_rows};
+use data_layer::profile::presets;
+
+fn archetypes() -> Vec<(String, data_layer::profile::UserProfile)> {
+    vec![
+        ("pro_athlete".to_string(), presets::pro_athlete()),
+        ("shift_worker".to_string(), presets::shift_worker()),
+    ]
+}
+
+#[test]
+fn population_is_deterministic_and_stable_as_it_grows() {
+    let small = plan_population(&archetypes(), 2, 42);
+    let large = plan_population(&archetypes(), 5, 42);
+    let ids: Vec<_> = small.iter().map(|u| u.user_id.as_str()).collect();
+    assert_eq!(ids, ["pro_athlete-000", "pro_athlete-001", "shift_worker-000", "shift_worker-001"]);
+    assert_eq!(large.len(), 10);
+
+    // The first users of each archetype are unchanged when more are added
+    for user in &small {
+        let same = large.iter().find(|u| u.user_id == user.user_id).unwrap();
+        assert_eq!(same.seed, user.seed);
+        assert_eq!(same.profile.resting_hr, user.profile.resting_hr);
+    }
+    let seeds: std::collections::HashSet<_> = large.iter().map(|u| u.seed).collect();
+    assert_eq!(seeds.len(), large.len());
+    assert_ne!(plan_population(&archetypes(), 2, 43)[1].seed, small[1].seed);
+}
+
+#[test]
+fn randomized_profiles_vary_but_stay_valid() {
+    let base = presets::pro_athlete();
+    let users = plan_population(&[("pro_athlete".to_string(), base.clone())], 50, 7);
+    assert_eq!(users[0].profile.resting_hr, base.resting_hr);
+
+    let resting: Vec<f32> = users[1..].iter().map(|u| u.profile.resting_hr).collect();
+    let mean = resting.iter().sum::<f32>() / resting.len() as f32;
+    assert!((mean - base.resting_hr).abs() < 3.0, "mean resting HR {mean}");
+    assert!(resting.iter().any(|&r| r != resting[0]));
+    for user in &users {
+        user.profile.validate().unwrap();
+        assert_eq!(user.profile.exercise_frequency_per_week, base.exercise_frequency_per_week);
+    }
+    assert_eq!(randomize_profile(&base, 9).hrv_baseline, randomize_profile(&base, 9).hrv_baseline);
+}
+
+#[test]
+fn sensor_rows_follow_the_sample_stride() {
+    let user = &plan_population(&archetypes(), 1, 42)[0];
+    let mut sim = user.simulator("2025-12-19", None).unwrap();
+    let start = sim.ts_ms / 1000;
+    let rows: Vec<_> = sensor_rows(&mut sim, 600, 5).collect();
+    assert_eq!(rows.len(), 120);
+    assert_eq!(rows[0].ts_unix_sec, start + 5);
+    assert!(rows.windows(2).all(|w| w[1].ts_unix_sec - w[0].ts_unix_sec == 5));
+
+    // Same user, same rows
+    let mut again = user.simulator("2025-12-19", None).unwrap();
+    let every: Vec<_> = sensor_rows(&mut again, 600, 1).collect();
+    assert_eq!((every[4].ts_unix_sec, every[4].hr), (rows[0].ts_unix_sec, rows[0].hr));
+}
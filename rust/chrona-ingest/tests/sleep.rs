@@ -0,0 +1,139 @@
+use chrona_ingest::simulator::SensorSimulator;
+use chrona_ingest::sleep::{SleepPlanner, StagePlan};
+use data_layer::profile::{presets, ActivityKind, SleepStage, UserProfile};
+
+const DAY: i64 = 86_400;
+/// 2025-12-17T00:00:00Z
+const DATE0: i64 = 1_765_929_600;
+
+fn sleep_profile(start_min: u16, duration_min: u16, jitter_min: u16) -> UserProfile {
+    let mut profile = presets::business_professional();
+    profile.typical_sleep_start_min = start_min;
+    profile.typical_sleep_duration_min = duration_min;
+    profile.sleep_jitter_min = jitter_min;
+    profile
+}
+
+#[test]
+fn nightly_jitter_stays_within_sleep_jitter_min() {
+    let profile = sleep_profile(1350, 450, 40);
+    let jitter = 40 * 60;
+    for seed in [1, 7, 42] {
+        let planner = SleepPlanner::from_profile(&profile, seed);
+        let windows: Vec<_> = (0..365).map(|night| planner.window_for_night(night)).collect();
+        let shifts: Vec<_> = windows.iter().enumerate().map(|(n, w)| w.start - (n as i64 * DAY + 1350 * 60)).collect();
+        assert!(shifts.iter().all(|s| s.abs() <= jitter), "seed {seed}");
+        assert!(windows.iter().all(|w| (w.end - w.start - 450 * 60).abs() <= jitter / 2), "seed {seed}");
+        // Nights actually vary, and mostly stay near the typical bedtime
+        assert!(shifts.iter().any(|&s| s != shifts[0]));
+        assert!(shifts.iter().filter(|s| s.abs() <= jitter / 2).count() > 365 / 2);
+        // A night's window depends only on the seed and the night
+        assert_eq!(planner.window_for_night(100), SleepPlanner::from_profile(&profile, seed).window_for_night(100));
+    }
+
+    let fixed = SleepPlanner::from_profile(&sleep_profile(1350, 450, 0), 3).window_for_night(2);
+    assert_eq!((fixed.start, fixed.end), (2 * DAY + 1350 * 60, 2 * DAY + 1800 * 60));
+}
+
+#[test]
+fn windows_cross_midnight() {
+    // 23:00 for 8 hours, give or take 30 minutes
+    let planner = SleepPlanner::from_profile(&sleep_profile(1380, 480, 30), 11);
+    for night in 0..30 {
+        let window = planner.window_for_night(night);
+        let midnight = (night + 1) * DAY;
+        assert!(window.start < midnight && window.end > midnight, "night {night}: {window:?}");
+        assert!(window.contains(midnight) && window.contains(midnight + 3 * 3600));
+        assert!(!window.contains(window.end) && !window.contains(window.start - 1));
+    }
+
+    // The simulator picks up the previous night's window after midnight
+    let mut sim = SensorSimulator::new(sleep_profile(1380, 480, 30), (DATE0 + 20 * 3600) * 1000, 11);
+    let mut asleep = Vec::new();
+    for _ in 0..12 * 3600 {
+        sim.poll_all();
+        if sim.activity == ActivityKind::Sleep {
+            asleep.push(sim.ts_ms / 1000);
+        }
+    }
+    let window = sim.sleep.window_for_night(DATE0 / DAY);
+    let midnight = DATE0 + DAY;
+    assert!(asleep.iter().all(|&ts| window.contains(ts)));
+    assert!(asleep.contains(&(midnight - 1)) && asleep.contains(&midnight) && asleep.contains(&(midnight + 3600)));
+}
+
+#[test]
+fn stage_plan_cycles_last_about_ninety_minutes() {
+    for seed in [1, 2, 3] {
+        let plan = StagePlan::new(DATE0, seed);
+        let stages: Vec<_> = (0..8 * 60).map(|m| plan.stage_at(DATE0 + m * 60)).collect();
+        assert_eq!(stages[0], SleepStage::Light);
+        // A cycle ends when light sleep follows REM or an awakening
+        let starts: Vec<_> = (1..stages.len())
+            .filter(|&m| stages[m] == SleepStage::Light && matches!(stages[m - 1], SleepStage::Rem | SleepStage::Wake))
+            .collect();
+        let mut bounds = vec![0];
+        bounds.extend(&starts);
+        let lengths: Vec<_> = bounds.windows(2).map(|w| w[1] - w[0]).collect();
+        assert!((4..=6).contains(&lengths.len()), "seed {seed}: {lengths:?}");
+        assert!(lengths.iter().all(|l| (79..=110).contains(l)), "seed {seed}: {lengths:?}");
+        let mean = lengths.iter().sum::<usize>() as f64 / lengths.len() as f64;
+        assert!((80.0..=100.0).contains(&mean), "seed {seed}: {mean}");
+
+        // Deep sleep front-loaded, REM back-loaded
+        let count = |range: &[SleepStage], stage| range.iter().filter(|&&s| s == stage).count();
+        let (first, last) = (&stages[..bounds[1]], &stages[bounds[bounds.len() - 2]..bounds[bounds.len() - 1]]);
+        assert!(count(first, SleepStage::Deep) > count(last, SleepStage::Deep));
+        assert!(count(first, SleepStage::Rem) < count(last, SleepStage::Rem));
+    }
+}
+
+#[test]
+fn sleep_stage_is_written_only_while_asleep() {
+    let mut sim = SensorSimulator::for_session(presets::business_professional(), "2025-12-17", 5).unwrap();
+    let mut stages = 0;
+    for _ in 0..DAY {
+        sim.poll_all();
+        assert_eq!(sim.sleep_stage.is_some(), sim.activity == ActivityKind::Sleep, "at {}", sim.ts_ms / 1000);
+        stages += sim.sleep_stage.is_some() as usize;
+    }
+    // Early morning and late evening of the same day
+    assert!(stages > 4 * 3600 && stages < 10 * 3600, "{stages}");
+}
+
+/// Minutes labelled Sleep by the simulator over `nights` nights, sampled once a minute
+/// from noon on the first day.
+fn sleep_minutes(profile: UserProfile, nights: i64, seed: u64) -> Vec<i64> {
+    let start = DATE0 + DAY / 2;
+    let mut sim = SensorSimulator::new(profile, start * 1000, seed);
+    (0..nights * 1440)
+        .map(|m| start + m * 60)
+        .filter(|&ts| {
+            sim.ts_ms = ts * 1000;
+            sim.current_activity() == ActivityKind::Sleep
+        })
+        .collect()
+}
+
+#[test]
+fn average_sleep_matches_typical_duration() {
+    // Nine hours runs past the end of the schedule's Sleep blocks at 06:00
+    for (profile, seed) in [(sleep_profile(1350, 540, 25), 3), (presets::college_student(), 8), (presets::remote_worker(), 21)] {
+        let typical = profile.typical_sleep_duration_min as f64;
+        let nights = 60;
+        let mean = sleep_minutes(profile.clone(), nights, seed).len() as f64 / nights as f64;
+        assert!((mean - typical).abs() < typical * 0.02, "seed {seed}: {mean} vs {typical}");
+    }
+}
+
+#[test]
+fn fixed_blocks_keep_the_user_awake_inside_the_window() {
+    // Night shift until 04:00 and a school run 08:00–08:30, with 75 minutes of bedtime jitter
+    let profile = presets::shift_worker();
+    let asleep = sleep_minutes(profile.clone(), 30, 4);
+    let minute = |ts: i64| ts.rem_euclid(DAY) / 60;
+    assert!(asleep.iter().all(|&ts| minute(ts) >= 240 && !(480..510).contains(&minute(ts))));
+    // Sleep still fills the rest of the window, so nights are only shortened by the commitments
+    let mean = asleep.len() as f64 / 30.0;
+    assert!(mean > 250.0 && mean < profile.typical_sleep_duration_min as f64, "{mean}");
+}
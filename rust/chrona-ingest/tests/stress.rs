@@ -0,0 +1,96 @@
+use chrona_ingest::stress::StressProcess;
+use data_layer::profile::{presets, UserProfile};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+/// `(level, phasic_eda)` each second.
+type Trace = Vec<(f32, f32)>;
+
+/// Stress trace from `from_min` to `to_min`.
+fn run(
+    process: &mut StressProcess,
+    rng: &mut ChaCha8Rng,
+    from_min: u16,
+    to_min: u16,
+    weekday: bool,
+    asleep: bool,
+) -> Trace {
+    (from_min as u32 * 60..to_min as u32 * 60)
+        .map(|sec| {
+            process.step(rng, (sec / 60) as u16, weekday, asleep);
+            (process.level, process.phasic_eda)
+        })
+        .collect()
+}
+
+/// A working day from 07:00 to 21:00, then a night asleep.
+fn day(profile: &UserProfile, seed: u64) -> (Trace, Trace) {
+    let (mut process, mut rng) = (StressProcess::new(profile), ChaCha8Rng::seed_from_u64(seed));
+    let awake = run(&mut process, &mut rng, 420, 1260, true, false);
+    let night = [run(&mut process, &mut rng, 1260, 1440, true, true), run(&mut process, &mut rng, 0, 420, true, true)];
+    (awake, night.concat())
+}
+
+/// Phasic EDA added each second, net of the previous second's decay.
+fn eda_jumps(trace: &[(f32, f32)]) -> Vec<f32> {
+    trace.windows(2).map(|w| w[1].1 - w[0].1 * (-1.0f32 / 6.0).exp()).collect()
+}
+
+#[test]
+fn seeded_stress_stays_in_range_and_is_reproducible() {
+    for profile in [presets::business_professional(), presets::shift_worker(), presets::college_student()] {
+        for seed in 0..5 {
+            let (awake, night) = day(&profile, seed);
+            assert!(awake.iter().chain(&night).all(|&(level, eda)| (0.0..=1.0).contains(&level) && eda >= 0.0));
+            assert_eq!(day(&profile, seed), (awake, night));
+        }
+    }
+}
+
+#[test]
+fn stress_events_raise_the_level_and_then_decay() {
+    let profile = presets::business_professional();
+    let tonic = 0.1 * (0.5 + profile.stress_sensitivity);
+    for seed in 0..5 {
+        let (awake, night) = day(&profile, seed);
+        let peak = awake.iter().map(|s| s.0).fold(0.0, f32::max);
+        assert!(peak > tonic + 0.15, "seed {seed}: peak {peak}");
+
+        // No new events start while asleep; running ones end and the level relaxes towards zero
+        let last = night.last().unwrap().0;
+        assert!(last < 0.01, "seed {seed}: {last}");
+        let tail = &night[night.len() / 2..];
+        assert!(tail.windows(2).all(|w| w[1].0 <= w[0].0));
+    }
+
+    // Weekday-only stressors leave a weekend at the tonic level
+    let (mut process, mut rng) = (StressProcess::new(&profile), ChaCha8Rng::seed_from_u64(3));
+    let weekend = run(&mut process, &mut rng, 420, 1260, false, false);
+    assert!(weekend.iter().all(|s| s.0 <= tonic + 1e-6));
+    assert!((weekend.last().unwrap().0 - tonic).abs() < 1e-3);
+}
+
+#[test]
+fn phasic_eda_responds_to_stress_events() {
+    let profile = presets::business_professional();
+    let tonic = 0.1 * (0.5 + profile.stress_sensitivity);
+    let (mut process, mut rng) = (StressProcess::new(&profile), ChaCha8Rng::seed_from_u64(3));
+    let weekend = run(&mut process, &mut rng, 420, 1260, false, false);
+    // Spontaneous responses at rest stay small
+    assert!(eda_jumps(&weekend).iter().all(|&j| j < 0.3));
+
+    let mut onsets = 0;
+    for seed in 0..5 {
+        let (awake, _) = day(&profile, seed);
+        let jumps = eda_jumps(&awake);
+        // Each event onset fires a startle response while the event drives stress up
+        for (i, _) in jumps.iter().enumerate().filter(|&(_, &j)| j >= 0.3 + 0.7 * 0.15) {
+            onsets += 1;
+            let peak = awake[i..(i + 600).min(awake.len())].iter().map(|s| s.0).fold(0.0, f32::max);
+            assert!(peak > tonic + 0.1, "seed {seed} at {i}: {peak}");
+        }
+        let mean = |trace: &[(f32, f32)]| trace.iter().map(|s| s.1).sum::<f32>() / trace.len() as f32;
+        assert!(mean(&awake) > mean(&weekend), "seed {seed}");
+    }
+    assert!(onsets >= 5, "{onsets}");
+}
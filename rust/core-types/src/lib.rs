@@ -13,11 +13,155 @@ pub enum Tier {
     Pro32,
 }
 
+impl Tier {
+    /// Directory name for this tier's local storage under `data/`.
+    pub fn data_dir_name(&self) -> &'static str {
+        match self {
+            Tier::Mini8 => "mini",
+            Tier::Standard16 => "regular",
+            Tier::Pro32 => "pro",
+        }
+    }
+}
+
 /// A basic time-series sample.
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct Sample {
     /// Milliseconds since epoch.
     pub ts_ms: i64,
     /// Numeric value for the stream.
     pub value: f32,
 }
+
+/// Physical signal carried by a sample, with its unit.
+///
+/// Vector signals are split into one scalar channel per axis (e.g. [`SignalKind::AccelX`]),
+/// so every reading stays a single `f32`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignalKind {
+    /// Heart rate (bpm).
+    HeartRate,
+    /// Beat-to-beat (RR) interval (ms).
+    RrInterval,
+    /// Heart rate variability as RMSSD (ms).
+    HrvRmssd,
+    /// Electrodermal activity / skin conductance (µS).
+    Eda,
+    /// Skin temperature (°C).
+    SkinTemp,
+    /// Accelerometer X axis (g).
+    AccelX,
+    /// Accelerometer Y axis (g).
+    AccelY,
+    /// Accelerometer Z axis (g).
+    AccelZ,
+    /// Accelerometer magnitude (g).
+    AccelMag,
+    /// Blood oxygen saturation (%).
+    #[serde(rename = "spo2")]
+    SpO2,
+    /// Step count since the previous reading.
+    Steps,
+    /// Raw photoplethysmogram (arbitrary units).
+    Ppg,
+    /// Electrocardiogram lead voltage (mV).
+    Ecg,
+    /// Respiration rate (breaths/min).
+    Respiration,
+}
+
+impl SignalKind {
+    /// Every signal kind, in declaration order.
+    pub const ALL: [SignalKind; 14] = [
+        SignalKind::HeartRate,
+        SignalKind::RrInterval,
+        SignalKind::HrvRmssd,
+        SignalKind::Eda,
+        SignalKind::SkinTemp,
+        SignalKind::AccelX,
+        SignalKind::AccelY,
+        SignalKind::AccelZ,
+        SignalKind::AccelMag,
+        SignalKind::SpO2,
+        SignalKind::Steps,
+        SignalKind::Ppg,
+        SignalKind::Ecg,
+        SignalKind::Respiration,
+    ];
+
+    /// Stable snake_case name, used as the stream name in storage (same as the serde name).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SignalKind::HeartRate => "heart_rate",
+            SignalKind::RrInterval => "rr_interval",
+            SignalKind::HrvRmssd => "hrv_rmssd",
+            SignalKind::Eda => "eda",
+            SignalKind::SkinTemp => "skin_temp",
+            SignalKind::AccelX => "accel_x",
+            SignalKind::AccelY => "accel_y",
+            SignalKind::AccelZ => "accel_z",
+            SignalKind::AccelMag => "accel_mag",
+            SignalKind::SpO2 => "spo2",
+            SignalKind::Steps => "steps",
+            SignalKind::Ppg => "ppg",
+            SignalKind::Ecg => "ecg",
+            SignalKind::Respiration => "respiration",
+        }
+    }
+
+    /// Unit symbol for display.
+    pub fn unit(&self) -> &'static str {
+        match self {
+            SignalKind::HeartRate => "bpm",
+            SignalKind::RrInterval | SignalKind::HrvRmssd => "ms",
+            SignalKind::Eda => "µS",
+            SignalKind::SkinTemp => "°C",
+            SignalKind::AccelX | SignalKind::AccelY | SignalKind::AccelZ | SignalKind::AccelMag => "g",
+            SignalKind::SpO2 => "%",
+            SignalKind::Steps => "steps",
+            SignalKind::Ppg => "a.u.",
+            SignalKind::Ecg => "mV",
+            SignalKind::Respiration => "br/min",
+        }
+    }
+
+    /// Parse a name produced by [`SignalKind::as_str`].
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|k| k.as_str() == name)
+    }
+}
+
+impl std::fmt::Display for SignalKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A sample tagged with the signal it measures.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Reading {
+    /// Signal measured.
+    pub kind: SignalKind,
+    /// Milliseconds since epoch.
+    pub ts_ms: i64,
+    /// Value in the unit of `kind`.
+    pub value: f32,
+}
+
+impl Reading {
+    /// Reading of `kind` at `ts_ms`.
+    pub fn new(kind: SignalKind, ts_ms: i64, value: f32) -> Self {
+        Self { kind, ts_ms, value }
+    }
+
+    /// The untagged sample, as stored in the `kind` stream.
+    pub fn sample(&self) -> Sample {
+        Sample { ts_ms: self.ts_ms, value: self.value }
+    }
+
+    /// Value of the first reading of `kind` in `readings`, if any.
+    pub fn find(readings: &[Reading], kind: SignalKind) -> Option<f32> {
+        readings.iter().find(|r| r.kind == kind).map(|r| r.value)
+    }
+}
@@ -0,0 +1,27 @@
+use core_types::{Reading, SignalKind};
+
+#[test]
+fn signal_names_round_trip_and_are_unique() {
+    let mut names: Vec<&str> = SignalKind::ALL.iter().map(|k| k.as_str()).collect();
+    for kind in SignalKind::ALL {
+        assert_eq!(SignalKind::from_name(kind.as_str()), Some(kind));
+        assert_eq!(kind.to_string(), kind.as_str());
+        assert!(!kind.unit().is_empty());
+    }
+    names.sort();
+    names.dedup();
+    assert_eq!(names.len(), SignalKind::ALL.len());
+    assert_eq!(SignalKind::from_name("synthetic_hr"), None);
+}
+
+#[test]
+fn readings_are_found_by_kind() {
+    let readings = [
+        Reading::new(SignalKind::HeartRate, 1000, 62.0),
+        Reading::new(SignalKind::SkinTemp, 1000, 33.5),
+    ];
+    assert_eq!(Reading::find(&readings, SignalKind::SkinTemp), Some(33.5));
+    assert_eq!(Reading::find(&readings, SignalKind::Eda), None);
+    assert_eq!(readings[0].sample().value, 62.0);
+    assert_eq!(SignalKind::HeartRate.unit(), "bpm");
+}
@@ -0,0 +1,143 @@
+//! Error type shared by the SQLite, query and Parquet modules.
+
+use crate::migrations::MigrationError;
+use arrow::error::ArrowError;
+use parquet::errors::ParquetError;
+use std::fmt;
+
+/// Result alias used throughout the data layer.
+pub type Result<T> = std::result::Result<T, DataLayerError>;
+
+/// Errors raised by the data layer.
+#[derive(Debug)]
+pub enum DataLayerError {
+    /// A stored row could not be decoded (NULL or mistyped value).
+    Decode {
+        /// Table or file the row was read from.
+        origin: String,
+        /// Zero-based offset of the row within the scan.
+        row: usize,
+        /// What was wrong with the row.
+        reason: String,
+    },
+    /// A table or file does not have the expected columns.
+    SchemaMismatch {
+        /// Offending column.
+        column: String,
+        /// Expected column type.
+        expected: String,
+        /// Type found instead (`"missing"` if absent).
+        found: String,
+    },
+    /// Schema migration failed.
+    Migration(MigrationError),
+    /// SQLite error.
+    Sqlite(rusqlite::Error),
+    /// Filesystem error.
+    Io(std::io::Error),
+    /// Arrow error.
+    Arrow(ArrowError),
+    /// Parquet error.
+    Parquet(ParquetError),
+    /// Timestamp outside the range of representable dates.
+    Timestamp(time::error::ComponentRange),
+}
+
+impl DataLayerError {
+    /// Classify a rusqlite error raised while decoding row `row` of `origin`.
+    ///
+    /// Value conversion failures become [`DataLayerError::Decode`] and unknown columns
+    /// [`DataLayerError::SchemaMismatch`]; anything else is passed through as SQLite.
+    pub(crate) fn from_row_error(origin: &str, row: usize, e: rusqlite::Error) -> Self {
+        match e {
+            rusqlite::Error::InvalidColumnType(_, name, found) => DataLayerError::Decode {
+                origin: origin.to_string(),
+                row,
+                reason: format!("column {} has type {}", name, found),
+            },
+            rusqlite::Error::FromSqlConversionFailure(..) | rusqlite::Error::IntegralValueOutOfRange(..) => {
+                DataLayerError::Decode { origin: origin.to_string(), row, reason: e.to_string() }
+            }
+            rusqlite::Error::InvalidColumnName(name) => DataLayerError::SchemaMismatch {
+                column: name,
+                expected: "present".to_string(),
+                found: "missing".to_string(),
+            },
+            e => DataLayerError::Sqlite(e),
+        }
+    }
+
+    /// True for errors confined to a single row, which lenient queries skip.
+    pub fn is_row_error(&self) -> bool {
+        matches!(self, DataLayerError::Decode { .. })
+    }
+}
+
+impl fmt::Display for DataLayerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataLayerError::Decode { origin, row, reason } => {
+                write!(f, "failed to decode row {} of {}: {}", row, origin, reason)
+            }
+            DataLayerError::SchemaMismatch { column, expected, found } => {
+                write!(f, "schema mismatch for column {}: expected {}, found {}", column, expected, found)
+            }
+            DataLayerError::Migration(e) => write!(f, "{}", e),
+            DataLayerError::Sqlite(e) => write!(f, "sqlite error: {}", e),
+            DataLayerError::Io(e) => write!(f, "io error: {}", e),
+            DataLayerError::Arrow(e) => write!(f, "arrow error: {}", e),
+            DataLayerError::Parquet(e) => write!(f, "parquet error: {}", e),
+            DataLayerError::Timestamp(e) => write!(f, "invalid timestamp: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for DataLayerError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DataLayerError::Decode { .. } | DataLayerError::SchemaMismatch { .. } => None,
+            DataLayerError::Migration(e) => Some(e),
+            DataLayerError::Sqlite(e) => Some(e),
+            DataLayerError::Io(e) => Some(e),
+            DataLayerError::Arrow(e) => Some(e),
+            DataLayerError::Parquet(e) => Some(e),
+            DataLayerError::Timestamp(e) => Some(e),
+        }
+    }
+}
+
+impl From<MigrationError> for DataLayerError {
+    fn from(e: MigrationError) -> Self {
+        DataLayerError::Migration(e)
+    }
+}
+
+impl From<rusqlite::Error> for DataLayerError {
+    fn from(e: rusqlite::Error) -> Self {
+        DataLayerError::Sqlite(e)
+    }
+}
+
+impl From<std::io::Error> for DataLayerError {
+    fn from(e: std::io::Error) -> Self {
+        DataLayerError::Io(e)
+    }
+}
+
+impl From<ArrowError> for DataLayerError {
+    fn from(e: ArrowError) -> Self {
+        DataLayerError::Arrow(e)
+    }
+}
+
+impl From<ParquetError> for DataLayerError {
+    fn from(e: ParquetError) -> Self {
+        DataLayerError::Parquet(e)
+    }
+}
+
+impl From<time::error::ComponentRange> for DataLayerError {
+    fn from(e: time::error::ComponentRange) -> Self {
+        DataLayerError::Timestamp(e)
+    }
+}
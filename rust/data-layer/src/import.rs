@@ -0,0 +1,457 @@
+//! Import of multi-rate wrist recordings (WESAD, PPG-DaLiA) into `sensor_data` rows.
+//!
+//! Both datasets ship their wrist data in the Empatica E4 CSV export layout: one file per
+//! signal, whose first line is the start time (Unix seconds, repeated per column) and
+//! second line the sample rate (Hz), followed by one sample per line.
+//!
+//! | File | Rate | Content |
+//! | --- | --- | --- |
+//! | `BVP.csv` | 64 Hz | blood volume pulse (PPG) |
+//! | `EDA.csv` | 4 Hz | electrodermal activity (μS) |
+//! | `TEMP.csv` | 4 Hz | skin temperature (°C) |
+//! | `ACC.csv` | 32 Hz | X, Y, Z acceleration (1/64 g) |
+//! | `HR.csv` | 1 Hz | heart rate (bpm), optional |
+//! | `IBI.csv` | — | `offset, interval` pairs (s) after a `start, IBI` line, optional |
+//! | `labels.csv` | any | integer protocol labels in the same layout, optional |
+//!
+//! Every signal is aligned to a common 1 Hz clock covering the seconds where all required
+//! signals overlap: second `t` holds the mean of each signal's samples in `[t, t + 1)`.
+//! Heart rate comes from `HR.csv` when present and RMSSD from `IBI.csv`; otherwise both
+//! are derived from beats detected in the BVP signal.
+
+use crate::query::SensorDataRow;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// E4 accelerometer units per g.
+const ACC_UNITS_PER_G: f32 = 64.0;
+/// Shortest and longest plausible inter-beat intervals (s): 200 and 40 bpm.
+const MIN_IBI_S: f64 = 0.3;
+const MAX_IBI_S: f64 = 1.5;
+/// Trailing windows for beat-derived HR and for RMSSD (s).
+const HR_WINDOW_S: f64 = 5.0;
+const RMSSD_WINDOW_S: f64 = 30.0;
+/// Largest mismatch (s) between a beat's interval and the time since the previous beat
+/// for the two to count as successive.
+const SUCCESSIVE_BEAT_TOLERANCE_S: f64 = 0.01;
+
+/// Errors from importing a wrist recording.
+#[derive(Debug)]
+pub enum ImportError {
+    /// Reading a file failed.
+    Io(PathBuf, std::io::Error),
+    /// A file does not follow the E4 CSV layout.
+    Parse {
+        /// File (or signal) name.
+        file: String,
+        /// 1-based line number.
+        line: usize,
+        /// What was wrong with it.
+        reason: String,
+    },
+    /// A required signal is absent.
+    Missing(&'static str),
+    /// The required signals share no whole second.
+    NoOverlap,
+    /// No second of the overlap has enough beats for both HR and RMSSD.
+    NoBeats,
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::Io(path, e) => write!(f, "failed to read {}: {}", path.display(), e),
+            ImportError::Parse { file, line, reason } => write!(f, "{} line {}: {}", file, line, reason),
+            ImportError::Missing(what) => write!(f, "recording has no {}", what),
+            ImportError::NoOverlap => write!(f, "signals do not overlap in time"),
+            ImportError::NoBeats => write!(f, "too few heartbeats for HR and RMSSD"),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ImportError::Io(_, e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Protocol label vocabulary of the source dataset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LabelScheme {
+    /// WESAD: 1 baseline, 2 stress (TSST), 3 amusement, 4 meditation; others are transient.
+    Wesad,
+    /// PPG-DaLiA: 1 sitting, 2 stairs, 3 table soccer, 4 cycling, 5 driving, 6 lunch,
+    /// 7 walking, 8 working; 0 is transient.
+    Dalia,
+}
+
+impl LabelScheme {
+    /// Lower-case name, as used on the command line and as the session's profile name.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LabelScheme::Wesad => "wesad",
+            LabelScheme::Dalia => "dalia",
+        }
+    }
+
+    /// Parse a scheme name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "wesad" => Some(LabelScheme::Wesad),
+            "dalia" | "ppg-dalia" | "ppg_dalia" => Some(LabelScheme::Dalia),
+            _ => None,
+        }
+    }
+
+    /// Value for the `activity` column. Uses the simulator's activity names where one
+    /// fits, otherwise the dataset's own activity name.
+    pub fn activity(&self, label: i64) -> &'static str {
+        match (self, label) {
+            (LabelScheme::Wesad, 1 | 3 | 4) => "sitting",
+            // The TSST is a speech and arithmetic task delivered standing
+            (LabelScheme::Wesad, 2) => "standing",
+            (LabelScheme::Dalia, 1) => "sitting",
+            (LabelScheme::Dalia, 2) => "stairs",
+            (LabelScheme::Dalia, 3) => "table_soccer",
+            (LabelScheme::Dalia, 4) => "cycling_easy",
+            (LabelScheme::Dalia, 5) => "driving",
+            (LabelScheme::Dalia, 6) => "lunch",
+            (LabelScheme::Dalia, 7) => "walking_slow",
+            (LabelScheme::Dalia, 8) => "working",
+            _ => "transient",
+        }
+    }
+
+    /// Nominal value for the `stress_level` column in [0, 1]: WESAD's stress condition is
+    /// high and its relaxing conditions low; DaLiA has no stress annotation, so only its
+    /// more demanding daily activities are raised slightly.
+    pub fn stress_level(&self, label: i64) -> f32 {
+        match (self, label) {
+            (LabelScheme::Wesad, 1) => 0.1,
+            (LabelScheme::Wesad, 2) => 0.8,
+            (LabelScheme::Wesad, 3) => 0.2,
+            (LabelScheme::Wesad, 4) => 0.0,
+            (LabelScheme::Dalia, 3 | 5 | 8) => 0.3,
+            (LabelScheme::Dalia, 1..=8) => 0.1,
+            _ => 0.0,
+        }
+    }
+
+    /// Value for the `exercise_flag` column.
+    pub fn is_exercise(&self, label: i64) -> bool {
+        matches!((self, label), (LabelScheme::Dalia, 4))
+    }
+}
+
+/// One signal in the E4 CSV layout.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Channel {
+    /// Unix time (s) of the first sample.
+    pub start_ts: f64,
+    /// Samples per second.
+    pub rate_hz: f64,
+    /// Values per sample (3 for `ACC.csv`, otherwise 1).
+    pub width: usize,
+    /// Samples, `width` values each, flattened.
+    pub values: Vec<f32>,
+}
+
+impl Channel {
+    /// Parse a signal file's contents; `file` names it in errors.
+    pub fn parse(file: &str, text: &str) -> Result<Self, ImportError> {
+        let err = |line: usize, reason: String| ImportError::Parse { file: file.to_string(), line, reason };
+        let mut lines = text.lines().enumerate().filter(|(_, l)| !l.trim().is_empty());
+        let (_, header) = lines.next().ok_or_else(|| err(1, "empty file".to_string()))?;
+        let start = parse_fields(header).map_err(|r| err(1, r))?;
+        let (_, rate_line) = lines.next().ok_or_else(|| err(2, "missing sample rate".to_string()))?;
+        let rate = parse_fields(rate_line).map_err(|r| err(2, r))?;
+        let width = start.len();
+        if width == 0 || rate.len() != width {
+            return Err(err(2, format!("expected {} sample rate value(s), found {}", width, rate.len())));
+        }
+        if !rate[0].is_finite() || rate[0] <= 0.0 {
+            return Err(err(2, format!("sample rate must be positive, got {}", rate[0])));
+        }
+        let mut values = Vec::new();
+        for (i, line) in lines {
+            let sample = parse_fields(line).map_err(|r| err(i + 1, r))?;
+            if sample.len() != width {
+                return Err(err(i + 1, format!("expected {} value(s), found {}", width, sample.len())));
+            }
+            values.extend(sample.into_iter().map(|v| v as f32));
+        }
+        Ok(Self { start_ts: start[0], rate_hz: rate[0], width, values })
+    }
+
+    /// Number of samples.
+    pub fn len(&self) -> usize {
+        self.values.len() / self.width
+    }
+
+    /// True if the channel has no samples.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Values of sample `i`.
+    pub fn sample(&self, i: usize) -> &[f32] {
+        &self.values[i * self.width..(i + 1) * self.width]
+    }
+
+    /// Unix time (s) just after the last sample.
+    pub fn end_ts(&self) -> f64 {
+        self.start_ts + self.len() as f64 / self.rate_hz
+    }
+
+    /// Indices of the samples in `[t, t + 1)`, or of the sample nearest the middle of
+    /// the second for channels slower than 1 Hz.
+    fn second(&self, t: i64) -> std::ops::Range<usize> {
+        let index = |ts: f64| (((ts - self.start_ts) * self.rate_hz).ceil().max(0.0) as usize).min(self.len());
+        let range = index(t as f64)..index(t as f64 + 1.0);
+        if range.is_empty() && !self.is_empty() {
+            let nearest = (((t as f64 + 0.5 - self.start_ts) * self.rate_hz).round().max(0.0) as usize).min(self.len() - 1);
+            return nearest..nearest + 1;
+        }
+        range
+    }
+
+    /// Mean of `f` over the samples in second `t`.
+    fn mean_over(&self, t: i64, f: impl Fn(&[f32]) -> f32) -> f32 {
+        let range = self.second(t);
+        let n = range.len().max(1) as f32;
+        range.map(|i| f(self.sample(i))).sum::<f32>() / n
+    }
+
+    /// Most frequent (first-column) value in second `t`, rounded to an integer label.
+    fn mode_over(&self, t: i64) -> i64 {
+        let mut counts: Vec<(i64, usize)> = Vec::new();
+        for i in self.second(t) {
+            let label = self.sample(i)[0].round() as i64;
+            match counts.iter_mut().find(|(l, _)| *l == label) {
+                Some((_, c)) => *c += 1,
+                None => counts.push((label, 1)),
+            }
+        }
+        counts.into_iter().fold((0, 0), |best, c| if c.1 > best.1 { c } else { best }).0
+    }
+}
+
+/// Parse an `IBI.csv` file: a `start, IBI` line, then `offset, interval` pairs (s).
+///
+/// Returns `(beat time, interval)` pairs with absolute Unix times.
+pub fn parse_ibi(file: &str, text: &str) -> Result<Vec<(f64, f64)>, ImportError> {
+    let err = |line: usize, reason: String| ImportError::Parse { file: file.to_string(), line, reason };
+    let mut lines = text.lines().enumerate().filter(|(_, l)| !l.trim().is_empty());
+    let Some((_, header)) = lines.next() else {
+        return Ok(Vec::new());
+    };
+    let start = header
+        .split(',')
+        .next()
+        .and_then(|s| s.trim().parse::<f64>().ok())
+        .ok_or_else(|| err(1, format!("invalid start time {:?}", header)))?;
+    lines
+        .map(|(i, line)| match parse_fields(line).map_err(|r| err(i + 1, r))?.as_slice() {
+            [offset, ibi] => Ok((start + offset, *ibi)),
+            other => Err(err(i + 1, format!("expected 2 values, found {}", other.len()))),
+        })
+        .collect()
+}
+
+fn parse_fields(line: &str) -> Result<Vec<f64>, String> {
+    line.split(',')
+        .map(|f| f.trim().parse::<f64>().map_err(|_| format!("invalid number {:?}", f.trim())))
+        .collect()
+}
+
+/// The signals of one wrist recording.
+#[derive(Debug, Clone, Default)]
+pub struct WristRecording {
+    /// Blood volume pulse (64 Hz).
+    pub bvp: Option<Channel>,
+    /// Electrodermal activity (4 Hz, μS).
+    pub eda: Option<Channel>,
+    /// Skin temperature (4 Hz, °C).
+    pub temp: Option<Channel>,
+    /// Acceleration (32 Hz, 1/64 g).
+    pub acc: Option<Channel>,
+    /// Device heart rate (1 Hz, bpm).
+    pub hr: Option<Channel>,
+    /// Device inter-beat intervals: `(beat time, interval)` in seconds.
+    pub ibi: Vec<(f64, f64)>,
+    /// Protocol labels.
+    pub labels: Option<Channel>,
+}
+
+impl WristRecording {
+    /// Load the E4 files found in `dir`; absent files leave their signal empty.
+    pub fn load_dir(dir: impl AsRef<Path>) -> Result<Self, ImportError> {
+        let dir = dir.as_ref();
+        let read = |name: &str| -> Result<Option<String>, ImportError> {
+            let path = dir.join(name);
+            match std::fs::read_to_string(&path) {
+                Ok(text) => Ok(Some(text)),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(ImportError::Io(path, e)),
+            }
+        };
+        let channel = |name: &str| read(name)?.map(|text| Channel::parse(name, &text)).transpose();
+        Ok(Self {
+            bvp: channel("BVP.csv")?,
+            eda: channel("EDA.csv")?,
+            temp: channel("TEMP.csv")?,
+            acc: channel("ACC.csv")?,
+            hr: channel("HR.csv")?,
+            ibi: read("IBI.csv")?.map(|text| parse_ibi("IBI.csv", &text)).transpose()?.unwrap_or_default(),
+            labels: channel("labels.csv")?,
+        })
+    }
+
+    /// Align every signal to a 1 Hz clock and label each second with `scheme`.
+    ///
+    /// EDA, TEMP, ACC and either HR or BVP are required. Rows start at the first second
+    /// with enough beats for both HR and RMSSD; later seconds without enough beats hold 0
+    /// for that signal.
+    pub fn to_rows(&self, scheme: LabelScheme) -> Result<Vec<SensorDataRow>, ImportError> {
+        let eda = self.eda.as_ref().ok_or(ImportError::Missing("EDA.csv"))?;
+        let temp = self.temp.as_ref().ok_or(ImportError::Missing("TEMP.csv"))?;
+        let acc = self.acc.as_ref().ok_or(ImportError::Missing("ACC.csv"))?;
+        if acc.width != 3 {
+            return Err(ImportError::Parse {
+                file: "ACC.csv".to_string(),
+                line: 1,
+                reason: format!("expected 3 columns, found {}", acc.width),
+            });
+        }
+        let hr_source = self.hr.as_ref().or(self.bvp.as_ref()).ok_or(ImportError::Missing("HR.csv or BVP.csv"))?;
+
+        let required = [eda, temp, acc, hr_source];
+        let start = required.iter().map(|c| c.start_ts).fold(f64::MIN, f64::max).ceil() as i64;
+        let end = required.iter().map(|c| c.end_ts()).fold(f64::MAX, f64::min).floor() as i64;
+        if end <= start {
+            return Err(ImportError::NoOverlap);
+        }
+
+        // Beat intervals for whatever the device files don't provide
+        let bvp_ibi = match (&self.bvp, self.hr.is_none() || self.ibi.is_empty()) {
+            (Some(bvp), true) => detect_beats(bvp),
+            _ => Vec::new(),
+        };
+        let ibi = if self.ibi.is_empty() { &bvp_ibi } else { &self.ibi };
+
+        let mut rows = Vec::new();
+        for t in start..end {
+            let hr = match &self.hr {
+                Some(hr) => Some(hr.mean_over(t, |s| s[0])),
+                None => beat_rate(&bvp_ibi, t),
+            };
+            let hrv_rmssd = rmssd(ibi, t);
+            // No row until both are known, rather than a run of zeros
+            if rows.is_empty() && (hr.is_none() || hrv_rmssd.is_none()) {
+                continue;
+            }
+            let label = self.labels.as_ref().map_or(0, |l| l.mode_over(t));
+            rows.push(SensorDataRow {
+                ts_unix_sec: t,
+                hr: hr.unwrap_or(0.0),
+                hrv_rmssd: hrv_rmssd.unwrap_or(0.0),
+                eda_mus: eda.mean_over(t, |s| s[0]),
+                temp_c: temp.mean_over(t, |s| s[0]),
+                accel_mag_g: acc.mean_over(t, |s| s.iter().map(|a| a * a).sum::<f32>().sqrt() / ACC_UNITS_PER_G),
+                activity: scheme.activity(label).to_string(),
+                stress_level: scheme.stress_level(label),
+                exercise_flag: scheme.is_exercise(label),
+                sleep_stage: None,
+            });
+        }
+        if rows.is_empty() {
+            return Err(ImportError::NoBeats);
+        }
+        Ok(rows)
+    }
+}
+
+/// Load a recording from `dir` and convert it to 1 Hz `sensor_data` rows.
+pub fn import_dir(dir: impl AsRef<Path>, scheme: LabelScheme) -> Result<Vec<SensorDataRow>, ImportError> {
+    WristRecording::load_dir(dir)?.to_rows(scheme)
+}
+
+/// Beats in a BVP signal as `(beat time, interval)` pairs.
+///
+/// A beat is a local maximum above the signal's mean over the surrounding second, at least
+/// [`MIN_IBI_S`] after the previous one; intervals outside the plausible range are dropped.
+fn detect_beats(bvp: &Channel) -> Vec<(f64, f64)> {
+    let n = bvp.len();
+    let half = (bvp.rate_hz / 2.0).round().max(1.0) as usize;
+    // Running sums for the centred one-second mean
+    let mut prefix = Vec::with_capacity(n + 1);
+    prefix.push(0f64);
+    for i in 0..n {
+        prefix.push(prefix[i] + bvp.sample(i)[0] as f64);
+    }
+    let mean = |i: usize| {
+        let (lo, hi) = (i.saturating_sub(half), (i + half + 1).min(n));
+        (prefix[hi] - prefix[lo]) / (hi - lo) as f64
+    };
+
+    let mut peaks: Vec<(f64, f32)> = Vec::new();
+    for i in 1..n.saturating_sub(1) {
+        let v = bvp.sample(i)[0];
+        if v <= bvp.sample(i - 1)[0] || v < bvp.sample(i + 1)[0] || (v as f64) <= mean(i) {
+            continue;
+        }
+        let ts = bvp.start_ts + i as f64 / bvp.rate_hz;
+        match peaks.last_mut() {
+            // Within the refractory period keep the taller peak
+            Some(last) if ts - last.0 < MIN_IBI_S => {
+                if v > last.1 {
+                    *last = (ts, v);
+                }
+            }
+            _ => peaks.push((ts, v)),
+        }
+    }
+    peaks
+        .windows(2)
+        .map(|w| (w[1].0, w[1].0 - w[0].0))
+        .filter(|&(_, ibi)| (MIN_IBI_S..=MAX_IBI_S).contains(&ibi))
+        .collect()
+}
+
+/// Intervals of the beats in the trailing `window` seconds ending with second `t`.
+fn trailing(ibi: &[(f64, f64)], t: i64, window: f64) -> &[(f64, f64)] {
+    let end = t as f64 + 1.0;
+    let lo = ibi.partition_point(|&(ts, _)| ts < end - window);
+    let hi = ibi.partition_point(|&(ts, _)| ts < end);
+    &ibi[lo..hi.max(lo)]
+}
+
+/// Heart rate (bpm) from the mean interval over the trailing [`HR_WINDOW_S`], if any beats.
+fn beat_rate(ibi: &[(f64, f64)], t: i64) -> Option<f32> {
+    let beats = trailing(ibi, t, HR_WINDOW_S);
+    if beats.is_empty() {
+        return None;
+    }
+    let mean = beats.iter().map(|&(_, i)| i).sum::<f64>() / beats.len() as f64;
+    Some((60.0 / mean) as f32)
+}
+
+/// RMSSD (ms) of successive intervals over the trailing [`RMSSD_WINDOW_S`], if there are
+/// at least two differences.
+///
+/// Two intervals are successive only if the later beat follows the earlier one by exactly
+/// its own interval; across a missed or rejected beat the difference is meaningless.
+fn rmssd(ibi: &[(f64, f64)], t: i64) -> Option<f32> {
+    let diffs: Vec<f64> = trailing(ibi, t, RMSSD_WINDOW_S)
+        .windows(2)
+        .filter(|w| (w[1].0 - w[0].0 - w[1].1).abs() <= SUCCESSIVE_BEAT_TOLERANCE_S)
+        .map(|w| (w[1].1 - w[0].1) * 1000.0)
+        .collect();
+    if diffs.len() < 2 {
+        return None;
+    }
+    Some((diffs.iter().map(|d| d * d).sum::<f64>() / diffs.len() as f64).sqrt() as f32)
+}
@@ -2,10 +2,19 @@
 #![deny(missing_docs)]
 /// Data access layer abstractions (SQLite/DuckDB/etc.).
 pub mod query;
+pub mod error;
+pub mod import;
 pub mod sqlite;
 pub mod profile;
 pub mod parquet;
+pub mod migrations;
+pub mod retention;
+pub mod rollup;
+pub mod scenario;
+pub mod store;
+pub use error::DataLayerError;
 pub use profile::*;
+pub use store::{MemoryStore, ParquetStore, SqliteStore, TimeSeriesStore};
 
 // ...existing code...
 
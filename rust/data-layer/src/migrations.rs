@@ -0,0 +1,195 @@
+//! Versioned SQLite schema migrations tracked via `PRAGMA user_version`.
+
+use rusqlite::Connection;
+use std::fmt;
+
+/// One ordered up-migration.
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    /// Schema version reached after applying this migration (1-based).
+    pub version: u32,
+    /// Short human-readable description.
+    pub description: &'static str,
+    /// SQL batch applied inside a transaction.
+    pub sql: &'static str,
+}
+
+/// All migrations, in the order they are applied.
+///
+/// Never edit a released migration; append a new one instead.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "user_sessions and sensor_data",
+        sql: r#"
+        CREATE TABLE IF NOT EXISTS user_sessions (
+            session_id INTEGER PRIMARY KEY,
+            user_id TEXT NOT NULL,
+            profile_name TEXT,
+            simulation_date TEXT,
+            seed INTEGER,
+            created_at TIMESTAMP
+        );
+        CREATE TABLE IF NOT EXISTS sensor_data (
+            id INTEGER PRIMARY KEY,
+            session_id INTEGER,
+            ts_unix_sec INTEGER,
+            hr REAL, hrv_rmssd REAL, eda_mus REAL, temp_c REAL, accel_mag_g REAL,
+            activity TEXT,
+            stress_level REAL,
+            exercise_flag BOOLEAN,
+            created_at TIMESTAMP,
+            FOREIGN KEY(session_id) REFERENCES user_sessions(session_id)
+        );
+        CREATE INDEX IF NOT EXISTS idx_session_ts ON sensor_data(session_id, ts_unix_sec);
+        "#,
+    },
+    Migration {
+        version: 2,
+        description: "generic per-stream samples",
+        sql: r#"
+        CREATE TABLE IF NOT EXISTS samples (
+            id INTEGER PRIMARY KEY,
+            stream TEXT NOT NULL,
+            ts_ms INTEGER NOT NULL,
+            value REAL NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_stream_ts ON samples(stream, ts_ms);
+        "#,
+    },
+    Migration {
+        version: 3,
+        description: "minute/hour/day rollups",
+        sql: r#"
+        CREATE TABLE IF NOT EXISTS sensor_rollup (
+            session_id INTEGER NOT NULL,
+            resolution TEXT NOT NULL,
+            bucket_start INTEGER NOT NULL,
+            sample_count INTEGER NOT NULL,
+            hr_min REAL, hr_mean REAL, hr_max REAL, hr_p95 REAL,
+            hrv_rmssd_min REAL, hrv_rmssd_mean REAL, hrv_rmssd_max REAL, hrv_rmssd_p95 REAL,
+            eda_mus_min REAL, eda_mus_mean REAL, eda_mus_max REAL, eda_mus_p95 REAL,
+            temp_c_min REAL, temp_c_mean REAL, temp_c_max REAL, temp_c_p95 REAL,
+            accel_mag_g_min REAL, accel_mag_g_mean REAL, accel_mag_g_max REAL, accel_mag_g_p95 REAL,
+            PRIMARY KEY (session_id, resolution, bucket_start)
+        );
+        CREATE TABLE IF NOT EXISTS activity_rollup (
+            session_id INTEGER NOT NULL,
+            resolution TEXT NOT NULL,
+            bucket_start INTEGER NOT NULL,
+            activity TEXT NOT NULL,
+            minutes INTEGER NOT NULL,
+            PRIMARY KEY (session_id, resolution, bucket_start, activity)
+        );
+        "#,
+    },
+    Migration {
+        version: 4,
+        description: "ground-truth sleep_stage label",
+        sql: r#"
+        ALTER TABLE sensor_data ADD COLUMN sleep_stage TEXT;
+        "#,
+    },
+    Migration {
+        version: 5,
+        description: "ground-truth anomaly events",
+        sql: r#"
+        CREATE TABLE IF NOT EXISTS events (
+            id INTEGER PRIMARY KEY,
+            session_id INTEGER NOT NULL,
+            kind TEXT NOT NULL,
+            start_ts INTEGER NOT NULL,
+            end_ts INTEGER NOT NULL,
+            magnitude REAL,
+            FOREIGN KEY(session_id) REFERENCES user_sessions(session_id)
+        );
+        CREATE INDEX IF NOT EXISTS idx_events_session_ts ON events(session_id, start_ts);
+        "#,
+    },
+];
+
+/// Schema version this binary understands.
+pub const CURRENT_VERSION: u32 = MIGRATIONS.len() as u32;
+
+/// Errors raised while migrating a database.
+#[derive(Debug)]
+pub enum MigrationError {
+    /// The database was written by a newer binary.
+    TooNew {
+        /// Version found in the database.
+        found: u32,
+        /// Latest version this binary supports.
+        supported: u32,
+    },
+    /// A migration failed to apply.
+    Failed {
+        /// Version of the failing migration.
+        version: u32,
+        /// Underlying SQLite error.
+        source: rusqlite::Error,
+    },
+    /// Reading or writing `user_version` failed.
+    Sqlite(rusqlite::Error),
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MigrationError::TooNew { found, supported } => write!(
+                f,
+                "database schema version {} is newer than supported version {}",
+                found, supported
+            ),
+            MigrationError::Failed { version, source } => {
+                write!(f, "migration to version {} failed: {}", version, source)
+            }
+            MigrationError::Sqlite(e) => write!(f, "sqlite error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for MigrationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MigrationError::TooNew { .. } => None,
+            MigrationError::Failed { source, .. } => Some(source),
+            MigrationError::Sqlite(e) => Some(e),
+        }
+    }
+}
+
+impl From<rusqlite::Error> for MigrationError {
+    fn from(e: rusqlite::Error) -> Self {
+        MigrationError::Sqlite(e)
+    }
+}
+
+/// Read the schema version stored in `PRAGMA user_version` (0 for a fresh or legacy DB).
+pub fn schema_version(conn: &Connection) -> rusqlite::Result<u32> {
+    conn.pragma_query_value(None, "user_version", |row| row.get(0))
+}
+
+/// Apply every pending migration, each in its own transaction.
+///
+/// Returns the resulting schema version. Fails without touching the database if it
+/// reports a version newer than [`CURRENT_VERSION`].
+pub fn migrate(conn: &Connection) -> Result<u32, MigrationError> {
+    let found = schema_version(conn)?;
+    if found > CURRENT_VERSION {
+        return Err(MigrationError::TooNew { found, supported: CURRENT_VERSION });
+    }
+    for migration in MIGRATIONS.iter().filter(|m| m.version > found) {
+        apply(conn, migration).map_err(|source| MigrationError::Failed {
+            version: migration.version,
+            source,
+        })?;
+    }
+    Ok(schema_version(conn)?)
+}
+
+fn apply(conn: &Connection, migration: &Migration) -> rusqlite::Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute_batch(migration.sql)?;
+    tx.pragma_update(None, "user_version", migration.version)?;
+    tx.commit()
+}
@@ -1,14 +1,44 @@
 //! Parquet/Arrow ingestion and persistence for sensor data.
 
-use arrow::array::{Float32Array, Int64Array, StringArray, BooleanArray, ArrayRef};
-use arrow::datatypes::{DataType, Field, Schema};
+use arrow::array::{Array, Float32Array, Int64Array, StringArray, BooleanArray};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
 use arrow::record_batch::RecordBatch;
+use crate::error::{DataLayerError, Result};
+use crate::query::{DecodeMode, Decoded, EventRow, SensorDataRow};
+use core_types::Sample;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
 use parquet::arrow::ArrowWriter;
+use parquet::basic::{Compression, ZstdLevel};
+use parquet::file::metadata::RowGroupMetaData;
 use parquet::file::properties::WriterProperties;
+use parquet::schema::types::ColumnPath;
+use parquet::file::statistics::Statistics;
 use std::fs::File;
+use std::path::Path;
 use std::sync::Arc;
 
+pub mod dataset;
+pub use dataset::{Partition, SensorDataset};
+
+/// Arrow schema of sensor_data Parquet files.
+pub fn sensor_data_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("session_id", DataType::Int64, false),
+        Field::new("ts_unix_sec", DataType::Int64, false),
+        Field::new("hr", DataType::Float32, false),
+        Field::new("hrv_rmssd", DataType::Float32, false),
+        Field::new("eda_mus", DataType::Float32, false),
+        Field::new("temp_c", DataType::Float32, false),
+        Field::new("accel_mag_g", DataType::Float32, false),
+        Field::new("activity", DataType::Utf8, false),
+        Field::new("stress_level", DataType::Float32, false),
+        Field::new("exercise_flag", DataType::Boolean, false),
+        Field::new("sleep_stage", DataType::Utf8, true),
+    ]))
+}
+
 /// Write a batch of sensor data to a Parquet file.
+#[allow(clippy::too_many_arguments)]
 pub fn write_sensor_data_parquet(
     path: &str,
     session_id: &[i64],
@@ -21,19 +51,9 @@ pub fn write_sensor_data_parquet(
     activity: &[String],
     stress_level: &[f32],
     exercise_flag: &[bool],
-) -> anyhow::Result<()> {
-    let schema = Arc::new(Schema::new(vec![
-        Field::new("session_id", DataType::Int64, false),
-        Field::new("ts_unix_sec", DataType::Int64, false),
-        Field::new("hr", DataType::Float32, false),
-        Field::new("hrv_rmssd", DataType::Float32, false),
-        Field::new("eda_mus", DataType::Float32, false),
-        Field::new("temp_c", DataType::Float32, false),
-        Field::new("accel_mag_g", DataType::Float32, false),
-        Field::new("activity", DataType::Utf8, false),
-        Field::new("stress_level", DataType::Float32, false),
-        Field::new("exercise_flag", DataType::Boolean, false),
-    ]));
+    sleep_stage: &[Option<String>],
+) -> Result<()> {
+    let schema = sensor_data_schema();
     let batch = RecordBatch::try_new(
         schema.clone(),
         vec![
@@ -47,6 +67,171 @@ pub fn write_sensor_data_parquet(
             Arc::new(StringArray::from(activity.to_vec())),
             Arc::new(Float32Array::from(stress_level.to_vec())),
             Arc::new(BooleanArray::from(exercise_flag.to_vec())),
+            Arc::new(StringArray::from(sleep_stage.to_vec())),
+        ],
+    )?;
+    let file = File::create(path)?;
+    let props = WriterProperties::builder().build();
+    let mut writer = ArrowWriter::try_new(file, schema, Some(props))?;
+    writer.write(&batch)?;
+    writer.close()?;
+    Ok(())
+}
+
+/// Tuning knobs for [`SensorParquetWriter`].
+#[derive(Debug, Clone)]
+pub struct SensorParquetOptions {
+    /// Rows buffered before a row group is flushed to disk.
+    pub row_group_size: usize,
+    /// Page compression codec (e.g. ZSTD or Snappy).
+    pub compression: Compression,
+    /// Dictionary-encode the `activity` and `sleep_stage` label columns (other columns
+    /// are plain-encoded).
+    pub dictionary_activity: bool,
+}
+
+impl Default for SensorParquetOptions {
+    fn default() -> Self {
+        Self {
+            row_group_size: 64 * 1024,
+            compression: Compression::ZSTD(ZstdLevel::default()),
+            dictionary_activity: true,
+        }
+    }
+}
+
+impl SensorParquetOptions {
+    /// Build the Parquet `WriterProperties` for these options.
+    pub fn writer_properties(&self) -> WriterProperties {
+        WriterProperties::builder()
+            .set_compression(self.compression)
+            .set_max_row_group_size(self.row_group_size)
+            .set_dictionary_enabled(false)
+            .set_column_dictionary_enabled(ColumnPath::from("activity"), self.dictionary_activity)
+            .set_column_dictionary_enabled(ColumnPath::from("sleep_stage"), self.dictionary_activity)
+            .build()
+    }
+}
+
+/// Streaming sensor_data Parquet writer.
+///
+/// Rows are buffered and written out as one row group every `row_group_size` rows, so
+/// memory stays bounded for long sessions. The file footer is written by
+/// [`close`](Self::close); dropping an unclosed writer finalizes it on a best-effort basis,
+/// so callers must `close` it to see write errors.
+pub struct SensorParquetWriter {
+    writer: Option<ArrowWriter<File>>,
+    buffer: Vec<(i64, SensorDataRow)>,
+    row_group_size: usize,
+    rows_written: usize,
+}
+
+impl SensorParquetWriter {
+    /// Create `path` and write sensor rows to it with default options.
+    pub fn create(path: impl AsRef<Path>) -> Result<Self> {
+        Self::create_with_options(path, SensorParquetOptions::default())
+    }
+
+    /// Create `path` and write sensor rows to it with the given options.
+    pub fn create_with_options(path: impl AsRef<Path>, options: SensorParquetOptions) -> Result<Self> {
+        let file = File::create(path)?;
+        let writer = ArrowWriter::try_new(file, sensor_data_schema(), Some(options.writer_properties()))?;
+        let row_group_size = options.row_group_size.max(1);
+        Ok(Self {
+            writer: Some(writer),
+            buffer: Vec::with_capacity(row_group_size),
+            row_group_size,
+            rows_written: 0,
+        })
+    }
+
+    /// Append one row for `session_id`.
+    pub fn write(&mut self, session_id: i64, row: &SensorDataRow) -> Result<()> {
+        self.buffer.push((session_id, row.clone()));
+        if self.buffer.len() >= self.row_group_size {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Append a chunk of rows for `session_id`.
+    pub fn write_all(&mut self, session_id: i64, rows: &[SensorDataRow]) -> Result<()> {
+        for row in rows {
+            self.write(session_id, row)?;
+        }
+        Ok(())
+    }
+
+    /// Write buffered rows out as a row group.
+    pub fn flush(&mut self) -> Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let writer = self
+            .writer
+            .as_mut()
+            .ok_or_else(|| std::io::Error::other("SensorParquetWriter already closed"))?;
+        let rows = std::mem::take(&mut self.buffer);
+        let batch = RecordBatch::try_new(
+            sensor_data_schema(),
+            vec![
+                Arc::new(Int64Array::from_iter_values(rows.iter().map(|(s, _)| *s))),
+                Arc::new(Int64Array::from_iter_values(rows.iter().map(|(_, r)| r.ts_unix_sec))),
+                Arc::new(Float32Array::from_iter_values(rows.iter().map(|(_, r)| r.hr))),
+                Arc::new(Float32Array::from_iter_values(rows.iter().map(|(_, r)| r.hrv_rmssd))),
+                Arc::new(Float32Array::from_iter_values(rows.iter().map(|(_, r)| r.eda_mus))),
+                Arc::new(Float32Array::from_iter_values(rows.iter().map(|(_, r)| r.temp_c))),
+                Arc::new(Float32Array::from_iter_values(rows.iter().map(|(_, r)| r.accel_mag_g))),
+                Arc::new(StringArray::from_iter_values(rows.iter().map(|(_, r)| r.activity.as_str()))),
+                Arc::new(Float32Array::from_iter_values(rows.iter().map(|(_, r)| r.stress_level))),
+                Arc::new(BooleanArray::from_iter(rows.iter().map(|(_, r)| Some(r.exercise_flag)))),
+                Arc::new(StringArray::from_iter(rows.iter().map(|(_, r)| r.sleep_stage.as_deref()))),
+            ],
+        )?;
+        writer.write(&batch)?;
+        writer.flush()?;
+        self.rows_written += rows.len();
+        Ok(())
+    }
+
+    /// Rows written to disk so far (excluding the unflushed buffer).
+    pub fn rows_written(&self) -> usize {
+        self.rows_written
+    }
+
+    /// Flush remaining rows and write the file footer. Returns the total row count.
+    pub fn close(mut self) -> Result<usize> {
+        self.finish()?;
+        Ok(self.rows_written)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.flush()?;
+        if let Some(writer) = self.writer.take() {
+            writer.close()?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for SensorParquetWriter {
+    fn drop(&mut self) {
+        // Errors cannot be reported from drop; callers wanting them close explicitly
+        let _ = self.finish();
+    }
+}
+
+/// Write generic time-series samples (`ts_ms`, `value`) to a Parquet file.
+pub fn write_samples_parquet(path: &Path, samples: &[Sample]) -> Result<()> {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("ts_ms", DataType::Int64, false),
+        Field::new("value", DataType::Float32, false),
+    ]));
+    let batch = RecordBatch::try_new(
+        schema.clone(),
+        vec![
+            Arc::new(Int64Array::from_iter_values(samples.iter().map(|s| s.ts_ms))),
+            Arc::new(Float32Array::from_iter_values(samples.iter().map(|s| s.value))),
         ],
     )?;
     let file = File::create(path)?;
@@ -56,3 +241,323 @@ pub fn write_sensor_data_parquet(
     writer.close()?;
     Ok(())
 }
+
+/// Read all time-series samples from a Parquet file written by [`write_samples_parquet`].
+pub fn read_samples_parquet(path: &Path) -> Result<Vec<Sample>> {
+    let file = File::open(path)?;
+    let reader = ParquetRecordBatchReaderBuilder::try_new(file)?.build()?;
+    let mut out = Vec::new();
+    for batch in reader {
+        let batch = batch?;
+        let ts = typed_column::<Int64Array>(&batch, "ts_ms", DataType::Int64)?;
+        let values = typed_column::<Float32Array>(&batch, "value", DataType::Float32)?;
+        for i in 0..batch.num_rows() {
+            out.push(Sample { ts_ms: ts.value(i), value: values.value(i) });
+        }
+    }
+    Ok(out)
+}
+
+/// Arrow schema of event Parquet files.
+pub fn events_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("session_id", DataType::Int64, false),
+        Field::new("kind", DataType::Utf8, false),
+        Field::new("start_ts", DataType::Int64, false),
+        Field::new("end_ts", DataType::Int64, false),
+        Field::new("magnitude", DataType::Float32, true),
+    ]))
+}
+
+/// Write a session's ground-truth events to a Parquet file.
+pub fn write_events_parquet(path: &Path, session_id: i64, events: &[EventRow]) -> Result<()> {
+    let schema = events_schema();
+    let batch = RecordBatch::try_new(
+        schema.clone(),
+        vec![
+            Arc::new(Int64Array::from(vec![session_id; events.len()])),
+            Arc::new(StringArray::from_iter_values(events.iter().map(|e| e.kind.as_str()))),
+            Arc::new(Int64Array::from_iter_values(events.iter().map(|e| e.start_ts))),
+            Arc::new(Int64Array::from_iter_values(events.iter().map(|e| e.end_ts))),
+            Arc::new(Float32Array::from_iter(events.iter().map(|e| e.magnitude))),
+        ],
+    )?;
+    let file = File::create(path)?;
+    let mut writer = ArrowWriter::try_new(file, schema, Some(WriterProperties::builder().build()))?;
+    writer.write(&batch)?;
+    writer.close()?;
+    Ok(())
+}
+
+/// Read one session's events from a Parquet file written by [`write_events_parquet`].
+pub fn read_events_parquet(path: &Path, session_id: i64) -> Result<Vec<EventRow>> {
+    let file = File::open(path)?;
+    let reader = ParquetRecordBatchReaderBuilder::try_new(file)?.build()?;
+    let mut out = Vec::new();
+    for batch in reader {
+        let batch = batch?;
+        let sessions = typed_column::<Int64Array>(&batch, "session_id", DataType::Int64)?;
+        let kind = typed_column::<StringArray>(&batch, "kind", DataType::Utf8)?;
+        let start = typed_column::<Int64Array>(&batch, "start_ts", DataType::Int64)?;
+        let end = typed_column::<Int64Array>(&batch, "end_ts", DataType::Int64)?;
+        let magnitude = typed_column::<Float32Array>(&batch, "magnitude", DataType::Float32)?;
+        for i in (0..batch.num_rows()).filter(|&i| sessions.value(i) == session_id) {
+            out.push(EventRow {
+                kind: kind.value(i).to_string(),
+                start_ts: start.value(i),
+                end_ts: end.value(i),
+                magnitude: magnitude.is_valid(i).then(|| magnitude.value(i)),
+            });
+        }
+    }
+    Ok(out)
+}
+
+/// Inclusive `[min, max]` range of an Int64 column in one row group, if statistics exist.
+fn row_group_i64_range(rg: &RowGroupMetaData, column: usize) -> Option<(i64, i64)> {
+    match rg.column(column).statistics()? {
+        Statistics::Int64(s) => Some((*s.min_opt()?, *s.max_opt()?)),
+        _ => None,
+    }
+}
+
+/// Column `name` of `batch` downcast to `T`, which must be the array type for `expected`.
+fn typed_column<'a, T: 'static>(batch: &'a RecordBatch, name: &str, expected: DataType) -> Result<&'a T> {
+    let mismatch = |found: String| DataLayerError::SchemaMismatch {
+        column: name.to_string(),
+        expected: expected.to_string(),
+        found,
+    };
+    let column = batch.column_by_name(name).ok_or_else(|| mismatch("missing".to_string()))?;
+    if column.data_type() != &expected {
+        return Err(mismatch(column.data_type().to_string()));
+    }
+    column
+        .as_any()
+        .downcast_ref::<T>()
+        .ok_or_else(|| mismatch(column.data_type().to_string()))
+}
+
+/// Optional Utf8 column `name`, or `None` if the file predates it.
+fn optional_utf8_column<'a>(batch: &'a RecordBatch, name: &str) -> Result<Option<&'a StringArray>> {
+    if batch.column_by_name(name).is_none() {
+        return Ok(None);
+    }
+    typed_column::<StringArray>(batch, name, DataType::Utf8).map(Some)
+}
+
+/// Read `(session_id, row)` pairs with `start_ts <= ts_unix_sec <= end_ts` from the given
+/// row groups, optionally restricted to one session.
+///
+/// Nulls in required columns of matching rows are decode errors, handled according to
+/// `mode`; rows outside the session or range are never checked. Error row numbers count
+/// from the start of the file.
+fn read_sensor_rows(
+    path: &Path,
+    row_groups: Vec<usize>,
+    session_id: Option<i64>,
+    start_ts: i64,
+    end_ts: i64,
+    mode: DecodeMode,
+) -> Result<Decoded<(i64, SensorDataRow)>> {
+    let mut out = Decoded::default();
+    if row_groups.is_empty() {
+        return Ok(out);
+    }
+    let builder = ParquetRecordBatchReaderBuilder::try_new(File::open(path)?)?;
+    // (first row within the selected groups, first row within the file) per selected group
+    let mut group_starts = Vec::with_capacity(row_groups.len());
+    let (mut selected, groups) = (0, builder.metadata().row_groups());
+    for &rg in &row_groups {
+        let file_start: i64 = groups[..rg].iter().map(|g| g.num_rows()).sum();
+        group_starts.push((selected, file_start as usize));
+        selected += groups[rg].num_rows() as usize;
+    }
+    let file_row = |pos: usize| {
+        let (sel, file) = group_starts[group_starts.partition_point(|&(sel, _)| sel <= pos) - 1];
+        file + pos - sel
+    };
+    let reader = builder.with_row_groups(row_groups).build()?;
+    let mut offset = 0;
+    for batch in reader {
+        let batch = batch?;
+        let sessions = typed_column::<Int64Array>(&batch, "session_id", DataType::Int64)?;
+        let ts = typed_column::<Int64Array>(&batch, "ts_unix_sec", DataType::Int64)?;
+        let hr = typed_column::<Float32Array>(&batch, "hr", DataType::Float32)?;
+        let hrv = typed_column::<Float32Array>(&batch, "hrv_rmssd", DataType::Float32)?;
+        let eda = typed_column::<Float32Array>(&batch, "eda_mus", DataType::Float32)?;
+        let temp = typed_column::<Float32Array>(&batch, "temp_c", DataType::Float32)?;
+        let accel = typed_column::<Float32Array>(&batch, "accel_mag_g", DataType::Float32)?;
+        let activity = typed_column::<StringArray>(&batch, "activity", DataType::Utf8)?;
+        let stress = typed_column::<Float32Array>(&batch, "stress_level", DataType::Float32)?;
+        let exercise = typed_column::<BooleanArray>(&batch, "exercise_flag", DataType::Boolean)?;
+        let sleep_stage = optional_utf8_column(&batch, "sleep_stage")?;
+        let columns: [(&str, &dyn Array); 10] = [
+            ("session_id", sessions),
+            ("ts_unix_sec", ts),
+            ("hr", hr),
+            ("hrv_rmssd", hrv),
+            ("eda_mus", eda),
+            ("temp_c", temp),
+            ("accel_mag_g", accel),
+            ("activity", activity),
+            ("stress_level", stress),
+            ("exercise_flag", exercise),
+        ];
+        let has_nulls = columns.iter().any(|(_, c)| c.null_count() > 0);
+        for i in 0..batch.num_rows() {
+            // Only files from foreign writers can have null keys; those rows can't be filtered
+            let keyed = sessions.is_valid(i) && ts.is_valid(i);
+            let t = ts.value(i);
+            if keyed && (session_id.is_some_and(|s| sessions.value(i) != s) || t < start_ts || t > end_ts) {
+                continue;
+            }
+            let null_column = if has_nulls {
+                columns.iter().find(|(_, c)| c.is_null(i)).map(|(name, _)| *name)
+            } else {
+                None
+            };
+            if let Some(name) = null_column {
+                let e = DataLayerError::Decode {
+                    origin: path.display().to_string(),
+                    row: file_row(offset + i),
+                    reason: format!("column {} is null", name),
+                };
+                out.push(Err(e), mode)?;
+                continue;
+            }
+            out.rows.push((sessions.value(i), SensorDataRow {
+                ts_unix_sec: t,
+                hr: hr.value(i),
+                hrv_rmssd: hrv.value(i),
+                eda_mus: eda.value(i),
+                temp_c: temp.value(i),
+                accel_mag_g: accel.value(i),
+                activity: activity.value(i).to_string(),
+                stress_level: stress.value(i),
+                exercise_flag: exercise.value(i),
+                sleep_stage: sleep_stage.filter(|c| c.is_valid(i)).map(|c| c.value(i).to_string()),
+            }));
+        }
+        offset += batch.num_rows();
+    }
+    Ok(out)
+}
+
+/// Read every `(session_id, row)` pair in a sensor_data Parquet file.
+pub(crate) fn read_all_with_sessions(path: &Path) -> Result<Vec<(i64, SensorDataRow)>> {
+    let row_groups = candidate_row_groups(path, None)?.into_iter().map(|(i, _, _)| i).collect();
+    Ok(read_sensor_rows(path, row_groups, None, i64::MIN, i64::MAX, DecodeMode::Strict)?.rows)
+}
+
+/// Row groups of a sensor_data Parquet file with their `ts_unix_sec` ranges.
+///
+/// Row groups whose `session_id` statistics exclude `session_id` (if given) are dropped;
+/// groups without statistics are kept with an unbounded range.
+fn candidate_row_groups(path: &Path, session_id: Option<i64>) -> Result<Vec<(usize, i64, i64)>> {
+    let builder = ParquetRecordBatchReaderBuilder::try_new(File::open(path)?)?;
+    let metadata = builder.metadata();
+    let schema = metadata.file_metadata().schema_descr();
+    let column_index = |name: &str| schema.columns().iter().position(|c| c.name() == name);
+    let ts_col = column_index("ts_unix_sec");
+    let session_col = column_index("session_id");
+
+    let mut out = Vec::new();
+    for (i, rg) in metadata.row_groups().iter().enumerate() {
+        if let (Some(id), Some((lo, hi))) = (session_id, session_col.and_then(|c| row_group_i64_range(rg, c))) {
+            if id < lo || id > hi {
+                continue;
+            }
+        }
+        let (lo, hi) = ts_col
+            .and_then(|c| row_group_i64_range(rg, c))
+            .unwrap_or((i64::MIN, i64::MAX));
+        out.push((i, lo, hi));
+    }
+    Ok(out)
+}
+
+/// Query sensor data for a session in a time range (inclusive) from a Parquet file.
+///
+/// Row groups whose `ts_unix_sec` statistics fall outside the range are never decoded.
+/// Fails on any row that cannot be decoded.
+pub fn query_sensor_data_range_parquet(
+    path: &str,
+    session_id: i64,
+    start_ts: i64,
+    end_ts: i64,
+) -> Result<Vec<SensorDataRow>> {
+    Ok(query_sensor_data_range_parquet_with_mode(path, session_id, start_ts, end_ts, DecodeMode::Strict)?.rows)
+}
+
+/// [`query_sensor_data_range_parquet`] with the given [`DecodeMode`].
+pub fn query_sensor_data_range_parquet_with_mode(
+    path: &str,
+    session_id: i64,
+    start_ts: i64,
+    end_ts: i64,
+    mode: DecodeMode,
+) -> Result<Decoded<SensorDataRow>> {
+    let mut decoded = read_range_in_file(Path::new(path), Some(session_id), start_ts, end_ts, mode)?;
+    decoded.rows.sort_by_key(|r| r.ts_unix_sec);
+    Ok(decoded)
+}
+
+/// Read rows in `[start_ts, end_ts]` from one file, pruning row groups by statistics.
+pub(crate) fn read_range_in_file(
+    path: &Path,
+    session_id: Option<i64>,
+    start_ts: i64,
+    end_ts: i64,
+    mode: DecodeMode,
+) -> Result<Decoded<SensorDataRow>> {
+    let row_groups = candidate_row_groups(path, session_id)?
+        .into_iter()
+        .filter(|&(_, lo, hi)| hi >= start_ts && lo <= end_ts)
+        .map(|(i, _, _)| i)
+        .collect();
+    let decoded = read_sensor_rows(path, row_groups, session_id, start_ts, end_ts, mode)?;
+    Ok(Decoded {
+        rows: decoded.rows.into_iter().map(|(_, row)| row).collect(),
+        skipped: decoded.skipped,
+    })
+}
+
+/// Query the latest N sensor data rows for a session from a Parquet file, newest first.
+///
+/// Row groups are visited newest-first by their `ts_unix_sec` maximum, stopping once
+/// no remaining group can contain a row newer than the N-th collected one.
+/// Fails on any row that cannot be decoded.
+pub fn query_latest_sensor_data_parquet(path: &str, session_id: i64, n: usize) -> Result<Vec<SensorDataRow>> {
+    Ok(query_latest_sensor_data_parquet_with_mode(path, session_id, n, DecodeMode::Strict)?.rows)
+}
+
+/// [`query_latest_sensor_data_parquet`] with the given [`DecodeMode`].
+///
+/// `skipped` counts bad rows in the row groups that were visited.
+pub fn query_latest_sensor_data_parquet_with_mode(
+    path: &str,
+    session_id: i64,
+    n: usize,
+    mode: DecodeMode,
+) -> Result<Decoded<SensorDataRow>> {
+    let mut out = Decoded::default();
+    if n == 0 {
+        return Ok(out);
+    }
+    let path = Path::new(path);
+    let mut groups = candidate_row_groups(path, Some(session_id))?;
+    groups.sort_by_key(|&(_, _, hi)| std::cmp::Reverse(hi));
+
+    for (i, _, hi) in groups {
+        if out.rows.len() >= n && out.rows[n - 1].ts_unix_sec >= hi {
+            break;
+        }
+        let group = read_sensor_rows(path, vec![i], Some(session_id), i64::MIN, i64::MAX, mode)?;
+        out.skipped += group.skipped;
+        out.rows.extend(group.rows.into_iter().map(|(_, row)| row));
+        out.rows.sort_by_key(|r| std::cmp::Reverse(r.ts_unix_sec));
+    }
+    out.rows.truncate(n);
+    Ok(out)
+}
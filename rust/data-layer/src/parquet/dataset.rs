@@ -0,0 +1,395 @@
+//! Hive-partitioned sensor_data dataset: `<root>/user=<id>/date=<yyyy-mm-dd>/part-NNNNN.parquet`.
+//!
+//! User ids are percent-encoded in the `user=` segment: every byte other than an ASCII
+//! letter, digit, `_`, `-` or `.` becomes `%XX`, so ids containing `/`, `\` or `=` stay
+//! inside one directory name.
+
+use super::{read_all_with_sessions, read_range_in_file, sensor_data_schema, SensorParquetOptions, SensorParquetWriter};
+use crate::error::{DataLayerError, Result};
+use crate::query::{DecodeMode, Decoded, SensorDataRow};
+use arrow::array::new_null_array;
+use arrow::record_batch::RecordBatch;
+use core_types::Tier;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::ArrowWriter;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use time::{Date, Month, OffsetDateTime};
+
+/// Files below this size are merged by [`SensorDataset::compact`] by default.
+pub const DEFAULT_SMALL_FILE_BYTES: u64 = 8 * 1024 * 1024;
+
+/// UTC calendar date of a Unix timestamp.
+pub fn date_of(ts_unix_sec: i64) -> Result<Date> {
+    Ok(OffsetDateTime::from_unix_timestamp(ts_unix_sec)?.date())
+}
+
+fn format_date(date: Date) -> String {
+    format!("{:04}-{:02}-{:02}", date.year(), date.month() as u8, date.day())
+}
+
+fn parse_date(s: &str) -> Option<Date> {
+    let mut parts = s.splitn(3, '-');
+    let year = parts.next()?.parse().ok()?;
+    let month = Month::try_from(parts.next()?.parse::<u8>().ok()?).ok()?;
+    let day = parts.next()?.parse().ok()?;
+    Date::from_calendar_date(year, month, day).ok()
+}
+
+/// Percent-encode a user id for the `user=` path segment.
+fn encode_user_id(user_id: &str) -> String {
+    let mut out = String::with_capacity(user_id.len());
+    for b in user_id.bytes() {
+        if b.is_ascii_alphanumeric() || matches!(b, b'_' | b'-' | b'.') {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{:02X}", b));
+        }
+    }
+    out
+}
+
+/// Inverse of [`encode_user_id`]; `None` for malformed escapes or non-UTF-8 ids.
+fn decode_user_id(segment: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(segment.len());
+    let mut rest = segment.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        if b == b'%' {
+            let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(b);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Index N of a `part-N.parquet` file name.
+fn part_index(path: &Path) -> Option<usize> {
+    path.file_name()?
+        .to_str()?
+        .strip_prefix("part-")?
+        .strip_suffix(".parquet")?
+        .parse()
+        .ok()
+}
+
+/// `batch` with its columns arranged as [`sensor_data_schema`].
+///
+/// Missing nullable columns (e.g. `sleep_stage` in files written before it existed) are
+/// filled with nulls; a missing required column or a mistyped one is a schema mismatch.
+fn conform_to_sensor_schema(batch: &RecordBatch) -> Result<RecordBatch> {
+    let schema = sensor_data_schema();
+    let mut columns = Vec::with_capacity(schema.fields().len());
+    for field in schema.fields() {
+        let mismatch = |found: String| DataLayerError::SchemaMismatch {
+            column: field.name().clone(),
+            expected: field.data_type().to_string(),
+            found,
+        };
+        match batch.column_by_name(field.name()) {
+            Some(column) if column.data_type() == field.data_type() => columns.push(column.clone()),
+            Some(column) => return Err(mismatch(column.data_type().to_string())),
+            None if field.is_nullable() => columns.push(new_null_array(field.data_type(), batch.num_rows())),
+            None => return Err(mismatch("missing".to_string())),
+        }
+    }
+    Ok(RecordBatch::try_new(schema, columns)?)
+}
+
+/// One `user=<id>/date=<yyyy-mm-dd>` directory of a dataset.
+#[derive(Debug, Clone)]
+pub struct Partition {
+    /// User id, decoded from the `user=` path segment.
+    pub user_id: String,
+    /// Date from the `date=` path segment.
+    pub date: Date,
+    /// Partition directory.
+    pub path: PathBuf,
+}
+
+impl Partition {
+    /// Part files in this partition, ordered by part index.
+    pub fn files(&self) -> Result<Vec<PathBuf>> {
+        let mut parts: Vec<(usize, PathBuf)> = fs::read_dir(&self.path)?
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter_map(|p| part_index(&p).map(|i| (i, p)))
+            .collect();
+        parts.sort();
+        Ok(parts.into_iter().map(|(_, p)| p).collect())
+    }
+
+    /// Total size of the partition's part files in bytes.
+    pub fn size_bytes(&self) -> Result<u64> {
+        let mut total = 0;
+        for file in self.files()? {
+            total += fs::metadata(file)?.len();
+        }
+        Ok(total)
+    }
+
+    fn next_part_path(&self) -> Result<PathBuf> {
+        let next = self
+            .files()?
+            .iter()
+            .filter_map(|p| part_index(p))
+            .max()
+            .map_or(0, |i| i + 1);
+        Ok(self.path.join(format!("part-{:05}.parquet", next)))
+    }
+}
+
+/// Summary of one [`SensorDataset::compact`] run.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CompactionStats {
+    /// Partitions that were rewritten.
+    pub partitions_compacted: usize,
+    /// Small files merged away.
+    pub files_merged: usize,
+    /// Bytes of the merged input files.
+    pub bytes_before: u64,
+    /// Bytes of the resulting files.
+    pub bytes_after: u64,
+}
+
+/// Partitioned sensor_data dataset rooted at one tier's storage directory.
+pub struct SensorDataset {
+    root: PathBuf,
+    options: SensorParquetOptions,
+}
+
+impl SensorDataset {
+    /// Open (or create) a dataset rooted at `root`.
+    pub fn open(root: impl Into<PathBuf>) -> Result<Self> {
+        let root = root.into();
+        fs::create_dir_all(&root)?;
+        Ok(Self { root, options: SensorParquetOptions::default() })
+    }
+
+    /// Open the dataset for `tier` under `data_dir` (e.g. `data/pro`).
+    pub fn for_tier(data_dir: impl AsRef<Path>, tier: Tier) -> Result<Self> {
+        Self::open(data_dir.as_ref().join(tier.data_dir_name()))
+    }
+
+    /// Use these writer options for new and compacted files.
+    pub fn with_options(mut self, options: SensorParquetOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Root directory of the dataset.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn partition(&self, user_id: &str, date: Date) -> Partition {
+        Partition {
+            user_id: user_id.to_string(),
+            date,
+            path: self
+                .root
+                .join(format!("user={}", encode_user_id(user_id)))
+                .join(format!("date={}", format_date(date))),
+        }
+    }
+
+    /// Write rows for one user/session, one new part file per UTC date touched.
+    ///
+    /// Returns the paths of the files written.
+    pub fn write(&self, user_id: &str, session_id: i64, rows: &[SensorDataRow]) -> Result<Vec<PathBuf>> {
+        let mut by_date: BTreeMap<Date, Vec<&SensorDataRow>> = BTreeMap::new();
+        for row in rows {
+            by_date.entry(date_of(row.ts_unix_sec)?).or_default().push(row);
+        }
+        let mut written = Vec::new();
+        for (date, rows) in by_date {
+            let partition = self.partition(user_id, date);
+            fs::create_dir_all(&partition.path)?;
+            let path = partition.next_part_path()?;
+            let mut writer = SensorParquetWriter::create_with_options(&path, self.options.clone())?;
+            for row in rows {
+                writer.write(session_id, row)?;
+            }
+            writer.close()?;
+            written.push(path);
+        }
+        Ok(written)
+    }
+
+    /// List all partitions, ordered by user and date.
+    pub fn partitions(&self) -> Result<Vec<Partition>> {
+        let mut out = Vec::new();
+        for user_dir in fs::read_dir(&self.root)? {
+            let user_dir = user_dir?.path();
+            let Some(user_id) = user_dir
+                .file_name()
+                .and_then(|n| n.to_str())
+                .and_then(|n| n.strip_prefix("user="))
+                .and_then(decode_user_id)
+            else {
+                continue;
+            };
+            if !user_dir.is_dir() {
+                continue;
+            }
+            for date_dir in fs::read_dir(&user_dir)? {
+                let date_dir = date_dir?.path();
+                let date = date_dir
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .and_then(|n| n.strip_prefix("date="))
+                    .and_then(parse_date);
+                if let (Some(date), true) = (date, date_dir.is_dir()) {
+                    out.push(Partition { user_id: user_id.clone(), date, path: date_dir });
+                }
+            }
+        }
+        out.sort_by(|a, b| (&a.user_id, a.date).cmp(&(&b.user_id, b.date)));
+        Ok(out)
+    }
+
+    /// Total size of all part files in the dataset, in bytes.
+    pub fn size_bytes(&self) -> Result<u64> {
+        let mut total = 0;
+        for partition in self.partitions()? {
+            total += partition.size_bytes()?;
+        }
+        Ok(total)
+    }
+
+    /// Read every `(session_id, row)` pair stored in a partition.
+    pub fn read_partition(&self, partition: &Partition) -> Result<Vec<(i64, SensorDataRow)>> {
+        let mut rows = Vec::new();
+        for file in partition.files()? {
+            rows.extend(read_all_with_sessions(&file)?);
+        }
+        Ok(rows)
+    }
+
+    /// Replace a partition's contents with `rows`, written as a single new part file.
+    pub fn rewrite_partition(&self, partition: &Partition, rows: &[(i64, SensorDataRow)]) -> Result<()> {
+        let old = partition.files()?;
+        let target = partition.next_part_path()?;
+        let tmp = target.with_extension("parquet.tmp");
+        let mut writer = SensorParquetWriter::create_with_options(&tmp, self.options.clone())?;
+        for (session_id, row) in rows {
+            writer.write(*session_id, row)?;
+        }
+        writer.close()?;
+        fs::rename(&tmp, &target)?;
+        for file in old {
+            fs::remove_file(file)?;
+        }
+        Ok(())
+    }
+
+    /// Delete a partition directory, returning the bytes freed.
+    pub fn remove_partition(&self, partition: &Partition) -> Result<u64> {
+        let bytes = partition.size_bytes()?;
+        fs::remove_dir_all(&partition.path)?;
+        Ok(bytes)
+    }
+
+    /// Partitions for `user_id` whose date lies in `[start, end]`.
+    pub fn prune(&self, user_id: &str, start: Date, end: Date) -> Result<Vec<Partition>> {
+        Ok(self
+            .partitions()?
+            .into_iter()
+            .filter(|p| p.user_id == user_id && p.date >= start && p.date <= end)
+            .collect())
+    }
+
+    /// Query a user's rows in a time range (inclusive), optionally for one session.
+    ///
+    /// Only partitions whose date overlaps the range are opened, and within each file
+    /// row groups are pruned by `ts_unix_sec` statistics. Fails on any row that cannot
+    /// be decoded.
+    pub fn query_range(
+        &self,
+        user_id: &str,
+        session_id: Option<i64>,
+        start_ts: i64,
+        end_ts: i64,
+    ) -> Result<Vec<SensorDataRow>> {
+        Ok(self.query_range_with_mode(user_id, session_id, start_ts, end_ts, DecodeMode::Strict)?.rows)
+    }
+
+    /// [`query_range`](Self::query_range) with the given [`DecodeMode`].
+    ///
+    /// `skipped` counts bad rows across every file visited.
+    pub fn query_range_with_mode(
+        &self,
+        user_id: &str,
+        session_id: Option<i64>,
+        start_ts: i64,
+        end_ts: i64,
+        mode: DecodeMode,
+    ) -> Result<Decoded<SensorDataRow>> {
+        let mut out = Decoded::default();
+        for partition in self.prune(user_id, date_of(start_ts)?, date_of(end_ts)?)? {
+            for file in partition.files()? {
+                let decoded = read_range_in_file(&file, session_id, start_ts, end_ts, mode)?;
+                out.skipped += decoded.skipped;
+                out.rows.extend(decoded.rows);
+            }
+        }
+        out.rows.sort_by_key(|r| r.ts_unix_sec);
+        Ok(out)
+    }
+
+    /// Merge each partition's files smaller than `small_file_bytes` into a single file.
+    ///
+    /// Partitions with fewer than two small files are left untouched. Merged files always
+    /// have the current [`sensor_data_schema`], so older files lacking `sleep_stage` can be
+    /// merged with newer ones.
+    pub fn compact(&self, small_file_bytes: u64) -> Result<CompactionStats> {
+        let mut stats = CompactionStats::default();
+        for partition in self.partitions()? {
+            let mut small = Vec::new();
+            for file in partition.files()? {
+                let len = fs::metadata(&file)?.len();
+                if len < small_file_bytes {
+                    small.push((file, len));
+                }
+            }
+            if small.len() < 2 {
+                continue;
+            }
+
+            let target = partition.next_part_path()?;
+            let tmp = target.with_extension("parquet.tmp");
+            let mut writer: Option<ArrowWriter<File>> = None;
+            for (file, _) in &small {
+                let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(file)?)?.build()?;
+                for batch in reader {
+                    let batch = conform_to_sensor_schema(&batch?)?;
+                    let writer = match writer.as_mut() {
+                        Some(w) => w,
+                        None => writer.insert(ArrowWriter::try_new(
+                            File::create(&tmp)?,
+                            sensor_data_schema(),
+                            Some(self.options.writer_properties()),
+                        )?),
+                    };
+                    writer.write(&batch)?;
+                }
+            }
+            let Some(writer) = writer else {
+                continue;
+            };
+            writer.close()?;
+            fs::rename(&tmp, &target)?;
+            for (file, len) in &small {
+                fs::remove_file(file)?;
+                stats.bytes_before += len;
+            }
+            stats.bytes_after += fs::metadata(&target)?.len();
+            stats.files_merged += small.len();
+            stats.partitions_compacted += 1;
+        }
+        Ok(stats)
+    }
+}
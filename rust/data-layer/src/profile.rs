@@ -1,7 +1,16 @@
 //! User profile types and preset archetypes for synthetic data generation.
+//!
+//! Profiles can be loaded from and saved to TOML or JSON files; see
+//! [`UserProfile::load`]. The presets are embedded copies of `profiles/*.toml`.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+use time::Weekday;
 
 /// Activity kind (used in daily schedule).
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ActivityKind {
     /// Sleep state.
     Sleep,
@@ -31,10 +40,74 @@ pub enum ActivityKind {
     GymHigh,
 }
 
+impl ActivityKind {
+    /// Label stored in the `activity` column (same as the serde name).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ActivityKind::Sleep => "sleep",
+            ActivityKind::Sitting => "sitting",
+            ActivityKind::Standing => "standing",
+            ActivityKind::WalkingSlow => "walking_slow",
+            ActivityKind::WalkingBrisk => "walking_brisk",
+            ActivityKind::Stairs => "stairs",
+            ActivityKind::CyclingEasy => "cycling_easy",
+            ActivityKind::CyclingHard => "cycling_hard",
+            ActivityKind::RunningEasy => "running_easy",
+            ActivityKind::RunningModerate => "running_moderate",
+            ActivityKind::RunningHard => "running_hard",
+            ActivityKind::GymModerate => "gym_moderate",
+            ActivityKind::GymHigh => "gym_high",
+        }
+    }
+
+    /// True for running, cycling and gym kinds, which count as exercise sessions.
+    pub fn is_exercise(&self) -> bool {
+        ExerciseType::from_activity(*self).is_some_and(|t| t != ExerciseType::BriskWalking)
+    }
+}
+
+/// Sleep stage, written as ground truth alongside the activity label.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SleepStage {
+    /// Brief awakening during a sleep period.
+    Wake,
+    /// Light sleep (N1/N2).
+    Light,
+    /// Deep slow-wave sleep (N3).
+    Deep,
+    /// Rapid eye movement sleep.
+    Rem,
+}
+
+impl SleepStage {
+    /// Label stored in the `sleep_stage` column.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SleepStage::Wake => "wake",
+            SleepStage::Light => "light",
+            SleepStage::Deep => "deep",
+            SleepStage::Rem => "rem",
+        }
+    }
+
+    /// Parse a `sleep_stage` column label.
+    pub fn from_label(label: &str) -> Option<Self> {
+        match label {
+            "wake" => Some(SleepStage::Wake),
+            "light" => Some(SleepStage::Light),
+            "deep" => Some(SleepStage::Deep),
+            "rem" => Some(SleepStage::Rem),
+            _ => None,
+        }
+    }
+}
+
 /// Exercise type used in exercise schedule.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ExerciseType {
-    /// Running (easy, moderate, hard variants handled via time blocks).
+    /// Running (easy, moderate, hard variants).
     Running,
     /// Cycling (easy, hard variants).
     Cycling,
@@ -44,8 +117,49 @@ pub enum ExerciseType {
     BriskWalking,
 }
 
+impl ExerciseType {
+    /// Activity performed for this exercise type at the given intensity.
+    pub fn activity(&self, intensity: ExerciseIntensity) -> ActivityKind {
+        use ExerciseIntensity::*;
+        match (self, intensity) {
+            (ExerciseType::Running, Low) => ActivityKind::RunningEasy,
+            (ExerciseType::Running, Moderate) => ActivityKind::RunningModerate,
+            (ExerciseType::Running, High) => ActivityKind::RunningHard,
+            (ExerciseType::Cycling, Low | Moderate) => ActivityKind::CyclingEasy,
+            (ExerciseType::Cycling, High) => ActivityKind::CyclingHard,
+            (ExerciseType::Gym, Low | Moderate) => ActivityKind::GymModerate,
+            (ExerciseType::Gym, High) => ActivityKind::GymHigh,
+            (ExerciseType::BriskWalking, _) => ActivityKind::WalkingBrisk,
+        }
+    }
+
+    /// Exercise type an activity belongs to, if any.
+    pub fn from_activity(kind: ActivityKind) -> Option<Self> {
+        match kind {
+            ActivityKind::RunningEasy | ActivityKind::RunningModerate | ActivityKind::RunningHard => Some(ExerciseType::Running),
+            ActivityKind::CyclingEasy | ActivityKind::CyclingHard => Some(ExerciseType::Cycling),
+            ActivityKind::GymModerate | ActivityKind::GymHigh => Some(ExerciseType::Gym),
+            ActivityKind::WalkingBrisk => Some(ExerciseType::BriskWalking),
+            _ => None,
+        }
+    }
+}
+
+/// Intensity of one exercise session, matching the [`IntensityDistribution`] buckets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExerciseIntensity {
+    /// Easy effort.
+    Low,
+    /// Moderate effort.
+    Moderate,
+    /// Hard effort.
+    High,
+}
+
 /// Fitness level (coarse descriptor).
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum FitnessLevel {
     /// Sedentary (<2000 steps/day).
     Sedentary,
@@ -60,7 +174,8 @@ pub enum FitnessLevel {
 }
 
 /// User profile archetype.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ProfileType {
     /// Business professional (sedentary, stressful).
     BusinessProfessional,
@@ -75,7 +190,8 @@ pub enum ProfileType {
 }
 
 /// Distribution of exercise intensity preferences (low/moderate/high).
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct IntensityDistribution {
     /// Fraction preferring low intensity.
     pub low: f32,
@@ -94,10 +210,23 @@ impl IntensityDistribution {
         self.high /= sum;
         self
     }
+
+    /// Intensity at cumulative probability `u` in [0, 1).
+    pub fn pick(&self, u: f32) -> ExerciseIntensity {
+        let d = self.normalized();
+        if u < d.low {
+            ExerciseIntensity::Low
+        } else if u < d.low + d.moderate {
+            ExerciseIntensity::Moderate
+        } else {
+            ExerciseIntensity::High
+        }
+    }
 }
 
 /// A time block in a daily schedule.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct ScheduleBlock {
     /// Start minute of day (0–1439).
     pub start_minute: u16,
@@ -105,33 +234,242 @@ pub struct ScheduleBlock {
     pub duration_min: u16,
     /// Activity kind during this block.
     pub kind: ActivityKind,
+    /// Fixed commitment (a shift, a school run) that keeps the user awake even inside
+    /// the night's sleep window; other blocks give way to sleep there.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub fixed: bool,
 }
 
 /// Repeating daily schedule template.
-#[derive(Debug, Clone)]
+///
+/// Serialized as a plain list of blocks.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct DailySchedule {
     /// Ordered blocks covering the day.
     pub blocks: Vec<ScheduleBlock>,
 }
 
+/// Minutes in a day; schedule blocks must end at or before this.
+pub const MINUTES_PER_DAY: u16 = 1440;
+
+/// A problem found by [`DailySchedule::issues`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScheduleIssue {
+    /// Minutes `[start, end)` are not covered by any block.
+    Gap {
+        /// First uncovered minute.
+        start: u16,
+        /// End of the uncovered range (exclusive).
+        end: u16,
+    },
+    /// Two blocks both cover minutes `[start, end)`.
+    Overlap {
+        /// Index of the earlier block.
+        first: usize,
+        /// Index of the later block.
+        second: usize,
+        /// First doubly-covered minute.
+        start: u16,
+        /// End of the doubly-covered range (exclusive).
+        end: u16,
+    },
+    /// A block starts or ends outside the day (e.g. runs past midnight).
+    OutOfRange {
+        /// Index of the block.
+        block: usize,
+        /// Block start minute.
+        start: u16,
+        /// Block end minute (exclusive).
+        end: u32,
+    },
+    /// A block has zero duration.
+    Empty {
+        /// Index of the block.
+        block: usize,
+    },
+}
+
+impl fmt::Display for ScheduleIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScheduleIssue::Gap { start, end } => write!(f, "minutes {}–{} are not covered", start, end),
+            ScheduleIssue::Overlap { first, second, start, end } => {
+                write!(f, "blocks {} and {} overlap at minutes {}–{}", first, second, start, end)
+            }
+            ScheduleIssue::OutOfRange { block, start, end } => {
+                write!(f, "block {} spans minutes {}–{}, outside 0–{}", block, start, end, MINUTES_PER_DAY)
+            }
+            ScheduleIssue::Empty { block } => write!(f, "block {} has zero duration", block),
+        }
+    }
+}
+
 impl DailySchedule {
     /// Return the current activity kind at a given minute-of-day (0–1439).
+    ///
+    /// Minutes not covered by any block read as `Sitting`; validated schedules have
+    /// no such minutes (see [`issues`](Self::issues) and [`fill_gaps`](Self::fill_gaps)).
     pub fn activity_at_minute(&self, minute: u16) -> ActivityKind {
-        for block in &self.blocks {
+        self.block_at_minute(minute).map_or(ActivityKind::Sitting, |b| b.kind)
+    }
+
+    /// First block covering a given minute-of-day (0–1439), if any.
+    pub fn block_at_minute(&self, minute: u16) -> Option<&ScheduleBlock> {
+        let m = minute as u32;
+        self.blocks.iter().find(|block| {
+            let start = block.start_minute as u32;
+            m >= start && m < start + block.duration_min as u32
+        })
+    }
+
+    /// Gaps, overlaps, out-of-range and empty blocks, ordered by minute.
+    ///
+    /// A valid schedule covers every minute of the day exactly once.
+    pub fn issues(&self) -> Vec<ScheduleIssue> {
+        let mut issues = Vec::new();
+        let mut order: Vec<usize> = (0..self.blocks.len()).collect();
+        order.sort_by_key(|&i| self.blocks[i].start_minute);
+
+        // End of coverage so far, and the block that reaches it.
+        let mut covered_to: u32 = 0;
+        let mut last: Option<usize> = None;
+        for i in order {
+            let block = &self.blocks[i];
             let start = block.start_minute as u32;
             let end = start + block.duration_min as u32;
-            let m = minute as u32;
-            if m >= start && m < end {
-                return block.kind;
+            if block.duration_min == 0 {
+                issues.push(ScheduleIssue::Empty { block: i });
+                continue;
+            }
+            let clipped_start = start.min(MINUTES_PER_DAY as u32);
+            let clipped_end = end.min(MINUTES_PER_DAY as u32);
+            if clipped_start > covered_to {
+                issues.push(ScheduleIssue::Gap { start: covered_to as u16, end: clipped_start as u16 });
+            } else if let (Some(prev), true) = (last, clipped_start < covered_to) {
+                issues.push(ScheduleIssue::Overlap {
+                    first: prev,
+                    second: i,
+                    start: clipped_start as u16,
+                    end: clipped_end.min(covered_to) as u16,
+                });
+            }
+            if end > MINUTES_PER_DAY as u32 {
+                issues.push(ScheduleIssue::OutOfRange { block: i, start: block.start_minute, end });
+            }
+            if clipped_end > covered_to {
+                covered_to = clipped_end;
+                last = Some(i);
             }
         }
-        ActivityKind::Sitting // fallback
+        if covered_to < MINUTES_PER_DAY as u32 {
+            issues.push(ScheduleIssue::Gap { start: covered_to as u16, end: MINUTES_PER_DAY });
+        }
+        issues
+    }
+
+    /// Cover every uncovered minute with a block of `kind`, keeping blocks sorted by start.
+    ///
+    /// Returns the number of minutes filled. Overlaps and out-of-range blocks are left alone.
+    pub fn fill_gaps(&mut self, kind: ActivityKind) -> u32 {
+        let mut filled = 0;
+        for issue in self.issues() {
+            if let ScheduleIssue::Gap { start, end } = issue {
+                self.blocks.push(ScheduleBlock { start_minute: start, duration_min: end - start, kind, fixed: false });
+                filled += (end - start) as u32;
+            }
+        }
+        self.blocks.sort_by_key(|b| b.start_minute);
+        filled
+    }
+}
+
+/// Weekday template plus an optional weekend (Saturday/Sunday) template.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WeeklySchedule {
+    /// Schedule for Monday–Friday, and for weekends when `weekend` is absent.
+    pub weekday: DailySchedule,
+    /// Schedule for Saturday and Sunday.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weekend: Option<DailySchedule>,
+}
+
+impl WeeklySchedule {
+    /// The same template every day of the week.
+    pub fn every_day(schedule: DailySchedule) -> Self {
+        Self { weekday: schedule, weekend: None }
+    }
+
+    /// Template for a given day of the week.
+    pub fn for_weekday(&self, day: Weekday) -> &DailySchedule {
+        match (day, &self.weekend) {
+            (Weekday::Saturday | Weekday::Sunday, Some(weekend)) => weekend,
+            _ => &self.weekday,
+        }
+    }
+
+    /// Template for the UTC day containing `ts_unix_sec`.
+    pub fn for_timestamp(&self, ts_unix_sec: i64) -> &DailySchedule {
+        // 1970-01-01 was a Thursday.
+        let day = Weekday::Thursday.nth_next((ts_unix_sec.div_euclid(86_400).rem_euclid(7)) as u8);
+        self.for_weekday(day)
+    }
+
+    /// Activity at `ts_unix_sec`, using the right day's template.
+    pub fn activity_at(&self, ts_unix_sec: i64) -> ActivityKind {
+        let minute = (ts_unix_sec.div_euclid(60).rem_euclid(MINUTES_PER_DAY as i64)) as u16;
+        self.for_timestamp(ts_unix_sec).activity_at_minute(minute)
+    }
+
+    /// True if `ts_unix_sec` falls in a block marked [`fixed`](ScheduleBlock::fixed).
+    pub fn is_fixed_at(&self, ts_unix_sec: i64) -> bool {
+        let minute = (ts_unix_sec.div_euclid(60).rem_euclid(MINUTES_PER_DAY as i64)) as u16;
+        self.for_timestamp(ts_unix_sec).block_at_minute(minute).is_some_and(|b| b.fixed)
+    }
+
+    /// Issues in each template, labelled `weekday` or `weekend`.
+    pub fn issues(&self) -> Vec<(&'static str, ScheduleIssue)> {
+        let mut issues: Vec<_> = self.weekday.issues().into_iter().map(|i| ("weekday", i)).collect();
+        if let Some(weekend) = &self.weekend {
+            issues.extend(weekend.issues().into_iter().map(|i| ("weekend", i)));
+        }
+        issues
+    }
+}
+
+/// Circadian rhythm amplitudes; the phase follows the typical sleep window.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CircadianParams {
+    /// HR swing around resting HR (± bpm), lowest at the core-temperature trough.
+    pub hr_amplitude_bpm: f32,
+    /// Skin temperature swing (± °C), highest at the core-temperature trough.
+    pub skin_temp_amplitude_c: f32,
+    /// Skin temperature rise over the first hour of sleep (°C).
+    pub sleep_onset_temp_rise_c: f32,
+    /// Relative EDA swing (fraction of baseline), lowest at the core-temperature trough.
+    pub eda_amplitude: f32,
+}
+
+impl Default for CircadianParams {
+    fn default() -> Self {
+        Self {
+            hr_amplitude_bpm: 4.0,
+            skin_temp_amplitude_c: 0.3,
+            sleep_onset_temp_rise_c: 0.5,
+            eda_amplitude: 0.15,
+        }
     }
 }
 
 /// Complete user profile for synthetic data generation.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct UserProfile {
+    /// Display name for custom cohorts (e.g. "Night-shift nurse").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
     /// Profile archetype.
     pub profile_type: ProfileType,
     /// Age in years.
@@ -156,20 +494,208 @@ pub struct UserProfile {
     pub typical_sleep_start_min: u16,
     /// Typical sleep duration in minutes.
     pub typical_sleep_duration_min: u16,
-    /// Daily activity schedule.
-    pub activity_schedule: DailySchedule,
+    /// Night-to-night variation in bedtime and duration (± minutes).
+    #[serde(default = "default_sleep_jitter_min")]
+    pub sleep_jitter_min: u16,
+    /// Activity schedule, with optional weekend variant.
+    pub activity_schedule: WeeklySchedule,
     /// Stress sensitivity (0.0–1.0); higher → more responsive to stressors.
     pub stress_sensitivity: f32,
+    /// Circadian rhythm amplitudes.
+    #[serde(default)]
+    pub circadian: CircadianParams,
 
     // Exercise
     /// Types of exercise the user does.
     pub exercise_types: Vec<ExerciseType>,
-    /// Target exercise frequency (times per week).
+    /// Target exercise frequency (times per week, at most 14).
     pub exercise_frequency_per_week: usize,
     /// Preferred intensity distribution.
     pub exercise_intensity_pref: IntensityDistribution,
 }
 
+/// Errors raised while loading or saving a [`UserProfile`].
+#[derive(Debug)]
+pub enum ProfileError {
+    /// Reading or writing the profile file failed.
+    Io(std::io::Error),
+    /// The file is not valid TOML/JSON for a profile.
+    Parse(String),
+    /// The profile could not be serialized to TOML/JSON.
+    Serialize(String),
+    /// The profile parsed but failed validation; one message per problem.
+    Invalid(Vec<String>),
+    /// The file extension is neither `.toml` nor `.json`.
+    UnsupportedFormat(String),
+}
+
+impl fmt::Display for ProfileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProfileError::Io(e) => write!(f, "io error: {}", e),
+            ProfileError::Parse(e) => write!(f, "failed to parse profile: {}", e),
+            ProfileError::Serialize(e) => write!(f, "failed to serialize profile: {}", e),
+            ProfileError::Invalid(problems) => write!(f, "invalid profile: {}", problems.join("; ")),
+            ProfileError::UnsupportedFormat(ext) => {
+                write!(f, "unsupported profile format {:?} (expected .toml or .json)", ext)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ProfileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ProfileError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ProfileError {
+    fn from(e: std::io::Error) -> Self {
+        ProfileError::Io(e)
+    }
+}
+
+impl UserProfile {
+    /// Minute of day of the core-temperature trough, about two hours before typical wake time.
+    pub fn circadian_trough_min(&self) -> u16 {
+        let wake = self.typical_sleep_start_min as u32 + self.typical_sleep_duration_min as u32;
+        ((wake + 2 * MINUTES_PER_DAY as u32 - 120) % MINUTES_PER_DAY as u32) as u16
+    }
+
+    /// Parse and validate a profile from TOML.
+    pub fn from_toml_str(s: &str) -> Result<Self, ProfileError> {
+        let profile: Self = toml::from_str(s).map_err(|e| ProfileError::Parse(e.to_string()))?;
+        profile.validated()
+    }
+
+    /// Parse and validate a profile from JSON.
+    pub fn from_json_str(s: &str) -> Result<Self, ProfileError> {
+        let profile: Self = serde_json::from_str(s).map_err(|e| ProfileError::Parse(e.to_string()))?;
+        profile.validated()
+    }
+
+    /// Serialize to TOML.
+    pub fn to_toml_string(&self) -> Result<String, ProfileError> {
+        toml::to_string_pretty(self).map_err(|e| ProfileError::Serialize(e.to_string()))
+    }
+
+    /// Serialize to pretty-printed JSON.
+    pub fn to_json_string(&self) -> Result<String, ProfileError> {
+        serde_json::to_string_pretty(self).map_err(|e| ProfileError::Serialize(e.to_string()))
+    }
+
+    /// Load a profile from a `.toml` or `.json` file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ProfileError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        match extension(path).as_str() {
+            "toml" => Self::from_toml_str(&text),
+            "json" => Self::from_json_str(&text),
+            other => Err(ProfileError::UnsupportedFormat(other.to_string())),
+        }
+    }
+
+    /// Save the profile to a `.toml` or `.json` file, chosen by extension.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ProfileError> {
+        let path = path.as_ref();
+        let text = match extension(path).as_str() {
+            "toml" => self.to_toml_string()?,
+            "json" => self.to_json_string()?,
+            other => return Err(ProfileError::UnsupportedFormat(other.to_string())),
+        };
+        std::fs::write(path, text)?;
+        Ok(())
+    }
+
+    /// Check physiological ranges and schedule sanity, reporting every problem found.
+    pub fn validate(&self) -> Result<(), ProfileError> {
+        let mut problems = Vec::new();
+        let mut check = |ok: bool, msg: String| {
+            if !ok {
+                problems.push(msg);
+            }
+        };
+        check(self.age > 0, "age must be positive".to_string());
+        check(
+            self.resting_hr > 20.0 && self.resting_hr < self.max_hr && self.max_hr <= 240.0,
+            format!("expected 20 < resting_hr ({}) < max_hr ({}) <= 240", self.resting_hr, self.max_hr),
+        );
+        check(self.hrv_baseline > 0.0, format!("hrv_baseline ({}) must be positive", self.hrv_baseline));
+        check(
+            (30.0..=42.0).contains(&self.baseline_temp),
+            format!("baseline_temp ({}) must be within 30–42 °C", self.baseline_temp),
+        );
+        check(self.baseline_eda >= 0.0, format!("baseline_eda ({}) must not be negative", self.baseline_eda));
+        check(
+            self.typical_sleep_start_min < 1440,
+            format!("typical_sleep_start_min ({}) must be below 1440", self.typical_sleep_start_min),
+        );
+        check(
+            (1..=1440).contains(&self.typical_sleep_duration_min),
+            format!("typical_sleep_duration_min ({}) must be within 1–1440", self.typical_sleep_duration_min),
+        );
+        check(
+            self.sleep_jitter_min <= 240,
+            format!("sleep_jitter_min ({}) must be at most 240", self.sleep_jitter_min),
+        );
+        check(
+            (0.0..=1.0).contains(&self.stress_sensitivity),
+            format!("stress_sensitivity ({}) must be within 0–1", self.stress_sensitivity),
+        );
+        let c = &self.circadian;
+        check(
+            (0.0..=20.0).contains(&c.hr_amplitude_bpm),
+            format!("circadian.hr_amplitude_bpm ({}) must be within 0–20", c.hr_amplitude_bpm),
+        );
+        check(
+            (0.0..=2.0).contains(&c.skin_temp_amplitude_c) && (0.0..=2.0).contains(&c.sleep_onset_temp_rise_c),
+            "circadian temperature terms must be within 0–2 °C".to_string(),
+        );
+        check(
+            (0.0..1.0).contains(&c.eda_amplitude),
+            format!("circadian.eda_amplitude ({}) must be within 0–1", c.eda_amplitude),
+        );
+        check(
+            self.exercise_frequency_per_week <= 14,
+            format!("exercise_frequency_per_week ({}) must be at most 14", self.exercise_frequency_per_week),
+        );
+        let pref = &self.exercise_intensity_pref;
+        check(
+            pref.low >= 0.0 && pref.moderate >= 0.0 && pref.high >= 0.0 && pref.low + pref.moderate + pref.high > 0.0,
+            "exercise_intensity_pref must be non-negative and not all zero".to_string(),
+        );
+        for (template, issue) in self.activity_schedule.issues() {
+            problems.push(format!("{} schedule: {}", template, issue));
+        }
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(ProfileError::Invalid(problems))
+        }
+    }
+
+    /// Validate, then normalize the intensity preferences.
+    fn validated(mut self) -> Result<Self, ProfileError> {
+        self.validate()?;
+        self.exercise_intensity_pref = self.exercise_intensity_pref.normalized();
+        Ok(self)
+    }
+}
+
+fn default_sleep_jitter_min() -> u16 {
+    30
+}
+
+pub(crate) fn extension(path: &Path) -> String {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
 /// Map an activity kind to a nominal intensity [0.0, 1.0].
 pub fn activity_intensity(kind: ActivityKind) -> f32 {
     match kind {
@@ -238,201 +764,109 @@ pub fn activity_accel_noise(kind: ActivityKind) -> f32 {
     }
 }
 
-/// Preset profiles.
+/// Physiological signature of a sleep stage, relative to the resting baseline.
+#[derive(Debug, Clone, Copy)]
+pub struct SleepSignature {
+    /// HR offset from resting HR (bpm).
+    pub hr_delta: f32,
+    /// HR noise amplitude (± bpm).
+    pub hr_noise: f32,
+    /// Multiplier on the HRV baseline.
+    pub hrv_factor: f32,
+    /// Skin temperature offset from the circadian baseline (°C).
+    pub temp_delta: f32,
+    /// Mean accel magnitude (g).
+    pub accel_mean: f32,
+    /// Accel noise amplitude (± g).
+    pub accel_noise: f32,
+}
+
+/// Map a sleep stage to its HR, HRV, temperature and movement signature.
+///
+/// Deep sleep has the lowest, steadiest HR and the highest HRV; REM has near-wake HR
+/// with high variability, low HRV and muscle atonia; wake bouts bring movement.
+/// Temperature offsets are small: the nightly skin-temperature rise is circadian.
+pub fn sleep_stage_signature(stage: SleepStage) -> SleepSignature {
+    match stage {
+        SleepStage::Wake => SleepSignature {
+            hr_delta: 6.0,
+            hr_noise: 3.0,
+            hrv_factor: 0.9,
+            temp_delta: -0.15,
+            accel_mean: 0.40,
+            accel_noise: 0.25,
+        },
+        SleepStage::Light => SleepSignature {
+            hr_delta: -3.0,
+            hr_noise: 1.5,
+            hrv_factor: 1.1,
+            temp_delta: 0.0,
+            accel_mean: 0.15,
+            accel_noise: 0.08,
+        },
+        SleepStage::Deep => SleepSignature {
+            hr_delta: -7.0,
+            hr_noise: 0.8,
+            hrv_factor: 1.4,
+            temp_delta: 0.05,
+            accel_mean: 0.05,
+            accel_noise: 0.02,
+        },
+        SleepStage::Rem => SleepSignature {
+            hr_delta: 2.0,
+            hr_noise: 4.0,
+            hrv_factor: 0.8,
+            temp_delta: -0.10,
+            accel_mean: 0.04,
+            accel_noise: 0.03,
+        },
+    }
+}
+
+/// Preset profiles, embedded from `profiles/*.toml`.
 pub mod presets {
-    use super::*;
+    use super::UserProfile;
+
+    /// Preset name and embedded TOML source, in display order.
+    pub const FILES: &[(&str, &str)] = &[
+        ("business_professional", include_str!("../profiles/business_professional.toml")),
+        ("college_student", include_str!("../profiles/college_student.toml")),
+        ("pro_athlete", include_str!("../profiles/pro_athlete.toml")),
+        ("shift_worker", include_str!("../profiles/shift_worker.toml")),
+        ("remote_worker", include_str!("../profiles/remote_worker.toml")),
+    ];
+
+    /// Load a preset by file name (e.g. `"shift_worker"`).
+    pub fn by_name(name: &str) -> Option<UserProfile> {
+        FILES.iter().find(|(n, _)| *n == name).map(|(n, src)| parse(n, src))
+    }
+
+    fn parse(name: &str, src: &str) -> UserProfile {
+        UserProfile::from_toml_str(src).unwrap_or_else(|e| panic!("embedded preset {} is invalid: {}", name, e))
+    }
 
     /// Create a BusinessProfessional profile.
     pub fn business_professional() -> UserProfile {
-        UserProfile {
-            profile_type: ProfileType::BusinessProfessional,
-            age: 35,
-            fitness_level: FitnessLevel::Low,
-            resting_hr: 68.0,
-            max_hr: 185.0,
-            hrv_baseline: 45.0,
-            baseline_temp: 36.2,
-            baseline_eda: 2.5,
-            typical_sleep_start_min: 22 * 60 + 30, // 22:30
-            typical_sleep_duration_min: 7 * 60 + 30, // 7h 30m
-            activity_schedule: DailySchedule {
-                blocks: vec![
-                    ScheduleBlock { start_minute: 0, duration_min: 360, kind: ActivityKind::Sleep },
-                    ScheduleBlock { start_minute: 360, duration_min: 60, kind: ActivityKind::Standing },
-                    ScheduleBlock { start_minute: 420, duration_min: 60, kind: ActivityKind::Sitting },
-                    ScheduleBlock { start_minute: 480, duration_min: 240, kind: ActivityKind::Sitting },
-                    ScheduleBlock { start_minute: 720, duration_min: 25, kind: ActivityKind::Sitting },
-                    ScheduleBlock { start_minute: 745, duration_min: 35, kind: ActivityKind::WalkingSlow },
-                    ScheduleBlock { start_minute: 780, duration_min: 240, kind: ActivityKind::Sitting },
-                    ScheduleBlock { start_minute: 1020, duration_min: 60, kind: ActivityKind::WalkingBrisk },
-                    ScheduleBlock { start_minute: 1080, duration_min: 60, kind: ActivityKind::Standing },
-                    ScheduleBlock { start_minute: 1140, duration_min: 120, kind: ActivityKind::Sitting },
-                    ScheduleBlock { start_minute: 1260, duration_min: 60, kind: ActivityKind::Sitting },
-                    ScheduleBlock { start_minute: 1320, duration_min: 180, kind: ActivityKind::Sleep },
-                ],
-            },
-            stress_sensitivity: 0.7,
-            exercise_types: vec![ExerciseType::Running, ExerciseType::Gym, ExerciseType::BriskWalking],
-            exercise_frequency_per_week: 3,
-            exercise_intensity_pref: IntensityDistribution {
-                low: 0.3,
-                moderate: 0.5,
-                high: 0.2,
-            }
-            .normalized(),
-        }
+        parse("business_professional", FILES[0].1)
     }
+
     /// Create a CollegeStudent profile.
     pub fn college_student() -> UserProfile {
-        UserProfile {
-            profile_type: ProfileType::CollegeStudent,
-            age: 21,
-            fitness_level: FitnessLevel::Moderate,
-            resting_hr: 62.0,
-            max_hr: 195.0,
-            hrv_baseline: 55.0,
-            baseline_temp: 36.4,
-            baseline_eda: 2.2,
-            typical_sleep_start_min: 1 * 60, // 1:00 AM
-            typical_sleep_duration_min: 6 * 60 + 30, // 6h 30m
-            activity_schedule: DailySchedule {
-                blocks: vec![
-                    ScheduleBlock { start_minute: 0, duration_min: 360, kind: ActivityKind::Sleep },
-                    ScheduleBlock { start_minute: 360, duration_min: 60, kind: ActivityKind::Sitting },
-                    ScheduleBlock { start_minute: 420, duration_min: 90, kind: ActivityKind::WalkingBrisk },
-                    ScheduleBlock { start_minute: 510, duration_min: 180, kind: ActivityKind::Sitting },
-                    ScheduleBlock { start_minute: 690, duration_min: 60, kind: ActivityKind::Standing },
-                    ScheduleBlock { start_minute: 750, duration_min: 120, kind: ActivityKind::WalkingSlow },
-                    ScheduleBlock { start_minute: 870, duration_min: 120, kind: ActivityKind::Sitting },
-                    ScheduleBlock { start_minute: 990, duration_min: 60, kind: ActivityKind::GymModerate },
-                    ScheduleBlock { start_minute: 1050, duration_min: 120, kind: ActivityKind::Sitting },
-                    ScheduleBlock { start_minute: 1170, duration_min: 90, kind: ActivityKind::WalkingBrisk },
-                    ScheduleBlock { start_minute: 1260, duration_min: 180, kind: ActivityKind::Sleep },
-                ],
-            },
-            stress_sensitivity: 0.5,
-            exercise_types: vec![ExerciseType::Running, ExerciseType::Gym, ExerciseType::BriskWalking],
-            exercise_frequency_per_week: 4,
-            exercise_intensity_pref: IntensityDistribution {
-                low: 0.2,
-                moderate: 0.6,
-                high: 0.2,
-            }.normalized(),
-        }
+        parse("college_student", FILES[1].1)
     }
 
     /// Create a ProAthlete profile.
     pub fn pro_athlete() -> UserProfile {
-        UserProfile {
-            profile_type: ProfileType::ProAthlete,
-            age: 28,
-            fitness_level: FitnessLevel::VeryActive,
-            resting_hr: 48.0,
-            max_hr: 205.0,
-            hrv_baseline: 80.0,
-            baseline_temp: 36.0,
-            baseline_eda: 2.0,
-            typical_sleep_start_min: 23 * 60, // 23:00
-            typical_sleep_duration_min: 8 * 60, // 8h
-            activity_schedule: DailySchedule {
-                blocks: vec![
-                    ScheduleBlock { start_minute: 0, duration_min: 420, kind: ActivityKind::Sleep },
-                    ScheduleBlock { start_minute: 420, duration_min: 60, kind: ActivityKind::RunningModerate },
-                    ScheduleBlock { start_minute: 480, duration_min: 120, kind: ActivityKind::Sitting },
-                    ScheduleBlock { start_minute: 600, duration_min: 60, kind: ActivityKind::GymHigh },
-                    ScheduleBlock { start_minute: 660, duration_min: 120, kind: ActivityKind::Standing },
-                    ScheduleBlock { start_minute: 780, duration_min: 60, kind: ActivityKind::WalkingBrisk },
-                    ScheduleBlock { start_minute: 840, duration_min: 120, kind: ActivityKind::CyclingHard },
-                    ScheduleBlock { start_minute: 960, duration_min: 120, kind: ActivityKind::Sitting },
-                    ScheduleBlock { start_minute: 1080, duration_min: 60, kind: ActivityKind::RunningEasy },
-                    ScheduleBlock { start_minute: 1140, duration_min: 120, kind: ActivityKind::Standing },
-                    ScheduleBlock { start_minute: 1260, duration_min: 180, kind: ActivityKind::Sleep },
-                ],
-            },
-            stress_sensitivity: 0.2,
-            exercise_types: vec![ExerciseType::Running, ExerciseType::Cycling, ExerciseType::Gym],
-            exercise_frequency_per_week: 10,
-            exercise_intensity_pref: IntensityDistribution {
-                low: 0.1,
-                moderate: 0.3,
-                high: 0.6,
-            }.normalized(),
-        }
+        parse("pro_athlete", FILES[2].1)
     }
 
     /// Create a ShiftWorker profile.
     pub fn shift_worker() -> UserProfile {
-        UserProfile {
-            profile_type: ProfileType::ShiftWorker,
-            age: 40,
-            fitness_level: FitnessLevel::Moderate,
-            resting_hr: 70.0,
-            max_hr: 180.0,
-            hrv_baseline: 40.0,
-            baseline_temp: 36.3,
-            baseline_eda: 2.7,
-            typical_sleep_start_min: 4 * 60, // 4:00 AM
-            typical_sleep_duration_min: 6 * 60, // 6h
-            activity_schedule: DailySchedule {
-                blocks: vec![
-                    ScheduleBlock { start_minute: 0, duration_min: 240, kind: ActivityKind::Sitting },
-                    ScheduleBlock { start_minute: 240, duration_min: 360, kind: ActivityKind::Standing },
-                    ScheduleBlock { start_minute: 600, duration_min: 120, kind: ActivityKind::WalkingSlow },
-                    ScheduleBlock { start_minute: 720, duration_min: 120, kind: ActivityKind::Sitting },
-                    ScheduleBlock { start_minute: 840, duration_min: 120, kind: ActivityKind::Sleep },
-                    ScheduleBlock { start_minute: 960, duration_min: 120, kind: ActivityKind::Standing },
-                    ScheduleBlock { start_minute: 1080, duration_min: 120, kind: ActivityKind::WalkingBrisk },
-                    ScheduleBlock { start_minute: 1200, duration_min: 120, kind: ActivityKind::Sitting },
-                    ScheduleBlock { start_minute: 1320, duration_min: 120, kind: ActivityKind::Sleep },
-                ],
-            },
-            stress_sensitivity: 0.8,
-            exercise_types: vec![ExerciseType::BriskWalking, ExerciseType::Gym],
-            exercise_frequency_per_week: 2,
-            exercise_intensity_pref: IntensityDistribution {
-                low: 0.5,
-                moderate: 0.4,
-                high: 0.1,
-            }.normalized(),
-        }
+        parse("shift_worker", FILES[3].1)
     }
 
     /// Create a RemoteWorker profile.
     pub fn remote_worker() -> UserProfile {
-        UserProfile {
-            profile_type: ProfileType::RemoteWorker,
-            age: 32,
-            fitness_level: FitnessLevel::Active,
-            resting_hr: 64.0,
-            max_hr: 190.0,
-            hrv_baseline: 50.0,
-            baseline_temp: 36.3,
-            baseline_eda: 2.3,
-            typical_sleep_start_min: 23 * 60 + 30, // 23:30
-            typical_sleep_duration_min: 7 * 60 + 30, // 7h 30m
-            activity_schedule: DailySchedule {
-                blocks: vec![
-                    ScheduleBlock { start_minute: 0, duration_min: 390, kind: ActivityKind::Sleep },
-                    ScheduleBlock { start_minute: 390, duration_min: 60, kind: ActivityKind::Standing },
-                    ScheduleBlock { start_minute: 450, duration_min: 120, kind: ActivityKind::Sitting },
-                    ScheduleBlock { start_minute: 570, duration_min: 60, kind: ActivityKind::WalkingBrisk },
-                    ScheduleBlock { start_minute: 630, duration_min: 180, kind: ActivityKind::Sitting },
-                    ScheduleBlock { start_minute: 810, duration_min: 60, kind: ActivityKind::Standing },
-                    ScheduleBlock { start_minute: 870, duration_min: 120, kind: ActivityKind::Sitting },
-                    ScheduleBlock { start_minute: 990, duration_min: 60, kind: ActivityKind::WalkingSlow },
-                    ScheduleBlock { start_minute: 1050, duration_min: 120, kind: ActivityKind::Sitting },
-                    ScheduleBlock { start_minute: 1170, duration_min: 90, kind: ActivityKind::WalkingBrisk },
-                    ScheduleBlock { start_minute: 1260, duration_min: 180, kind: ActivityKind::Sleep },
-                ],
-            },
-            stress_sensitivity: 0.4,
-            exercise_types: vec![ExerciseType::Running, ExerciseType::BriskWalking],
-            exercise_frequency_per_week: 5,
-            exercise_intensity_pref: IntensityDistribution {
-                low: 0.2,
-                moderate: 0.5,
-                high: 0.3,
-            }.normalized(),
-        }
+        parse("remote_worker", FILES[4].1)
     }
 }
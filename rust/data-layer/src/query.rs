@@ -1,6 +1,59 @@
 //! Query functions for sensor_data in SQLite.
 
-use rusqlite::{Connection, Result, Row};
+use crate::error::{DataLayerError, Result};
+use crate::rollup::{ActivityMinutes, Resolution, RollupRow, SignalStats};
+use core_types::{Reading, Sample, SignalKind};
+use rusqlite::{Connection, Row};
+
+/// How queries treat rows that fail to decode.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DecodeMode {
+    /// Fail on the first bad row.
+    #[default]
+    Strict,
+    /// Skip bad rows and report how many were skipped.
+    Lenient,
+}
+
+/// Rows returned by a query, with the number of undecodable rows skipped.
+#[derive(Debug, Clone)]
+pub struct Decoded<T> {
+    /// Successfully decoded rows.
+    pub rows: Vec<T>,
+    /// Rows skipped in [`DecodeMode::Lenient`] (always 0 in strict mode).
+    pub skipped: usize,
+}
+
+impl<T> Default for Decoded<T> {
+    fn default() -> Self {
+        Self { rows: Vec::new(), skipped: 0 }
+    }
+}
+
+impl<T> Decoded<T> {
+    /// Add one decode result, failing or counting a skip according to `mode`.
+    pub(crate) fn push(&mut self, row: Result<T>, mode: DecodeMode) -> Result<()> {
+        match row {
+            Ok(row) => self.rows.push(row),
+            Err(e) if mode == DecodeMode::Lenient && e.is_row_error() => self.skipped += 1,
+            Err(e) => return Err(e),
+        }
+        Ok(())
+    }
+}
+
+/// Decode every row produced by `rows`, attributing failures to `origin`.
+fn collect_rows<T>(
+    origin: &str,
+    rows: impl Iterator<Item = rusqlite::Result<T>>,
+    mode: DecodeMode,
+) -> Result<Decoded<T>> {
+    let mut out = Decoded::default();
+    for (i, row) in rows.enumerate() {
+        out.push(row.map_err(|e| DataLayerError::from_row_error(origin, i, e)), mode)?;
+    }
+    Ok(out)
+}
 
 /// A single row of sensor data from the database.
 #[derive(Debug, Clone)]
@@ -23,9 +76,55 @@ pub struct SensorDataRow {
     pub stress_level: f32,
     /// True if exercising during this sample.
     pub exercise_flag: bool,
+    /// Ground-truth sleep stage label (wake/light/deep/rem); `None` outside sleep.
+    pub sleep_stage: Option<String>,
 }
 
 impl SensorDataRow {
+    /// Signals stored as columns, in column order.
+    pub const SIGNALS: [SignalKind; 5] = [
+        SignalKind::HeartRate,
+        SignalKind::HrvRmssd,
+        SignalKind::Eda,
+        SignalKind::SkinTemp,
+        SignalKind::AccelMag,
+    ];
+
+    /// Value of the column holding `kind`, or `None` if the row has no such column.
+    pub fn value(&self, kind: SignalKind) -> Option<f32> {
+        match kind {
+            SignalKind::HeartRate => Some(self.hr),
+            SignalKind::HrvRmssd => Some(self.hrv_rmssd),
+            SignalKind::Eda => Some(self.eda_mus),
+            SignalKind::SkinTemp => Some(self.temp_c),
+            SignalKind::AccelMag => Some(self.accel_mag_g),
+            _ => None,
+        }
+    }
+
+    /// Store `value` in the column holding `kind`; returns false if there is no such column.
+    pub fn set_value(&mut self, kind: SignalKind, value: f32) -> bool {
+        let column = match kind {
+            SignalKind::HeartRate => &mut self.hr,
+            SignalKind::HrvRmssd => &mut self.hrv_rmssd,
+            SignalKind::Eda => &mut self.eda_mus,
+            SignalKind::SkinTemp => &mut self.temp_c,
+            SignalKind::AccelMag => &mut self.accel_mag_g,
+            _ => return false,
+        };
+        *column = value;
+        true
+    }
+
+    /// The row's signal columns as tagged readings.
+    pub fn readings(&self) -> Vec<Reading> {
+        let ts_ms = self.ts_unix_sec * 1000;
+        Self::SIGNALS
+            .iter()
+            .filter_map(|&kind| self.value(kind).map(|v| Reading::new(kind, ts_ms, v)))
+            .collect()
+    }
+
     fn from_row(row: &Row) -> rusqlite::Result<Self> {
         Ok(Self {
             ts_unix_sec: row.get("ts_unix_sec")?,
@@ -37,26 +136,168 @@ impl SensorDataRow {
             activity: row.get("activity")?,
             stress_level: row.get("stress_level")?,
             exercise_flag: row.get("exercise_flag")?,
+            sleep_stage: row.get("sleep_stage")?,
         })
     }
 }
 
-/// Query the latest N sensor data rows for a session.
+/// A labelled ground-truth event (e.g. an injected anomaly) covering `[start_ts, end_ts)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventRow {
+    /// Event type label (e.g. `tachycardia`, `dropout`).
+    pub kind: String,
+    /// Start, Unix seconds (inclusive).
+    pub start_ts: i64,
+    /// End, Unix seconds (exclusive).
+    pub end_ts: i64,
+    /// Event size in its own unit (bpm, °C, severity), if it has one.
+    pub magnitude: Option<f32>,
+}
+
+/// Query the latest N sensor data rows for a session, failing on any undecodable row.
 pub fn query_latest_sensor_data(conn: &Connection, session_id: i64, n: usize) -> Result<Vec<SensorDataRow>> {
+    Ok(query_latest_sensor_data_with_mode(conn, session_id, n, DecodeMode::Strict)?.rows)
+}
+
+/// Query the latest N sensor data rows for a session with the given [`DecodeMode`].
+pub fn query_latest_sensor_data_with_mode(
+    conn: &Connection,
+    session_id: i64,
+    n: usize,
+    mode: DecodeMode,
+) -> Result<Decoded<SensorDataRow>> {
     let mut stmt = conn.prepare(
-        "SELECT ts_unix_sec, hr, hrv_rmssd, eda_mus, temp_c, accel_mag_g, activity, stress_level, exercise_flag \
+        "SELECT ts_unix_sec, hr, hrv_rmssd, eda_mus, temp_c, accel_mag_g, activity, stress_level, exercise_flag, sleep_stage \
          FROM sensor_data WHERE session_id = ?1 ORDER BY ts_unix_sec DESC LIMIT ?2"
     )?;
-    let rows = stmt.query_map([session_id, n as i64], |row| SensorDataRow::from_row(row))?;
-    Ok(rows.filter_map(Result::ok).collect())
+    let rows = stmt.query_map([session_id, n as i64], SensorDataRow::from_row)?;
+    collect_rows("sensor_data", rows, mode)
 }
 
-/// Query sensor data for a session in a time range (inclusive).
+/// Query sensor data for a session in a time range (inclusive), failing on any undecodable row.
 pub fn query_sensor_data_range(conn: &Connection, session_id: i64, start_ts: i64, end_ts: i64) -> Result<Vec<SensorDataRow>> {
+    Ok(query_sensor_data_range_with_mode(conn, session_id, start_ts, end_ts, DecodeMode::Strict)?.rows)
+}
+
+/// Query sensor data for a session in a time range (inclusive) with the given [`DecodeMode`].
+pub fn query_sensor_data_range_with_mode(
+    conn: &Connection,
+    session_id: i64,
+    start_ts: i64,
+    end_ts: i64,
+    mode: DecodeMode,
+) -> Result<Decoded<SensorDataRow>> {
     let mut stmt = conn.prepare(
-        "SELECT ts_unix_sec, hr, hrv_rmssd, eda_mus, temp_c, accel_mag_g, activity, stress_level, exercise_flag \
+        "SELECT ts_unix_sec, hr, hrv_rmssd, eda_mus, temp_c, accel_mag_g, activity, stress_level, exercise_flag, sleep_stage \
          FROM sensor_data WHERE session_id = ?1 AND ts_unix_sec >= ?2 AND ts_unix_sec <= ?3 ORDER BY ts_unix_sec ASC"
     )?;
-    let rows = stmt.query_map([session_id, start_ts, end_ts], |row| SensorDataRow::from_row(row))?;
-    Ok(rows.filter_map(Result::ok).collect())
+    let rows = stmt.query_map([session_id, start_ts, end_ts], SensorDataRow::from_row)?;
+    collect_rows("sensor_data", rows, mode)
+}
+
+/// Query events for a session that overlap `[start_ts, end_ts]`, ordered by start.
+pub fn query_events(conn: &Connection, session_id: i64, start_ts: i64, end_ts: i64) -> Result<Vec<EventRow>> {
+    let mut stmt = conn.prepare(
+        "SELECT kind, start_ts, end_ts, magnitude FROM events \
+         WHERE session_id = ?1 AND end_ts > ?2 AND start_ts <= ?3 ORDER BY start_ts ASC, id ASC"
+    )?;
+    let rows = stmt.query_map([session_id, start_ts, end_ts], |row| {
+        Ok(EventRow {
+            kind: row.get("kind")?,
+            start_ts: row.get("start_ts")?,
+            end_ts: row.get("end_ts")?,
+            magnitude: row.get("magnitude")?,
+        })
+    })?;
+    Ok(collect_rows("events", rows, DecodeMode::Strict)?.rows)
+}
+
+fn sample_from_row(row: &Row) -> rusqlite::Result<Sample> {
+    Ok(Sample {
+        ts_ms: row.get("ts_ms")?,
+        value: row.get("value")?,
+    })
+}
+
+/// Query the latest N samples for a stream, newest first.
+pub fn query_latest_samples(conn: &Connection, stream: &str, n: usize) -> Result<Vec<Sample>> {
+    let mut stmt = conn.prepare(
+        "SELECT ts_ms, value FROM samples WHERE stream = ?1 ORDER BY ts_ms DESC LIMIT ?2"
+    )?;
+    let rows = stmt.query_map(rusqlite::params![stream, n as i64], sample_from_row)?;
+    Ok(collect_rows("samples", rows, DecodeMode::Strict)?.rows)
+}
+
+/// Query samples for a stream in a time range (inclusive, milliseconds).
+pub fn query_samples_range(conn: &Connection, stream: &str, start_ms: i64, end_ms: i64) -> Result<Vec<Sample>> {
+    let mut stmt = conn.prepare(
+        "SELECT ts_ms, value FROM samples WHERE stream = ?1 AND ts_ms >= ?2 AND ts_ms <= ?3 ORDER BY ts_ms ASC"
+    )?;
+    let rows = stmt.query_map(rusqlite::params![stream, start_ms, end_ms], sample_from_row)?;
+    Ok(collect_rows("samples", rows, DecodeMode::Strict)?.rows)
+}
+
+fn rollup_from_row(row: &Row) -> rusqlite::Result<RollupRow> {
+    let stats = |signal: &str| -> rusqlite::Result<SignalStats> {
+        Ok(SignalStats {
+            min: row.get(format!("{}_min", signal).as_str())?,
+            mean: row.get(format!("{}_mean", signal).as_str())?,
+            max: row.get(format!("{}_max", signal).as_str())?,
+            p95: row.get(format!("{}_p95", signal).as_str())?,
+        })
+    };
+    let name: String = row.get("resolution")?;
+    let Some(resolution) = Resolution::from_name(&name) else {
+        let column = row.as_ref().column_index("resolution")?;
+        let reason = format!("unknown resolution {:?}", name);
+        return Err(rusqlite::Error::FromSqlConversionFailure(column, rusqlite::types::Type::Text, reason.into()));
+    };
+    Ok(RollupRow {
+        resolution,
+        bucket_start: row.get("bucket_start")?,
+        sample_count: row.get::<_, i64>("sample_count")? as usize,
+        hr: stats("hr")?,
+        hrv_rmssd: stats("hrv_rmssd")?,
+        eda_mus: stats("eda_mus")?,
+        temp_c: stats("temp_c")?,
+        accel_mag_g: stats("accel_mag_g")?,
+    })
+}
+
+/// Query rollups for a session at one resolution, for buckets starting in a time range (inclusive).
+pub fn query_rollups(
+    conn: &Connection,
+    session_id: i64,
+    resolution: Resolution,
+    start_ts: i64,
+    end_ts: i64,
+) -> Result<Vec<RollupRow>> {
+    let mut stmt = conn.prepare(
+        "SELECT * FROM sensor_rollup WHERE session_id = ?1 AND resolution = ?2 \
+         AND bucket_start >= ?3 AND bucket_start <= ?4 ORDER BY bucket_start ASC"
+    )?;
+    let rows = stmt.query_map(rusqlite::params![session_id, resolution.as_str(), start_ts, end_ts], rollup_from_row)?;
+    Ok(collect_rows("sensor_rollup", rows, DecodeMode::Strict)?.rows)
+}
+
+/// Query activity-minute counts for a session at one resolution, for buckets starting in a time range (inclusive).
+pub fn query_activity_minutes(
+    conn: &Connection,
+    session_id: i64,
+    resolution: Resolution,
+    start_ts: i64,
+    end_ts: i64,
+) -> Result<Vec<ActivityMinutes>> {
+    let mut stmt = conn.prepare(
+        "SELECT bucket_start, activity, minutes FROM activity_rollup WHERE session_id = ?1 AND resolution = ?2 \
+         AND bucket_start >= ?3 AND bucket_start <= ?4 ORDER BY bucket_start ASC, minutes DESC"
+    )?;
+    let rows = stmt.query_map(rusqlite::params![session_id, resolution.as_str(), start_ts, end_ts], |row| {
+        Ok(ActivityMinutes {
+            bucket_start: row.get("bucket_start")?,
+            activity: row.get("activity")?,
+            minutes: row.get("minutes")?,
+        })
+    })?;
+    Ok(collect_rows("activity_rollup", rows, DecodeMode::Strict)?.rows)
 }
@@ -0,0 +1,434 @@
+//! Per-tier storage quotas and retention enforcement across SQLite and Parquet.
+
+use crate::parquet::dataset::{date_of, Partition};
+use crate::parquet::SensorDataset;
+use crate::query::SensorDataRow;
+use core_types::Tier;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs;
+use time::Date;
+
+const SECS_PER_DAY: i64 = 86_400;
+
+/// Marker file left in a partition once its rows have been downsampled.
+const DOWNSAMPLED_MARKER: &str = "_DOWNSAMPLED";
+
+/// What happens to raw data older than the retention window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpiredData {
+    /// Delete it outright.
+    Delete,
+    /// Replace it with one averaged row per `bucket_secs` per session.
+    Downsample {
+        /// Aggregation bucket width in seconds.
+        bucket_secs: i64,
+    },
+}
+
+/// Retention rules for one tier.
+#[derive(Debug, Clone, Copy)]
+pub struct RetentionPolicy {
+    /// Days of raw (full-rate) data kept.
+    pub raw_retention_days: u32,
+    /// Storage budget in bytes across SQLite and Parquet.
+    pub max_bytes: u64,
+    /// Handling of raw data older than `raw_retention_days`.
+    pub expired: ExpiredData,
+}
+
+impl RetentionPolicy {
+    /// Default policy for a device tier.
+    pub fn for_tier(tier: Tier) -> Self {
+        const MB: u64 = 1024 * 1024;
+        match tier {
+            Tier::Mini8 => Self {
+                raw_retention_days: 7,
+                max_bytes: 128 * MB,
+                expired: ExpiredData::Delete,
+            },
+            Tier::Standard16 => Self {
+                raw_retention_days: 30,
+                max_bytes: 256 * MB,
+                expired: ExpiredData::Downsample { bucket_secs: 60 },
+            },
+            Tier::Pro32 => Self {
+                raw_retention_days: 180,
+                max_bytes: 512 * MB,
+                expired: ExpiredData::Downsample { bucket_secs: 60 },
+            },
+        }
+    }
+}
+
+/// Bytes used by each storage backend.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StorageUsage {
+    /// Bytes in live (non-free) SQLite pages.
+    pub sqlite_bytes: u64,
+    /// Bytes of Parquet part files.
+    pub parquet_bytes: u64,
+}
+
+impl StorageUsage {
+    /// Combined usage in bytes.
+    pub fn total(&self) -> u64 {
+        self.sqlite_bytes + self.parquet_bytes
+    }
+}
+
+/// One eviction performed by [`RetentionManager::enforce`].
+#[derive(Debug, Clone)]
+pub enum Eviction {
+    /// SQLite rows older than `before_ts` were deleted.
+    SqliteDeleted {
+        /// Table the rows were deleted from.
+        table: &'static str,
+        /// Exclusive cutoff (Unix seconds).
+        before_ts: i64,
+        /// Rows deleted.
+        rows: usize,
+    },
+    /// SQLite rows older than `before_ts` were replaced by bucket averages.
+    SqliteDownsampled {
+        /// Table that was downsampled.
+        table: &'static str,
+        /// Exclusive cutoff (Unix seconds).
+        before_ts: i64,
+        /// Raw rows removed.
+        rows_in: usize,
+        /// Aggregated rows inserted.
+        rows_out: usize,
+    },
+    /// A Parquet partition was deleted.
+    PartitionDeleted {
+        /// Partition user id.
+        user_id: String,
+        /// Partition date.
+        date: Date,
+        /// Bytes freed.
+        bytes: u64,
+    },
+    /// A Parquet partition was rewritten with bucket averages.
+    PartitionDownsampled {
+        /// Partition user id.
+        user_id: String,
+        /// Partition date.
+        date: Date,
+        /// Rows before downsampling.
+        rows_in: usize,
+        /// Rows after downsampling.
+        rows_out: usize,
+    },
+}
+
+impl fmt::Display for Eviction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Eviction::SqliteDeleted { table, before_ts, rows } => {
+                write!(f, "deleted {} rows from {} before ts {}", rows, table, before_ts)
+            }
+            Eviction::SqliteDownsampled { table, before_ts, rows_in, rows_out } => write!(
+                f,
+                "downsampled {} rows of {} before ts {} into {}",
+                rows_in, table, before_ts, rows_out
+            ),
+            Eviction::PartitionDeleted { user_id, date, bytes } => {
+                write!(f, "deleted partition user={}/date={} ({} bytes)", user_id, date, bytes)
+            }
+            Eviction::PartitionDownsampled { user_id, date, rows_in, rows_out } => write!(
+                f,
+                "downsampled partition user={}/date={} from {} to {} rows",
+                user_id, date, rows_in, rows_out
+            ),
+        }
+    }
+}
+
+/// Outcome of one [`RetentionManager::enforce`] run.
+#[derive(Debug, Clone, Default)]
+pub struct RetentionReport {
+    /// Usage before enforcement.
+    pub before: StorageUsage,
+    /// Usage after enforcement.
+    pub after: StorageUsage,
+    /// Evictions in the order they were applied.
+    pub evictions: Vec<Eviction>,
+}
+
+/// Applies a [`RetentionPolicy`] to a tier's SQLite database and Parquet dataset.
+pub struct RetentionManager<'a> {
+    policy: RetentionPolicy,
+    conn: Option<&'a Connection>,
+    dataset: Option<&'a SensorDataset>,
+}
+
+impl<'a> RetentionManager<'a> {
+    /// Create a manager using the default policy for `tier`.
+    pub fn new(tier: Tier) -> Self {
+        Self::with_policy(RetentionPolicy::for_tier(tier))
+    }
+
+    /// Create a manager with an explicit policy.
+    pub fn with_policy(policy: RetentionPolicy) -> Self {
+        Self { policy, conn: None, dataset: None }
+    }
+
+    /// Manage the `sensor_data` and `samples` tables of this database.
+    pub fn sqlite(mut self, conn: &'a Connection) -> Self {
+        self.conn = Some(conn);
+        self
+    }
+
+    /// Manage the partitions of this Parquet dataset.
+    pub fn dataset(mut self, dataset: &'a SensorDataset) -> Self {
+        self.dataset = Some(dataset);
+        self
+    }
+
+    /// The policy being enforced.
+    pub fn policy(&self) -> &RetentionPolicy {
+        &self.policy
+    }
+
+    /// Measure current storage usage.
+    pub fn usage(&self) -> anyhow::Result<StorageUsage> {
+        let sqlite_bytes = match self.conn {
+            Some(conn) => sqlite_used_bytes(conn)?,
+            None => 0,
+        };
+        let parquet_bytes = match self.dataset {
+            Some(dataset) => dataset.size_bytes()?,
+            None => 0,
+        };
+        Ok(StorageUsage { sqlite_bytes, parquet_bytes })
+    }
+
+    /// Enforce the retention window and then the byte budget, as of `now_ts` (Unix seconds).
+    ///
+    /// Raw data older than the window is deleted or downsampled per the policy. If usage
+    /// still exceeds `max_bytes`, whole days are evicted oldest-first across both backends.
+    pub fn enforce(&self, now_ts: i64) -> anyhow::Result<RetentionReport> {
+        let mut report = RetentionReport { before: self.usage()?, ..Default::default() };
+        let cutoff = now_ts - self.policy.raw_retention_days as i64 * SECS_PER_DAY;
+
+        if let Some(conn) = self.conn {
+            self.expire_sqlite(conn, cutoff, &mut report.evictions)?;
+        }
+        if let Some(dataset) = self.dataset {
+            self.expire_parquet(dataset, cutoff, &mut report.evictions)?;
+        }
+        self.enforce_budget(&mut report.evictions)?;
+
+        report.after = self.usage()?;
+        Ok(report)
+    }
+
+    fn expire_sqlite(&self, conn: &Connection, cutoff: i64, out: &mut Vec<Eviction>) -> anyhow::Result<()> {
+        match self.policy.expired {
+            ExpiredData::Delete => delete_sqlite_before(conn, cutoff, out),
+            ExpiredData::Downsample { bucket_secs } => {
+                let tx = conn.unchecked_transaction()?;
+                let (rows_in, rows_out) = downsample_sensor_data(&tx, cutoff, bucket_secs)?;
+                if rows_in > 0 {
+                    out.push(Eviction::SqliteDownsampled { table: "sensor_data", before_ts: cutoff, rows_in, rows_out });
+                }
+                let (rows_in, rows_out) = downsample_samples(&tx, cutoff, bucket_secs)?;
+                if rows_in > 0 {
+                    out.push(Eviction::SqliteDownsampled { table: "samples", before_ts: cutoff, rows_in, rows_out });
+                }
+                tx.commit()?;
+                Ok(())
+            }
+        }
+    }
+
+    fn expire_parquet(&self, dataset: &SensorDataset, cutoff: i64, out: &mut Vec<Eviction>) -> anyhow::Result<()> {
+        // Only partitions lying entirely before the cutoff are touched.
+        let cutoff_date = date_of(cutoff)?;
+        for partition in dataset.partitions()?.into_iter().filter(|p| p.date < cutoff_date) {
+            match self.policy.expired {
+                ExpiredData::Delete => {
+                    let bytes = dataset.remove_partition(&partition)?;
+                    out.push(Eviction::PartitionDeleted { user_id: partition.user_id, date: partition.date, bytes });
+                }
+                ExpiredData::Downsample { bucket_secs } => {
+                    let marker = partition.path.join(DOWNSAMPLED_MARKER);
+                    if marker.exists() {
+                        continue;
+                    }
+                    let rows = dataset.read_partition(&partition)?;
+                    let downsampled = downsample_rows(&rows, bucket_secs);
+                    dataset.rewrite_partition(&partition, &downsampled)?;
+                    fs::write(&marker, b"")?;
+                    out.push(Eviction::PartitionDownsampled {
+                        user_id: partition.user_id,
+                        date: partition.date,
+                        rows_in: rows.len(),
+                        rows_out: downsampled.len(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn enforce_budget(&self, out: &mut Vec<Eviction>) -> anyhow::Result<()> {
+        loop {
+            if self.usage()?.total() <= self.policy.max_bytes {
+                return Ok(());
+            }
+            let oldest_partition = match self.dataset {
+                Some(dataset) => dataset.partitions()?.into_iter().min_by_key(|p| p.date).map(|p| (dataset, p)),
+                None => None,
+            };
+            let oldest_sqlite_day = match self.conn {
+                Some(conn) => oldest_sqlite_ts(conn)?.map(|ts| (conn, ts.div_euclid(SECS_PER_DAY) * SECS_PER_DAY)),
+                None => None,
+            };
+            match (oldest_partition, oldest_sqlite_day) {
+                (Some((dataset, partition)), sqlite)
+                    if sqlite.is_none_or(|(_, day)| partition_start(&partition) <= day) =>
+                {
+                    let bytes = dataset.remove_partition(&partition)?;
+                    out.push(Eviction::PartitionDeleted { user_id: partition.user_id, date: partition.date, bytes });
+                }
+                (_, Some((conn, day))) => delete_sqlite_before(conn, day + SECS_PER_DAY, out)?,
+                _ => return Ok(()),
+            }
+        }
+    }
+}
+
+fn partition_start(partition: &Partition) -> i64 {
+    partition.date.midnight().assume_utc().unix_timestamp()
+}
+
+/// Bytes held by live SQLite pages (free-list pages excluded).
+fn sqlite_used_bytes(conn: &Connection) -> rusqlite::Result<u64> {
+    let page_count: i64 = conn.pragma_query_value(None, "page_count", |r| r.get(0))?;
+    let freelist: i64 = conn.pragma_query_value(None, "freelist_count", |r| r.get(0))?;
+    let page_size: i64 = conn.pragma_query_value(None, "page_size", |r| r.get(0))?;
+    Ok(((page_count - freelist).max(0) * page_size) as u64)
+}
+
+fn oldest_sqlite_ts(conn: &Connection) -> rusqlite::Result<Option<i64>> {
+    let sensor: Option<i64> = conn
+        .query_row("SELECT MIN(ts_unix_sec) FROM sensor_data", [], |r| r.get(0))
+        .optional()?
+        .flatten();
+    let samples: Option<i64> = conn
+        .query_row("SELECT MIN(ts_ms) / 1000 FROM samples", [], |r| r.get(0))
+        .optional()?
+        .flatten();
+    Ok(match (sensor, samples) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    })
+}
+
+fn delete_sqlite_before(conn: &Connection, before_ts: i64, out: &mut Vec<Eviction>) -> anyhow::Result<()> {
+    let rows = conn.execute("DELETE FROM sensor_data WHERE ts_unix_sec < ?1", [before_ts])?;
+    if rows > 0 {
+        out.push(Eviction::SqliteDeleted { table: "sensor_data", before_ts, rows });
+    }
+    let rows = conn.execute("DELETE FROM samples WHERE ts_ms < ?1", [before_ts * 1000])?;
+    if rows > 0 {
+        out.push(Eviction::SqliteDeleted { table: "samples", before_ts, rows });
+    }
+    Ok(())
+}
+
+/// Replace sensor_data rows before `cutoff` with per-session bucket averages.
+///
+/// Buckets already holding a single row are left alone, so repeated runs are no-ops.
+fn downsample_sensor_data(conn: &Connection, cutoff: i64, bucket_secs: i64) -> rusqlite::Result<(usize, usize)> {
+    let max_id: i64 = conn.query_row("SELECT COALESCE(MAX(id), 0) FROM sensor_data", [], |r| r.get(0))?;
+    let rows_out = conn.execute(
+        "INSERT INTO sensor_data (session_id, ts_unix_sec, hr, hrv_rmssd, eda_mus, temp_c, accel_mag_g, activity, stress_level, exercise_flag, sleep_stage, created_at) \
+         SELECT s.session_id, (s.ts_unix_sec / ?1) * ?1, AVG(s.hr), AVG(s.hrv_rmssd), AVG(s.eda_mus), AVG(s.temp_c), AVG(s.accel_mag_g), \
+             (SELECT a.activity FROM sensor_data a \
+              WHERE a.session_id = s.session_id AND a.ts_unix_sec / ?1 = s.ts_unix_sec / ?1 AND a.ts_unix_sec < ?2 \
+              GROUP BY a.activity ORDER BY COUNT(*) DESC LIMIT 1), \
+             AVG(s.stress_level), MAX(s.exercise_flag), \
+             (SELECT a.sleep_stage FROM sensor_data a \
+              WHERE a.session_id = s.session_id AND a.ts_unix_sec / ?1 = s.ts_unix_sec / ?1 AND a.ts_unix_sec < ?2 \
+              GROUP BY a.sleep_stage ORDER BY COUNT(*) DESC LIMIT 1), \
+             CURRENT_TIMESTAMP \
+         FROM sensor_data s WHERE s.ts_unix_sec < ?2 \
+         GROUP BY s.session_id, s.ts_unix_sec / ?1 HAVING COUNT(*) > 1",
+        params![bucket_secs, cutoff],
+    )?;
+    let rows_in = conn.execute(
+        "DELETE FROM sensor_data WHERE id <= ?1 AND ts_unix_sec < ?2 \
+         AND (session_id, ts_unix_sec / ?3) IN (SELECT session_id, ts_unix_sec / ?3 FROM sensor_data WHERE id > ?1)",
+        params![max_id, cutoff, bucket_secs],
+    )?;
+    Ok((rows_in, rows_out))
+}
+
+/// Replace samples before `cutoff` with per-stream bucket averages.
+fn downsample_samples(conn: &Connection, cutoff: i64, bucket_secs: i64) -> rusqlite::Result<(usize, usize)> {
+    let bucket_ms = bucket_secs * 1000;
+    let cutoff_ms = cutoff * 1000;
+    let max_id: i64 = conn.query_row("SELECT COALESCE(MAX(id), 0) FROM samples", [], |r| r.get(0))?;
+    let rows_out = conn.execute(
+        "INSERT INTO samples (stream, ts_ms, value) \
+         SELECT stream, (ts_ms / ?1) * ?1, AVG(value) FROM samples WHERE ts_ms < ?2 \
+         GROUP BY stream, ts_ms / ?1 HAVING COUNT(*) > 1",
+        params![bucket_ms, cutoff_ms],
+    )?;
+    let rows_in = conn.execute(
+        "DELETE FROM samples WHERE id <= ?1 AND ts_ms < ?2 \
+         AND (stream, ts_ms / ?3) IN (SELECT stream, ts_ms / ?3 FROM samples WHERE id > ?1)",
+        params![max_id, cutoff_ms, bucket_ms],
+    )?;
+    Ok((rows_in, rows_out))
+}
+
+/// Most frequent value (ties broken towards the smaller value).
+fn most_frequent<T: Ord + std::hash::Hash + Copy>(values: impl Iterator<Item = T>) -> Option<T> {
+    let mut counts: HashMap<T, usize> = HashMap::new();
+    for value in values {
+        *counts.entry(value).or_default() += 1;
+    }
+    counts
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(&a.0)))
+        .map(|(value, _)| value)
+}
+
+/// Average `(session_id, row)` pairs into one row per session per `bucket_secs`.
+///
+/// Numeric signals are averaged, `activity` and `sleep_stage` take the most frequent
+/// label and `exercise_flag` is set if any input row had it.
+fn downsample_rows(rows: &[(i64, SensorDataRow)], bucket_secs: i64) -> Vec<(i64, SensorDataRow)> {
+    let mut buckets: BTreeMap<(i64, i64), Vec<&SensorDataRow>> = BTreeMap::new();
+    for (session_id, row) in rows {
+        let bucket = row.ts_unix_sec.div_euclid(bucket_secs) * bucket_secs;
+        buckets.entry((bucket, *session_id)).or_default().push(row);
+    }
+    buckets
+        .into_iter()
+        .map(|((ts, session_id), group)| {
+            let n = group.len() as f32;
+            let mean = |f: fn(&SensorDataRow) -> f32| group.iter().map(|r| f(r)).sum::<f32>() / n;
+            let activity = most_frequent(group.iter().map(|r| r.activity.as_str())).unwrap_or_default();
+            let sleep_stage = most_frequent(group.iter().map(|r| r.sleep_stage.as_deref())).flatten();
+            let row = SensorDataRow {
+                ts_unix_sec: ts,
+                hr: mean(|r| r.hr),
+                hrv_rmssd: mean(|r| r.hrv_rmssd),
+                eda_mus: mean(|r| r.eda_mus),
+                temp_c: mean(|r| r.temp_c),
+                accel_mag_g: mean(|r| r.accel_mag_g),
+                activity: activity.to_string(),
+                stress_level: mean(|r| r.stress_level),
+                exercise_flag: group.iter().any(|r| r.exercise_flag),
+                sleep_stage: sleep_stage.map(str::to_string),
+            };
+            (session_id, row)
+        })
+        .collect()
+}
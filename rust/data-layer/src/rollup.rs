@@ -0,0 +1,319 @@
+//! Minute/hour/day rollups of sensor_data, maintained incrementally on ingest.
+
+use crate::error::Result;
+use crate::query::{query_rollups, query_sensor_data_range, SensorDataRow};
+use core_types::SignalKind;
+use rusqlite::{params, Connection};
+use std::collections::BTreeMap;
+
+/// Signals summarized in `sensor_rollup`, in column order.
+pub const ROLLUP_SIGNALS: [&str; 5] = ["hr", "hrv_rmssd", "eda_mus", "temp_c", "accel_mag_g"];
+
+/// Rollup bucket width.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Resolution {
+    /// One-minute buckets.
+    Minute,
+    /// One-hour buckets.
+    Hour,
+    /// One-day (UTC) buckets.
+    Day,
+}
+
+impl Resolution {
+    /// All resolutions, finest first.
+    pub const ALL: [Resolution; 3] = [Resolution::Minute, Resolution::Hour, Resolution::Day];
+
+    /// Bucket width in seconds.
+    pub fn secs(&self) -> i64 {
+        match self {
+            Resolution::Minute => 60,
+            Resolution::Hour => 3_600,
+            Resolution::Day => 86_400,
+        }
+    }
+
+    /// Value stored in the `resolution` column.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Resolution::Minute => "minute",
+            Resolution::Hour => "hour",
+            Resolution::Day => "day",
+        }
+    }
+
+    /// Parse a value of the `resolution` column.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|r| r.as_str() == name)
+    }
+
+    /// Start of the bucket containing `ts_unix_sec`.
+    pub fn bucket_start(&self, ts_unix_sec: i64) -> i64 {
+        ts_unix_sec.div_euclid(self.secs()) * self.secs()
+    }
+}
+
+/// Summary statistics of one signal over a bucket.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SignalStats {
+    /// Minimum value.
+    pub min: f32,
+    /// Arithmetic mean.
+    pub mean: f32,
+    /// Maximum value.
+    pub max: f32,
+    /// 95th percentile (nearest-rank).
+    pub p95: f32,
+}
+
+impl SignalStats {
+    /// Compute statistics over `values`; returns `None` when empty.
+    pub fn from_values(values: &mut [f32]) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+        values.sort_by(|a, b| a.total_cmp(b));
+        let n = values.len();
+        let rank = ((0.95 * n as f64).ceil() as usize).clamp(1, n) - 1;
+        Some(Self {
+            min: values[0],
+            mean: values.iter().sum::<f32>() / n as f32,
+            max: values[n - 1],
+            p95: values[rank],
+        })
+    }
+}
+
+/// One row of `sensor_rollup`.
+#[derive(Debug, Clone)]
+pub struct RollupRow {
+    /// Bucket width.
+    pub resolution: Resolution,
+    /// Bucket start (Unix seconds).
+    pub bucket_start: i64,
+    /// Raw rows summarized.
+    pub sample_count: usize,
+    /// Heart rate (bpm).
+    pub hr: SignalStats,
+    /// Heart rate variability (RMSSD, ms).
+    pub hrv_rmssd: SignalStats,
+    /// Electrodermal activity (μS).
+    pub eda_mus: SignalStats,
+    /// Skin temperature (°C).
+    pub temp_c: SignalStats,
+    /// Accelerometer magnitude (g).
+    pub accel_mag_g: SignalStats,
+}
+
+impl RollupRow {
+    /// Statistics for `kind`, or `None` if it is not rolled up.
+    pub fn stats(&self, kind: SignalKind) -> Option<&SignalStats> {
+        match kind {
+            SignalKind::HeartRate => Some(&self.hr),
+            SignalKind::HrvRmssd => Some(&self.hrv_rmssd),
+            SignalKind::Eda => Some(&self.eda_mus),
+            SignalKind::SkinTemp => Some(&self.temp_c),
+            SignalKind::AccelMag => Some(&self.accel_mag_g),
+            _ => None,
+        }
+    }
+}
+
+/// Minutes spent in one activity within a bucket.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActivityMinutes {
+    /// Bucket start (Unix seconds).
+    pub bucket_start: i64,
+    /// Activity label.
+    pub activity: String,
+    /// Minutes whose majority label was `activity`.
+    pub minutes: u32,
+}
+
+fn summarize(resolution: Resolution, bucket_start: i64, rows: &[&SensorDataRow]) -> Option<RollupRow> {
+    let stats = |f: fn(&SensorDataRow) -> f32| {
+        let mut values: Vec<f32> = rows.iter().map(|r| f(r)).collect();
+        SignalStats::from_values(&mut values)
+    };
+    Some(RollupRow {
+        resolution,
+        bucket_start,
+        sample_count: rows.len(),
+        hr: stats(|r| r.hr)?,
+        hrv_rmssd: stats(|r| r.hrv_rmssd)?,
+        eda_mus: stats(|r| r.eda_mus)?,
+        temp_c: stats(|r| r.temp_c)?,
+        accel_mag_g: stats(|r| r.accel_mag_g)?,
+    })
+}
+
+/// Most frequent activity label (ties broken alphabetically).
+fn majority_activity(rows: &[&SensorDataRow]) -> Option<String> {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for row in rows {
+        *counts.entry(row.activity.as_str()).or_default() += 1;
+    }
+    counts
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(a.0)))
+        .map(|(a, _)| a.to_string())
+}
+
+/// Weighted merge of per-bucket statistics, each paired with its sample count.
+///
+/// Min, max and mean are exact; p95 is the nearest-rank 95th percentile of the parts'
+/// p95 values weighted by their sample counts, an approximation of the true percentile.
+fn merge_stats(parts: &mut [(usize, SignalStats)]) -> SignalStats {
+    let total: usize = parts.iter().map(|(n, _)| n).sum();
+    parts.sort_by(|a, b| a.1.p95.total_cmp(&b.1.p95));
+    let rank = (0.95 * total as f64).ceil() as usize;
+    let mut seen = 0;
+    let p95 = parts
+        .iter()
+        .find(|(n, _)| {
+            seen += n;
+            seen >= rank
+        })
+        .map_or(0.0, |(_, s)| s.p95);
+    SignalStats {
+        min: parts.iter().map(|(_, s)| s.min).fold(f32::INFINITY, f32::min),
+        mean: (parts.iter().map(|(n, s)| *n as f64 * s.mean as f64).sum::<f64>() / total as f64) as f32,
+        max: parts.iter().map(|(_, s)| s.max).fold(f32::NEG_INFINITY, f32::max),
+        p95,
+    }
+}
+
+/// Combine finer rollups into one bucket at `resolution`; `None` if `parts` is empty.
+fn merge_rollups(resolution: Resolution, bucket_start: i64, parts: &[&RollupRow]) -> Option<RollupRow> {
+    if parts.is_empty() {
+        return None;
+    }
+    let merge = |f: fn(&RollupRow) -> SignalStats| {
+        merge_stats(&mut parts.iter().map(|r| (r.sample_count, f(r))).collect::<Vec<_>>())
+    };
+    Some(RollupRow {
+        resolution,
+        bucket_start,
+        sample_count: parts.iter().map(|r| r.sample_count).sum(),
+        hr: merge(|r| r.hr),
+        hrv_rmssd: merge(|r| r.hrv_rmssd),
+        eda_mus: merge(|r| r.eda_mus),
+        temp_c: merge(|r| r.temp_c),
+        accel_mag_g: merge(|r| r.accel_mag_g),
+    })
+}
+
+/// Delete a session's rollups at `resolution` for buckets starting in `[lo, hi]`.
+fn clear_buckets(conn: &Connection, session_id: i64, resolution: Resolution, lo: i64, hi: i64) -> Result<()> {
+    for table in ["sensor_rollup", "activity_rollup"] {
+        conn.execute(
+            &format!(
+                "DELETE FROM {} WHERE session_id = ?1 AND resolution = ?2 AND bucket_start >= ?3 AND bucket_start <= ?4",
+                table
+            ),
+            params![session_id, resolution.as_str(), lo, hi],
+        )?;
+    }
+    Ok(())
+}
+
+fn insert_rollup(conn: &Connection, session_id: i64, rollup: &RollupRow) -> Result<()> {
+    let columns: Vec<String> = ROLLUP_SIGNALS
+        .iter()
+        .flat_map(|s| ["min", "mean", "max", "p95"].map(|stat| format!("{}_{}", s, stat)))
+        .collect();
+    let placeholders: Vec<String> = (5..5 + columns.len()).map(|i| format!("?{}", i)).collect();
+    let mut insert = conn.prepare_cached(&format!(
+        "INSERT INTO sensor_rollup (session_id, resolution, bucket_start, sample_count, {}) VALUES (?1, ?2, ?3, ?4, {})",
+        columns.join(", "),
+        placeholders.join(", ")
+    ))?;
+    let mut values: Vec<rusqlite::types::Value> = vec![
+        session_id.into(),
+        rollup.resolution.as_str().to_string().into(),
+        rollup.bucket_start.into(),
+        (rollup.sample_count as i64).into(),
+    ];
+    for stats in [rollup.hr, rollup.hrv_rmssd, rollup.eda_mus, rollup.temp_c, rollup.accel_mag_g] {
+        for v in [stats.min, stats.mean, stats.max, stats.p95] {
+            values.push((v as f64).into());
+        }
+    }
+    insert.execute(rusqlite::params_from_iter(values))?;
+    Ok(())
+}
+
+/// Recompute every rollup bucket touched by `[start_ts, end_ts]` for a session.
+///
+/// Minute and hour buckets are recomputed from the raw rows of the hours the range
+/// touches. Day buckets are then merged from their hour rollups: counts, min, mean, max
+/// and activity minutes stay exact, while p95 becomes the sample-weighted 95th
+/// percentile of the hourly p95s. Buckets outside the range are left as is.
+/// Returns the number of `sensor_rollup` rows written.
+pub fn refresh_rollups(conn: &Connection, session_id: i64, start_ts: i64, end_ts: i64) -> Result<usize> {
+    let from = Resolution::Hour.bucket_start(start_ts);
+    let to = Resolution::Hour.bucket_start(end_ts) + Resolution::Hour.secs() - 1;
+    let rows = query_sensor_data_range(conn, session_id, from, to)?;
+    let mut insert_activity = conn.prepare_cached(
+        "INSERT INTO activity_rollup (session_id, resolution, bucket_start, activity, minutes) VALUES (?1, ?2, ?3, ?4, ?5)",
+    )?;
+
+    // Majority activity per minute drives the activity-minute counts at every resolution.
+    let mut minute_activity: BTreeMap<i64, String> = BTreeMap::new();
+    let mut written = 0;
+    for resolution in [Resolution::Minute, Resolution::Hour] {
+        let lo = resolution.bucket_start(start_ts);
+        let hi = resolution.bucket_start(end_ts);
+        clear_buckets(conn, session_id, resolution, lo, hi)?;
+
+        let mut buckets: BTreeMap<i64, Vec<&SensorDataRow>> = BTreeMap::new();
+        for row in &rows {
+            buckets.entry(resolution.bucket_start(row.ts_unix_sec)).or_default().push(row);
+        }
+        for (bucket_start, group) in &buckets {
+            if resolution == Resolution::Minute {
+                if let Some(activity) = majority_activity(group) {
+                    minute_activity.insert(*bucket_start, activity);
+                }
+            }
+            if *bucket_start < lo || *bucket_start > hi {
+                continue;
+            }
+            if let Some(rollup) = summarize(resolution, *bucket_start, group) {
+                insert_rollup(conn, session_id, &rollup)?;
+                written += 1;
+            }
+        }
+
+        let mut minutes: BTreeMap<(i64, &str), u32> = BTreeMap::new();
+        for (minute, activity) in minute_activity.range(lo..hi + resolution.secs()) {
+            *minutes.entry((resolution.bucket_start(*minute), activity.as_str())).or_default() += 1;
+        }
+        for ((bucket_start, activity), count) in minutes {
+            insert_activity.execute(params![session_id, resolution.as_str(), bucket_start, activity, count])?;
+        }
+    }
+
+    let day = Resolution::Day;
+    let (lo, hi) = (day.bucket_start(start_ts), day.bucket_start(end_ts));
+    clear_buckets(conn, session_id, day, lo, hi)?;
+    let hours = query_rollups(conn, session_id, Resolution::Hour, lo, hi + day.secs() - 1)?;
+    let mut days: BTreeMap<i64, Vec<&RollupRow>> = BTreeMap::new();
+    for hour in &hours {
+        days.entry(day.bucket_start(hour.bucket_start)).or_default().push(hour);
+    }
+    for (bucket_start, group) in &days {
+        if let Some(rollup) = merge_rollups(day, *bucket_start, group) {
+            insert_rollup(conn, session_id, &rollup)?;
+            written += 1;
+        }
+    }
+    conn.execute(
+        "INSERT INTO activity_rollup (session_id, resolution, bucket_start, activity, minutes) \
+         SELECT session_id, ?2, (bucket_start / ?3) * ?3, activity, SUM(minutes) FROM activity_rollup \
+         WHERE session_id = ?1 AND resolution = ?4 AND bucket_start >= ?5 AND bucket_start <= ?6 \
+         GROUP BY bucket_start / ?3, activity",
+        params![session_id, day.as_str(), day.secs(), Resolution::Hour.as_str(), lo, hi + day.secs() - 1],
+    )?;
+    Ok(written)
+}
@@ -0,0 +1,243 @@
+//! Anomaly scenarios: labelled events the simulator injects into a session.
+//!
+//! A scenario file lists events with a start offset and duration in minutes from the
+//! start of the simulation:
+//!
+//! ```toml
+//! name = "flu week"
+//!
+//! [[events]]
+//! kind = "illness"
+//! start_min = 0
+//! duration_min = 4320
+//! resting_hr_rise_bpm = 8.0
+//!
+//! [[events]]
+//! kind = "fever"
+//! start_min = 1440
+//! duration_min = 1080
+//! peak_c = 1.2
+//! ```
+
+use crate::profile::extension;
+use crate::query::EventRow;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+
+/// One kind of injected anomaly with its parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Anomaly {
+    /// Sudden sustained HR elevation, awake or asleep.
+    Tachycardia {
+        /// HR added on top of the current target (bpm).
+        #[serde(default = "default_tachycardia_bpm")]
+        extra_bpm: f32,
+    },
+    /// HR drop that only applies while asleep.
+    SleepBradycardia {
+        /// HR removed from the sleeping target (bpm).
+        #[serde(default = "default_bradycardia_bpm")]
+        drop_bpm: f32,
+    },
+    /// Atrial-fibrillation-like rhythm: irregular RR intervals, elevated and erratic HR.
+    AtrialFibrillation {
+        /// Mean HR elevation (bpm).
+        #[serde(default = "default_afib_bpm")]
+        extra_bpm: f32,
+    },
+    /// Skin temperature drifting up to a peak and back, with the HR rise that goes with it.
+    Fever {
+        /// Peak temperature rise (°C).
+        #[serde(default = "default_fever_c")]
+        peak_c: f32,
+    },
+    /// Illness raising resting HR and lowering HRV, typically over several days.
+    Illness {
+        /// Peak resting HR rise (bpm).
+        #[serde(default = "default_illness_bpm")]
+        resting_hr_rise_bpm: f32,
+    },
+    /// Sensor produces no samples.
+    Dropout,
+    /// Movement corrupting optical HR and EDA, with accelerometer spikes.
+    MotionArtifact {
+        /// Artefact strength in [0, 1].
+        #[serde(default = "default_motion_severity")]
+        severity: f32,
+    },
+}
+
+fn default_tachycardia_bpm() -> f32 { 40.0 }
+fn default_bradycardia_bpm() -> f32 { 15.0 }
+fn default_afib_bpm() -> f32 { 25.0 }
+fn default_fever_c() -> f32 { 1.5 }
+fn default_illness_bpm() -> f32 { 8.0 }
+fn default_motion_severity() -> f32 { 0.5 }
+
+impl Anomaly {
+    /// Label stored in the `events.kind` column (same as the serde tag).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Anomaly::Tachycardia { .. } => "tachycardia",
+            Anomaly::SleepBradycardia { .. } => "sleep_bradycardia",
+            Anomaly::AtrialFibrillation { .. } => "atrial_fibrillation",
+            Anomaly::Fever { .. } => "fever",
+            Anomaly::Illness { .. } => "illness",
+            Anomaly::Dropout => "dropout",
+            Anomaly::MotionArtifact { .. } => "motion_artifact",
+        }
+    }
+
+    /// The anomaly's size in its own unit, stored in `events.magnitude`.
+    pub fn magnitude(&self) -> Option<f32> {
+        match *self {
+            Anomaly::Tachycardia { extra_bpm } | Anomaly::AtrialFibrillation { extra_bpm } => Some(extra_bpm),
+            Anomaly::SleepBradycardia { drop_bpm } => Some(drop_bpm),
+            Anomaly::Fever { peak_c } => Some(peak_c),
+            Anomaly::Illness { resting_hr_rise_bpm } => Some(resting_hr_rise_bpm),
+            Anomaly::Dropout => None,
+            Anomaly::MotionArtifact { severity } => Some(severity),
+        }
+    }
+
+    /// Allowed magnitude range, or `None` for parameterless anomalies.
+    fn magnitude_range(&self) -> Option<(f32, f32)> {
+        match self {
+            Anomaly::Tachycardia { .. } => Some((5.0, 120.0)),
+            Anomaly::SleepBradycardia { .. } => Some((1.0, 40.0)),
+            Anomaly::AtrialFibrillation { .. } => Some((0.0, 80.0)),
+            Anomaly::Fever { .. } => Some((0.1, 4.0)),
+            Anomaly::Illness { .. } => Some((1.0, 30.0)),
+            Anomaly::Dropout => None,
+            Anomaly::MotionArtifact { .. } => Some((0.0, 1.0)),
+        }
+    }
+}
+
+/// One scheduled anomaly.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ScenarioEvent {
+    /// Minutes after the simulation start.
+    pub start_min: u32,
+    /// Length in minutes.
+    pub duration_min: u32,
+    /// What happens.
+    #[serde(flatten)]
+    pub anomaly: Anomaly,
+}
+
+impl ScenarioEvent {
+    /// Ground-truth event row for a simulation starting at `sim_start_ts` (Unix seconds).
+    pub fn to_event_row(&self, sim_start_ts: i64) -> EventRow {
+        let start_ts = sim_start_ts + self.start_min as i64 * 60;
+        EventRow {
+            kind: self.anomaly.as_str().to_string(),
+            start_ts,
+            end_ts: start_ts + self.duration_min as i64 * 60,
+            magnitude: self.anomaly.magnitude(),
+        }
+    }
+}
+
+/// A named list of anomalies to inject into a simulated session.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    /// Optional display name.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Events, in any order; they may overlap.
+    #[serde(default)]
+    pub events: Vec<ScenarioEvent>,
+}
+
+/// Errors from loading a scenario file.
+#[derive(Debug)]
+pub enum ScenarioError {
+    /// Reading the scenario file failed.
+    Io(std::io::Error),
+    /// The file is not valid TOML/JSON for a scenario.
+    Parse(String),
+    /// The scenario parsed but failed validation; one message per problem.
+    Invalid(Vec<String>),
+    /// The file extension is neither `.toml` nor `.json`.
+    UnsupportedFormat(String),
+}
+
+impl fmt::Display for ScenarioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScenarioError::Io(e) => write!(f, "io error: {}", e),
+            ScenarioError::Parse(e) => write!(f, "failed to parse scenario: {}", e),
+            ScenarioError::Invalid(problems) => write!(f, "invalid scenario: {}", problems.join("; ")),
+            ScenarioError::UnsupportedFormat(ext) => {
+                write!(f, "unsupported scenario format {:?} (expected .toml or .json)", ext)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ScenarioError {}
+
+impl From<std::io::Error> for ScenarioError {
+    fn from(e: std::io::Error) -> Self {
+        ScenarioError::Io(e)
+    }
+}
+
+impl Scenario {
+    /// Parse and validate a scenario from TOML.
+    pub fn from_toml_str(s: &str) -> Result<Self, ScenarioError> {
+        let scenario: Self = toml::from_str(s).map_err(|e| ScenarioError::Parse(e.to_string()))?;
+        scenario.validate()?;
+        Ok(scenario)
+    }
+
+    /// Parse and validate a scenario from JSON.
+    pub fn from_json_str(s: &str) -> Result<Self, ScenarioError> {
+        let scenario: Self = serde_json::from_str(s).map_err(|e| ScenarioError::Parse(e.to_string()))?;
+        scenario.validate()?;
+        Ok(scenario)
+    }
+
+    /// Load a scenario from a `.toml` or `.json` file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ScenarioError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        match extension(path).as_str() {
+            "toml" => Self::from_toml_str(&text),
+            "json" => Self::from_json_str(&text),
+            other => Err(ScenarioError::UnsupportedFormat(other.to_string())),
+        }
+    }
+
+    /// Check durations and magnitudes, reporting every problem found.
+    pub fn validate(&self) -> Result<(), ScenarioError> {
+        let mut problems = Vec::new();
+        for (i, event) in self.events.iter().enumerate() {
+            let kind = event.anomaly.as_str();
+            if event.duration_min == 0 {
+                problems.push(format!("event {} ({}): duration_min must be positive", i, kind));
+            }
+            if let (Some(value), Some((lo, hi))) = (event.anomaly.magnitude(), event.anomaly.magnitude_range()) {
+                if !(lo..=hi).contains(&value) {
+                    problems.push(format!("event {} ({}): magnitude {} outside [{}, {}]", i, kind, value, lo, hi));
+                }
+            }
+        }
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(ScenarioError::Invalid(problems))
+        }
+    }
+
+    /// Ground-truth rows for every event, for a simulation starting at `sim_start_ts`.
+    pub fn event_rows(&self, sim_start_ts: i64) -> Vec<EventRow> {
+        let mut rows: Vec<_> = self.events.iter().map(|e| e.to_event_row(sim_start_ts)).collect();
+        rows.sort_by_key(|r| r.start_ts);
+        rows
+    }
+}
@@ -1,33 +1,17 @@
 //! SQLite ingestion and persistence for user_sessions and sensor_data.
 
-use rusqlite::{params, Connection, Result};
+use crate::error::Result;
+use crate::migrations;
+use crate::query::{EventRow, SensorDataRow};
+use crate::rollup;
+use core_types::{Reading, Sample, SignalKind};
+use rusqlite::{params, Connection};
+use std::time::{Duration, Instant};
 
-/// Initialize the SQLite database with required tables and index.
+/// Initialize the SQLite database by applying any pending schema migrations.
 pub fn init_db(conn: &Connection) -> Result<()> {
-    conn.execute_batch(
-        r#"
-        CREATE TABLE IF NOT EXISTS user_sessions (
-            session_id INTEGER PRIMARY KEY,
-            user_id TEXT NOT NULL,
-            profile_name TEXT,
-            simulation_date TEXT,
-            seed INTEGER,
-            created_at TIMESTAMP
-        );
-        CREATE TABLE IF NOT EXISTS sensor_data (
-            id INTEGER PRIMARY KEY,
-            session_id INTEGER,
-            ts_unix_sec INTEGER,
-            hr REAL, hrv_rmssd REAL, eda_mus REAL, temp_c REAL, accel_mag_g REAL,
-            activity TEXT,
-            stress_level REAL,
-            exercise_flag BOOLEAN,
-            created_at TIMESTAMP,
-            FOREIGN KEY(session_id) REFERENCES user_sessions(session_id)
-        );
-        CREATE INDEX IF NOT EXISTS idx_session_ts ON sensor_data(session_id, ts_unix_sec);
-        "#
-    )
+    migrations::migrate(conn)?;
+    Ok(())
 }
 
 /// Insert a new user session and return its session_id.
@@ -45,7 +29,11 @@ pub fn insert_user_session(
     Ok(conn.last_insert_rowid())
 }
 
-/// Insert a sensor data row.
+/// Insert a single sensor data row (autocommit; prefer [`SensorBatchWriter`] for bulk loads).
+///
+/// Unlike [`SensorBatchWriter`], this does not update `sensor_rollup`; call
+/// [`rollup::refresh_rollups`] over the inserted range once done.
+#[allow(clippy::too_many_arguments)]
 pub fn insert_sensor_data(
     conn: &Connection,
     session_id: i64,
@@ -58,10 +46,141 @@ pub fn insert_sensor_data(
     activity: &str,
     stress_level: f32,
     exercise_flag: bool,
+    sleep_stage: Option<&str>,
 ) -> Result<()> {
     conn.execute(
-        "INSERT INTO sensor_data (session_id, ts_unix_sec, hr, hrv_rmssd, eda_mus, temp_c, accel_mag_g, activity, stress_level, exercise_flag, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, CURRENT_TIMESTAMP)",
-        params![session_id, ts_unix_sec, hr, hrv_rmssd, eda_mus, temp_c, accel_mag_g, activity, stress_level, exercise_flag],
+        "INSERT INTO sensor_data (session_id, ts_unix_sec, hr, hrv_rmssd, eda_mus, temp_c, accel_mag_g, activity, stress_level, exercise_flag, sleep_stage, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, CURRENT_TIMESTAMP)",
+        params![session_id, ts_unix_sec, hr, hrv_rmssd, eda_mus, temp_c, accel_mag_g, activity, stress_level, exercise_flag, sleep_stage],
     )?;
     Ok(())
 }
+
+/// Insert a single time-series sample for a named stream.
+pub fn insert_sample(conn: &Connection, stream: &str, sample: &Sample) -> Result<()> {
+    conn.execute(
+        "INSERT INTO samples (stream, ts_ms, value) VALUES (?1, ?2, ?3)",
+        params![stream, sample.ts_ms, sample.value],
+    )?;
+    Ok(())
+}
+
+/// Insert a batch of samples for a named stream inside one transaction.
+pub fn insert_samples(conn: &mut Connection, stream: &str, samples: &[Sample]) -> Result<()> {
+    let tx = conn.transaction()?;
+    {
+        let mut stmt = tx.prepare("INSERT INTO samples (stream, ts_ms, value) VALUES (?1, ?2, ?3)")?;
+        for sample in samples {
+            stmt.execute(params![stream, sample.ts_ms, sample.value])?;
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// Insert tagged readings inside one transaction, grouped into one stream per signal
+/// ([`SignalKind::as_str`]).
+pub fn insert_readings(conn: &mut Connection, readings: &[Reading]) -> Result<()> {
+    let mut streams: Vec<(SignalKind, Vec<Sample>)> = Vec::new();
+    for reading in readings {
+        match streams.iter_mut().find(|(kind, _)| *kind == reading.kind) {
+            Some((_, samples)) => samples.push(reading.sample()),
+            None => streams.push((reading.kind, vec![reading.sample()])),
+        }
+    }
+    let tx = conn.transaction()?;
+    {
+        let mut stmt = tx.prepare("INSERT INTO samples (stream, ts_ms, value) VALUES (?1, ?2, ?3)")?;
+        for (kind, samples) in &streams {
+            for sample in samples {
+                stmt.execute(params![kind.as_str(), sample.ts_ms, sample.value])?;
+            }
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// Insert ground-truth events for a session inside one transaction.
+pub fn insert_events(conn: &mut Connection, session_id: i64, events: &[EventRow]) -> Result<()> {
+    let tx = conn.transaction()?;
+    {
+        let mut stmt = tx.prepare(
+            "INSERT INTO events (session_id, kind, start_ts, end_ts, magnitude) VALUES (?1, ?2, ?3, ?4, ?5)",
+        )?;
+        for event in events {
+            stmt.execute(params![session_id, event.kind, event.start_ts, event.end_ts, event.magnitude])?;
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// Throughput report for one [`SensorBatchWriter::write`] call.
+#[derive(Debug, Clone, Copy)]
+pub struct BatchStats {
+    /// Number of rows inserted.
+    pub rows: usize,
+    /// Wall-clock time spent inserting and committing.
+    pub elapsed: Duration,
+}
+
+impl BatchStats {
+    /// Rows inserted per second of wall-clock time.
+    pub fn rows_per_sec(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.rows as f64 / secs
+        } else {
+            self.rows as f64
+        }
+    }
+}
+
+/// Bulk writer inserting sensor_data rows for one session.
+///
+/// Each [`write`](Self::write) call runs inside a single transaction and reuses one
+/// cached prepared statement, so a batch costs one commit instead of one per row.
+/// Rollups for the buckets the batch touches are refreshed in the same transaction.
+pub struct SensorBatchWriter<'c> {
+    conn: &'c mut Connection,
+    session_id: i64,
+}
+
+impl<'c> SensorBatchWriter<'c> {
+    /// Create a writer for `session_id`.
+    pub fn new(conn: &'c mut Connection, session_id: i64) -> Self {
+        Self { conn, session_id }
+    }
+
+    /// Insert all rows in one transaction and report throughput.
+    pub fn write(&mut self, rows: &[SensorDataRow]) -> Result<BatchStats> {
+        let started = Instant::now();
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare_cached(
+                "INSERT INTO sensor_data (session_id, ts_unix_sec, hr, hrv_rmssd, eda_mus, temp_c, accel_mag_g, activity, stress_level, exercise_flag, sleep_stage, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, CURRENT_TIMESTAMP)",
+            )?;
+            for row in rows {
+                stmt.execute(params![
+                    self.session_id,
+                    row.ts_unix_sec,
+                    row.hr,
+                    row.hrv_rmssd,
+                    row.eda_mus,
+                    row.temp_c,
+                    row.accel_mag_g,
+                    row.activity,
+                    row.stress_level,
+                    row.exercise_flag,
+                    row.sleep_stage
+                ])?;
+            }
+        }
+        let ts = rows.iter().map(|r| r.ts_unix_sec);
+        if let (Some(start), Some(end)) = (ts.clone().min(), ts.max()) {
+            rollup::refresh_rollups(&tx, self.session_id, start, end)?;
+        }
+        tx.commit()?;
+        Ok(BatchStats { rows: rows.len(), elapsed: started.elapsed() })
+    }
+}
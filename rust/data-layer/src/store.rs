@@ -0,0 +1,269 @@
+//! Generic per-stream time-series storage with in-memory, SQLite and Parquet backends.
+
+use crate::{parquet, query, sqlite};
+use core_types::{Reading, Sample};
+use rusqlite::Connection;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Storage interface for named sample streams (e.g. one per sensor).
+pub trait TimeSeriesStore {
+    /// Persist a single sample for a stream.
+    fn write(&mut self, stream: &str, sample: Sample) -> anyhow::Result<()>;
+
+    /// Persist a batch of samples for a stream.
+    fn write_batch(&mut self, stream: &str, samples: &[Sample]) -> anyhow::Result<()> {
+        for sample in samples {
+            self.write(stream, sample.clone())?;
+        }
+        Ok(())
+    }
+
+    /// Persist tagged readings, each in the stream named after its signal
+    /// ([`core_types::SignalKind::as_str`]).
+    fn write_readings(&mut self, readings: &[Reading]) -> anyhow::Result<()> {
+        for reading in readings {
+            self.write(reading.kind.as_str(), reading.sample())?;
+        }
+        Ok(())
+    }
+
+    /// Read samples for a stream in a time range (inclusive, milliseconds), oldest first.
+    fn read_range(&self, stream: &str, start_ms: i64, end_ms: i64) -> anyhow::Result<Vec<Sample>>;
+
+    /// Read the latest N samples for a stream, newest first.
+    fn read_latest(&self, stream: &str, n: usize) -> anyhow::Result<Vec<Sample>>;
+
+    /// Flush any buffered samples to durable storage.
+    fn flush(&mut self) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+fn range_of(samples: &[Sample], start_ms: i64, end_ms: i64) -> Vec<Sample> {
+    let mut out: Vec<Sample> = samples
+        .iter()
+        .filter(|s| s.ts_ms >= start_ms && s.ts_ms <= end_ms)
+        .cloned()
+        .collect();
+    out.sort_by_key(|s| s.ts_ms);
+    out
+}
+
+fn latest_of(samples: &[Sample], n: usize) -> Vec<Sample> {
+    let mut out = samples.to_vec();
+    out.sort_by_key(|s| std::cmp::Reverse(s.ts_ms));
+    out.truncate(n);
+    out
+}
+
+/// Volatile store keeping every stream in memory.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    streams: HashMap<String, Vec<Sample>>,
+}
+
+impl MemoryStore {
+    /// Create an empty in-memory store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl TimeSeriesStore for MemoryStore {
+    fn write(&mut self, stream: &str, sample: Sample) -> anyhow::Result<()> {
+        self.streams.entry(stream.to_string()).or_default().push(sample);
+        Ok(())
+    }
+
+    fn write_batch(&mut self, stream: &str, samples: &[Sample]) -> anyhow::Result<()> {
+        self.streams
+            .entry(stream.to_string())
+            .or_default()
+            .extend_from_slice(samples);
+        Ok(())
+    }
+
+    fn read_range(&self, stream: &str, start_ms: i64, end_ms: i64) -> anyhow::Result<Vec<Sample>> {
+        Ok(self
+            .streams
+            .get(stream)
+            .map(|s| range_of(s, start_ms, end_ms))
+            .unwrap_or_default())
+    }
+
+    fn read_latest(&self, stream: &str, n: usize) -> anyhow::Result<Vec<Sample>> {
+        Ok(self.streams.get(stream).map(|s| latest_of(s, n)).unwrap_or_default())
+    }
+}
+
+/// Store backed by the `samples` table of a SQLite database.
+pub struct SqliteStore {
+    conn: Connection,
+}
+
+impl SqliteStore {
+    /// Open (or create) a SQLite database at `path` and initialize its schema.
+    pub fn open(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        Self::from_connection(Connection::open(path)?)
+    }
+
+    /// Wrap an existing connection, initializing the schema if needed.
+    pub fn from_connection(conn: Connection) -> anyhow::Result<Self> {
+        sqlite::init_db(&conn)?;
+        Ok(Self { conn })
+    }
+
+    /// Borrow the underlying connection.
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+}
+
+impl TimeSeriesStore for SqliteStore {
+    fn write(&mut self, stream: &str, sample: Sample) -> anyhow::Result<()> {
+        sqlite::insert_sample(&self.conn, stream, &sample)?;
+        Ok(())
+    }
+
+    fn write_batch(&mut self, stream: &str, samples: &[Sample]) -> anyhow::Result<()> {
+        sqlite::insert_samples(&mut self.conn, stream, samples)?;
+        Ok(())
+    }
+
+    /// Writes all readings in one transaction rather than one autocommit insert each.
+    fn write_readings(&mut self, readings: &[Reading]) -> anyhow::Result<()> {
+        sqlite::insert_readings(&mut self.conn, readings)?;
+        Ok(())
+    }
+
+    fn read_range(&self, stream: &str, start_ms: i64, end_ms: i64) -> anyhow::Result<Vec<Sample>> {
+        Ok(query::query_samples_range(&self.conn, stream, start_ms, end_ms)?)
+    }
+
+    fn read_latest(&self, stream: &str, n: usize) -> anyhow::Result<Vec<Sample>> {
+        Ok(query::query_latest_samples(&self.conn, stream, n)?)
+    }
+}
+
+/// Store writing each stream as a directory of Parquet part files.
+///
+/// Samples are buffered per stream and written to `<root>/<stream>/part-NNNNN.parquet`
+/// once `flush_every` samples have accumulated, on [`TimeSeriesStore::flush`], or on drop.
+/// Stream names become directory names, so they are limited to ASCII letters, digits, `_`,
+/// `-` and `.` (and may not be `.` or `..`). Dropping the store flushes on a best-effort
+/// basis; call [`TimeSeriesStore::flush`] first to observe write errors.
+pub struct ParquetStore {
+    root: PathBuf,
+    flush_every: usize,
+    buffers: HashMap<String, Vec<Sample>>,
+}
+
+impl ParquetStore {
+    /// Default number of buffered samples per stream before a part file is written.
+    pub const DEFAULT_FLUSH_EVERY: usize = 3600;
+
+    /// Open (or create) a Parquet store rooted at `root`.
+    pub fn open(root: impl Into<PathBuf>) -> anyhow::Result<Self> {
+        Self::with_flush_every(root, Self::DEFAULT_FLUSH_EVERY)
+    }
+
+    /// Open a Parquet store that writes a part file every `flush_every` samples.
+    pub fn with_flush_every(root: impl Into<PathBuf>, flush_every: usize) -> anyhow::Result<Self> {
+        let root = root.into();
+        fs::create_dir_all(&root)?;
+        Ok(Self {
+            root,
+            flush_every: flush_every.max(1),
+            buffers: HashMap::new(),
+        })
+    }
+
+    /// Directory holding `stream`'s part files, rejecting names that are not a single
+    /// plain path component.
+    fn stream_dir(&self, stream: &str) -> anyhow::Result<PathBuf> {
+        let valid = !stream.is_empty()
+            && stream != "."
+            && stream != ".."
+            && stream.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'));
+        if !valid {
+            anyhow::bail!("invalid stream name {:?}", stream);
+        }
+        Ok(self.root.join(stream))
+    }
+
+    fn part_files(&self, stream: &str) -> anyhow::Result<Vec<PathBuf>> {
+        let dir = self.stream_dir(stream)?;
+        if !dir.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut parts: Vec<PathBuf> = fs::read_dir(&dir)?
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|ext| ext == "parquet"))
+            .collect();
+        parts.sort();
+        Ok(parts)
+    }
+
+    fn flush_stream(&mut self, stream: &str) -> anyhow::Result<()> {
+        let Some(buffer) = self.buffers.get_mut(stream) else {
+            return Ok(());
+        };
+        if buffer.is_empty() {
+            return Ok(());
+        }
+        let samples = std::mem::take(buffer);
+        let dir = self.stream_dir(stream)?;
+        fs::create_dir_all(&dir)?;
+        let part = self.part_files(stream)?.len();
+        parquet::write_samples_parquet(&dir.join(format!("part-{:05}.parquet", part)), &samples)?;
+        Ok(())
+    }
+
+    fn all_samples(&self, stream: &str) -> anyhow::Result<Vec<Sample>> {
+        let mut out = Vec::new();
+        for part in self.part_files(stream)? {
+            out.extend(parquet::read_samples_parquet(&part)?);
+        }
+        if let Some(buffer) = self.buffers.get(stream) {
+            out.extend_from_slice(buffer);
+        }
+        Ok(out)
+    }
+}
+
+impl TimeSeriesStore for ParquetStore {
+    fn write(&mut self, stream: &str, sample: Sample) -> anyhow::Result<()> {
+        self.stream_dir(stream)?;
+        let buffer = self.buffers.entry(stream.to_string()).or_default();
+        buffer.push(sample);
+        if buffer.len() >= self.flush_every {
+            self.flush_stream(stream)?;
+        }
+        Ok(())
+    }
+
+    fn read_range(&self, stream: &str, start_ms: i64, end_ms: i64) -> anyhow::Result<Vec<Sample>> {
+        Ok(range_of(&self.all_samples(stream)?, start_ms, end_ms))
+    }
+
+    fn read_latest(&self, stream: &str, n: usize) -> anyhow::Result<Vec<Sample>> {
+        Ok(latest_of(&self.all_samples(stream)?, n))
+    }
+
+    fn flush(&mut self) -> anyhow::Result<()> {
+        let streams: Vec<String> = self.buffers.keys().cloned().collect();
+        for stream in streams {
+            self.flush_stream(&stream)?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for ParquetStore {
+    fn drop(&mut self) {
+        // Errors cannot be reported from drop; callers wanting them flush explicitly
+        let _ = self.flush();
+    }
+}
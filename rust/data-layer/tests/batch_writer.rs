@@ -0,0 +1,75 @@
+use data_layer::query::{query_sensor_data_range, SensorDataRow};
+use data_layer::sqlite::{self, SensorBatchWriter};
+use rusqlite::Connection;
+
+fn row(ts: i64) -> SensorDataRow {
+    SensorDataRow {
+        ts_unix_sec: ts,
+        hr: 60.0 + ts as f32,
+        hrv_rmssd: 40.0,
+        eda_mus: 2.0,
+        temp_c: 33.0,
+        accel_mag_g: 1.0,
+        activity: "sitting".into(),
+        stress_level: 0.2,
+        exercise_flag: false,
+        sleep_stage: None,
+    }
+}
+
+fn session_db() -> (Connection, i64) {
+    let conn = Connection::open_in_memory().unwrap();
+    sqlite::init_db(&conn).unwrap();
+    let session = sqlite::insert_user_session(&conn, "u1", "test", "2025-12-19", 1).unwrap();
+    (conn, session)
+}
+
+fn count(conn: &Connection, table: &str) -> i64 {
+    conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |r| r.get(0)).unwrap()
+}
+
+fn minute_samples(conn: &Connection) -> i64 {
+    conn.query_row("SELECT SUM(sample_count) FROM sensor_rollup WHERE resolution = 'minute'", [], |r| r.get(0))
+        .unwrap()
+}
+
+#[test]
+fn batch_write_reports_rows_and_persists_them() {
+    let (mut conn, session) = session_db();
+    let rows: Vec<_> = (0..120).map(row).collect();
+    let stats = SensorBatchWriter::new(&mut conn, session).write(&rows).unwrap();
+    assert_eq!(stats.rows, 120);
+    assert!(stats.rows_per_sec() > 0.0);
+
+    let stored = query_sensor_data_range(&conn, session, 0, 119).unwrap();
+    assert_eq!(stored.len(), 120);
+    assert_eq!(stored[7].hr, 67.0);
+    assert!(count(&conn, "sensor_rollup") > 0);
+
+    let empty = SensorBatchWriter::new(&mut conn, session).write(&[]).unwrap();
+    assert_eq!(empty.rows, 0);
+    assert_eq!(count(&conn, "sensor_data"), 120);
+}
+
+#[test]
+fn failing_row_rolls_back_the_whole_batch() {
+    let (mut conn, session) = session_db();
+    SensorBatchWriter::new(&mut conn, session).write(&[row(0)]).unwrap();
+    conn.execute_batch(
+        "CREATE TRIGGER reject_ts_5 BEFORE INSERT ON sensor_data WHEN NEW.ts_unix_sec = 5
+         BEGIN SELECT RAISE(ABORT, 'rejected'); END;",
+    )
+    .unwrap();
+
+    let rows: Vec<_> = (1..10).map(row).collect();
+    assert!(SensorBatchWriter::new(&mut conn, session).write(&rows).is_err());
+    // Rows 1..5 were inserted before the failure and must not survive it
+    assert_eq!(count(&conn, "sensor_data"), 1);
+    assert_eq!(minute_samples(&conn), 1);
+
+    // The connection is usable again once the offending row is gone
+    let rows: Vec<_> = (1..10).filter(|&ts| ts != 5).map(row).collect();
+    assert_eq!(SensorBatchWriter::new(&mut conn, session).write(&rows).unwrap().rows, 8);
+    assert_eq!(count(&conn, "sensor_data"), 9);
+    assert_eq!(minute_samples(&conn), 9);
+}
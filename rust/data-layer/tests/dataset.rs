@@ -0,0 +1,228 @@
+use arrow::array::{ArrayRef, BooleanArray, Float32Array, Int64Array, StringArray};
+use arrow::datatypes::{Field, Schema};
+use arrow::record_batch::RecordBatch;
+use data_layer::parquet::dataset::date_of;
+use data_layer::parquet::{sensor_data_schema, SensorDataset};
+use data_layer::query::{DecodeMode, SensorDataRow};
+use data_layer::DataLayerError;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::ArrowWriter;
+use std::fs::File;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// 2025-12-19T00:00:00Z
+const DAY: i64 = 1_766_102_400;
+
+fn row(ts: i64) -> SensorDataRow {
+    SensorDataRow {
+        ts_unix_sec: ts,
+        hr: 65.0,
+        hrv_rmssd: 42.0,
+        eda_mus: 2.1,
+        temp_c: 33.4,
+        accel_mag_g: 1.0,
+        activity: "sitting".into(),
+        stress_level: 0.25,
+        exercise_flag: false,
+        sleep_stage: None,
+    }
+}
+
+/// Rows every 10 minutes from `start` for `hours` hours.
+fn rows(start: i64, hours: i64) -> Vec<SensorDataRow> {
+    (0..hours * 6).map(|i| row(start + i * 600)).collect()
+}
+
+fn temp_root(name: &str) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("chrona-dataset-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    root
+}
+
+#[test]
+fn writes_and_lists_partitions_per_user_and_date() {
+    let root = temp_root("roundtrip");
+    let dataset = SensorDataset::open(&root).unwrap();
+    // 20:00 on day 0 to 04:00 on day 1
+    let written = dataset.write("alice", 1, &rows(DAY + 20 * 3600, 8)).unwrap();
+    assert_eq!(written.len(), 2);
+    dataset.write("bob", 2, &rows(DAY, 1)).unwrap();
+
+    let partitions = dataset.partitions().unwrap();
+    let listed: Vec<_> = partitions.iter().map(|p| (p.user_id.as_str(), p.date)).collect();
+    let (d0, d1) = (date_of(DAY).unwrap(), date_of(DAY + 86_400).unwrap());
+    assert_eq!(listed, [("alice", d0), ("alice", d1), ("bob", d0)]);
+    assert!(partitions[0].path.ends_with("user=alice/date=2025-12-19"));
+
+    let first = dataset.read_partition(&partitions[0]).unwrap();
+    assert_eq!(first.len(), 24);
+    assert!(first.iter().all(|(session, r)| *session == 1 && r.ts_unix_sec < DAY + 86_400));
+    assert_eq!(dataset.read_partition(&partitions[1]).unwrap().len(), 24);
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn user_ids_are_encoded_into_a_single_directory() {
+    let root = temp_root("encoding");
+    let dataset = SensorDataset::open(&root).unwrap();
+    for user in ["../escape", "a/b", "k=v", "100%", "..", "üser"] {
+        for path in dataset.write(user, 1, &rows(DAY, 1)).unwrap() {
+            assert!(path.starts_with(&root), "{}", path.display());
+            assert_eq!(path.strip_prefix(&root).unwrap().components().count(), 3, "{}", path.display());
+        }
+    }
+    assert!(root.join("user=..%2Fescape").is_dir());
+    assert!(root.join("user=k%3Dv").is_dir());
+
+    let mut users: Vec<_> = dataset.partitions().unwrap().into_iter().map(|p| p.user_id).collect();
+    users.sort();
+    assert_eq!(users, ["..", "../escape", "100%", "a/b", "k=v", "üser"]);
+    assert_eq!(dataset.query_range("a/b", None, DAY, DAY + 3600).unwrap().len(), 6);
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn queries_prune_partitions_by_date() {
+    let root = temp_root("prune");
+    let dataset = SensorDataset::open(&root).unwrap();
+    for day in 0..5 {
+        dataset.write("alice", day, &rows(DAY + day * 86_400, 2)).unwrap();
+    }
+    dataset.write("bob", 9, &rows(DAY + 86_400, 2)).unwrap();
+
+    let (d1, d3) = (date_of(DAY + 86_400).unwrap(), date_of(DAY + 3 * 86_400).unwrap());
+    let pruned = dataset.prune("alice", d1, d3).unwrap();
+    assert_eq!(pruned.iter().map(|p| p.date).collect::<Vec<_>>(), [d1, date_of(DAY + 2 * 86_400).unwrap(), d3]);
+
+    // 01:00 on day 1 to 00:30 on day 3
+    let rows = dataset.query_range("alice", None, DAY + 86_400 + 3600, DAY + 3 * 86_400 + 1800).unwrap();
+    assert_eq!(rows.len(), 6 + 12 + 4);
+    assert!(rows.windows(2).all(|w| w[0].ts_unix_sec < w[1].ts_unix_sec));
+    assert_eq!(dataset.query_range("alice", Some(2), DAY, DAY + 5 * 86_400).unwrap().len(), 12);
+    assert!(dataset.query_range("carol", None, DAY, DAY + 5 * 86_400).unwrap().is_empty());
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn lenient_queries_skip_bad_rows_across_files() {
+    let root = temp_root("lenient");
+    let dataset = SensorDataset::open(&root).unwrap();
+    let written = dataset.write("alice", 1, &rows(DAY, 1)).unwrap();
+
+    // A foreign writer's file in the same partition, with a null heart rate at 00:05
+    let schema = Arc::new(Schema::new(
+        sensor_data_schema()
+            .fields()
+            .iter()
+            .map(|f| Field::new(f.name(), f.data_type().clone(), true))
+            .collect::<Vec<_>>(),
+    ));
+    let floats = |v: f32| Arc::new(Float32Array::from(vec![v; 2])) as ArrayRef;
+    let batch = RecordBatch::try_new(
+        schema.clone(),
+        vec![
+            Arc::new(Int64Array::from(vec![1, 1])),
+            Arc::new(Int64Array::from(vec![DAY + 300, DAY + 900])),
+            Arc::new(Float32Array::from(vec![None, Some(70.0)])),
+            floats(40.0),
+            floats(2.0),
+            floats(33.0),
+            floats(1.0),
+            Arc::new(StringArray::from(vec!["sitting"; 2])),
+            floats(0.3),
+            Arc::new(BooleanArray::from(vec![false; 2])),
+            Arc::new(StringArray::from(vec![None::<&str>; 2])),
+        ],
+    )
+    .unwrap();
+    let foreign = written[0].with_file_name("part-00001.parquet");
+    let mut writer = ArrowWriter::try_new(File::create(&foreign).unwrap(), schema, None).unwrap();
+    writer.write(&batch).unwrap();
+    writer.close().unwrap();
+
+    let strict = dataset.query_range("alice", None, DAY, DAY + 3600);
+    assert!(matches!(strict, Err(DataLayerError::Decode { row: 0, .. })));
+
+    let lenient = dataset.query_range_with_mode("alice", None, DAY, DAY + 3600, DecodeMode::Lenient).unwrap();
+    assert_eq!((lenient.rows.len(), lenient.skipped), (7, 1));
+    assert!(lenient.rows.windows(2).all(|w| w[0].ts_unix_sec <= w[1].ts_unix_sec));
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn compaction_merges_small_files_and_keeps_rows() {
+    let root = temp_root("compact");
+    let dataset = SensorDataset::open(&root).unwrap();
+    for hour in 0..4 {
+        dataset.write("alice", hour, &rows(DAY + hour * 3600, 1)).unwrap();
+    }
+    dataset.write("bob", 7, &rows(DAY, 1)).unwrap();
+    let partition = dataset.prune("alice", date_of(DAY).unwrap(), date_of(DAY).unwrap()).unwrap().remove(0);
+    assert_eq!(partition.files().unwrap().len(), 4);
+    let before = dataset.read_partition(&partition).unwrap();
+
+    let stats = dataset.compact(u64::MAX).unwrap();
+    assert_eq!((stats.partitions_compacted, stats.files_merged), (1, 4));
+    assert!(stats.bytes_before > 0 && stats.bytes_after > 0);
+    let files = partition.files().unwrap();
+    assert_eq!(files.len(), 1);
+    assert!(files[0].ends_with("part-00004.parquet"));
+
+    let after = dataset.read_partition(&partition).unwrap();
+    assert_eq!(after.len(), before.len());
+    let key = |rows: &[(i64, SensorDataRow)]| rows.iter().map(|(s, r)| (*s, r.ts_unix_sec)).collect::<Vec<_>>();
+    assert_eq!(key(&after), key(&before));
+
+    // Nothing left to merge
+    let again = dataset.compact(u64::MAX).unwrap();
+    assert_eq!(again.files_merged, 0);
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn compaction_merges_files_from_before_sleep_stage() {
+    let root = temp_root("compact-mixed");
+    let dataset = SensorDataset::open(&root).unwrap();
+    let mut asleep = row(DAY + 3600);
+    asleep.sleep_stage = Some("deep".into());
+    let written = dataset.write("alice", 1, &[asleep]).unwrap();
+
+    // An older part file without the sleep_stage column
+    let schema = sensor_data_schema();
+    let old_schema = Arc::new(Schema::new(schema.fields()[..10].to_vec()));
+    let floats = |v: f32| Arc::new(Float32Array::from(vec![v; 2])) as ArrayRef;
+    let batch = RecordBatch::try_new(
+        old_schema.clone(),
+        vec![
+            Arc::new(Int64Array::from(vec![1, 1])),
+            Arc::new(Int64Array::from(vec![DAY, DAY + 600])),
+            floats(60.0),
+            floats(45.0),
+            floats(2.0),
+            floats(33.0),
+            floats(1.0),
+            Arc::new(StringArray::from(vec!["sitting"; 2])),
+            floats(0.2),
+            Arc::new(BooleanArray::from(vec![false; 2])),
+        ],
+    )
+    .unwrap();
+    let old = written[0].with_file_name("part-00001.parquet");
+    let mut writer = ArrowWriter::try_new(File::create(&old).unwrap(), old_schema, None).unwrap();
+    writer.write(&batch).unwrap();
+    writer.close().unwrap();
+
+    let stats = dataset.compact(u64::MAX).unwrap();
+    assert_eq!((stats.partitions_compacted, stats.files_merged), (1, 2));
+    let partition = dataset.partitions().unwrap().remove(0);
+    let files = partition.files().unwrap();
+    assert_eq!(files.len(), 1);
+    let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(&files[0]).unwrap()).unwrap();
+    assert_eq!(reader.schema().fields(), schema.fields());
+
+    let merged = dataset.query_range("alice", None, DAY, DAY + 86_399).unwrap();
+    let stages: Vec<_> = merged.iter().map(|r| (r.ts_unix_sec, r.sleep_stage.as_deref())).collect();
+    assert_eq!(stages, [(DAY, None), (DAY + 600, None), (DAY + 3600, Some("deep"))]);
+    std::fs::remove_dir_all(&root).unwrap();
+}
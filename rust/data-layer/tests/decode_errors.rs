@@ -0,0 +1,185 @@
+use arrow::array::{ArrayRef, BooleanArray, Float32Array, Int64Array, StringArray};
+use arrow::datatypes::{Field, Schema};
+use arrow::record_batch::RecordBatch;
+use data_layer::parquet::{
+    query_sensor_data_range_parquet, query_sensor_data_range_parquet_with_mode, sensor_data_schema,
+};
+use data_layer::query::{query_sensor_data_range, query_sensor_data_range_with_mode, DecodeMode};
+use data_layer::sqlite;
+use data_layer::DataLayerError;
+use parquet::arrow::ArrowWriter;
+use parquet::file::properties::WriterProperties;
+use rusqlite::Connection;
+use std::fs::File;
+use std::sync::Arc;
+
+fn db_with_null_activity() -> (Connection, i64) {
+    let conn = Connection::open_in_memory().unwrap();
+    sqlite::init_db(&conn).unwrap();
+    let session = sqlite::insert_user_session(&conn, "u", "p", "2025-12-19", 1).unwrap();
+    for ts in 0..5 {
+        sqlite::insert_sensor_data(&conn, session, ts, 60.0, 50.0, 1.0, 33.0, 0.1, "sitting", 0.2, false, None).unwrap();
+    }
+    conn.execute("UPDATE sensor_data SET activity = NULL WHERE ts_unix_sec = 2", []).unwrap();
+    (conn, session)
+}
+
+#[test]
+fn sqlite_strict_fails_on_null_activity() {
+    let (conn, session) = db_with_null_activity();
+    match query_sensor_data_range(&conn, session, 0, 10) {
+        Err(DataLayerError::Decode { origin, row, .. }) => {
+            assert_eq!(origin, "sensor_data");
+            assert_eq!(row, 2);
+        }
+        other => panic!("expected decode error, got {:?}", other.map(|r| r.len())),
+    }
+}
+
+#[test]
+fn sqlite_lenient_reports_skipped_rows() {
+    let (conn, session) = db_with_null_activity();
+    let decoded = query_sensor_data_range_with_mode(&conn, session, 0, 10, DecodeMode::Lenient).unwrap();
+    assert_eq!(decoded.rows.len(), 4);
+    assert_eq!(decoded.skipped, 1);
+    assert!(decoded.rows.iter().all(|r| r.ts_unix_sec != 2));
+}
+
+#[test]
+fn parquet_nulls_are_strict_errors_and_lenient_skips() {
+    // Same columns as sensor_data, but nullable so a foreign writer can leave gaps.
+    let schema = Arc::new(Schema::new(
+        sensor_data_schema()
+            .fields()
+            .iter()
+            .map(|f| Field::new(f.name(), f.data_type().clone(), true))
+            .collect::<Vec<_>>(),
+    ));
+    let floats = |v: f32| Arc::new(Float32Array::from(vec![v; 3])) as ArrayRef;
+    let batch = RecordBatch::try_new(
+        schema.clone(),
+        vec![
+            Arc::new(Int64Array::from(vec![7, 7, 7])),
+            Arc::new(Int64Array::from(vec![0, 1, 2])),
+            Arc::new(Float32Array::from(vec![Some(60.0), None, Some(62.0)])),
+            floats(50.0),
+            floats(1.0),
+            floats(33.0),
+            floats(0.1),
+            Arc::new(StringArray::from(vec!["sitting"; 3])),
+            floats(0.2),
+            Arc::new(BooleanArray::from(vec![false; 3])),
+            Arc::new(StringArray::from(vec![None, Some("deep"), None])),
+        ],
+    )
+    .unwrap();
+    let path = std::env::temp_dir().join(format!("chrona-decode-{}.parquet", std::process::id()));
+    let mut writer = ArrowWriter::try_new(File::create(&path).unwrap(), schema, None).unwrap();
+    writer.write(&batch).unwrap();
+    writer.close().unwrap();
+    let path_str = path.to_str().unwrap();
+
+    let strict = query_sensor_data_range_parquet(path_str, 7, 0, 10);
+    assert!(matches!(strict, Err(DataLayerError::Decode { row: 1, .. })));
+
+    let lenient = query_sensor_data_range_parquet_with_mode(path_str, 7, 0, 10, DecodeMode::Lenient).unwrap();
+    assert_eq!(lenient.rows.iter().map(|r| r.ts_unix_sec).collect::<Vec<_>>(), vec![0, 2]);
+    assert_eq!(lenient.skipped, 1);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn parquet_missing_column_is_schema_mismatch() {
+    let schema = Arc::new(Schema::new(vec![Field::new("session_id", arrow::datatypes::DataType::Int64, false)]));
+    let batch = RecordBatch::try_new(schema.clone(), vec![Arc::new(Int64Array::from(vec![7]))]).unwrap();
+    let path = std::env::temp_dir().join(format!("chrona-schema-{}.parquet", std::process::id()));
+    let mut writer = ArrowWriter::try_new(File::create(&path).unwrap(), schema, None).unwrap();
+    writer.write(&batch).unwrap();
+    writer.close().unwrap();
+
+    let result = query_sensor_data_range_parquet_with_mode(path.to_str().unwrap(), 7, 0, 10, DecodeMode::Lenient);
+    match result {
+        Err(DataLayerError::SchemaMismatch { column, found, .. }) => {
+            assert_eq!(column, "ts_unix_sec");
+            assert_eq!(found, "missing");
+        }
+        other => panic!("expected schema mismatch, got {:?}", other.map(|d| d.rows.len())),
+    }
+    std::fs::remove_file(&path).unwrap();
+}
+
+/// Write `(session_id, ts, hr)` rows to a Parquet file with nullable sensor_data columns,
+/// `row_group_size` rows per row group.
+fn write_nullable_hr(name: &str, rows: &[(i64, i64, Option<f32>)], row_group_size: usize) -> std::path::PathBuf {
+    let schema = Arc::new(Schema::new(
+        sensor_data_schema()
+            .fields()
+            .iter()
+            .map(|f| Field::new(f.name(), f.data_type().clone(), true))
+            .collect::<Vec<_>>(),
+    ));
+    let n = rows.len();
+    let floats = |v: f32| Arc::new(Float32Array::from(vec![v; n])) as ArrayRef;
+    let batch = RecordBatch::try_new(
+        schema.clone(),
+        vec![
+            Arc::new(Int64Array::from_iter_values(rows.iter().map(|r| r.0))),
+            Arc::new(Int64Array::from_iter_values(rows.iter().map(|r| r.1))),
+            Arc::new(Float32Array::from_iter(rows.iter().map(|r| r.2))),
+            floats(50.0),
+            floats(1.0),
+            floats(33.0),
+            floats(0.1),
+            Arc::new(StringArray::from(vec!["sitting"; n])),
+            floats(0.2),
+            Arc::new(BooleanArray::from(vec![false; n])),
+            Arc::new(StringArray::from(vec![None::<&str>; n])),
+        ],
+    )
+    .unwrap();
+    let path = std::env::temp_dir().join(format!("chrona-{}-{}.parquet", name, std::process::id()));
+    let props = WriterProperties::builder().set_max_row_group_size(row_group_size).build();
+    let mut writer = ArrowWriter::try_new(File::create(&path).unwrap(), schema, Some(props)).unwrap();
+    writer.write(&batch).unwrap();
+    writer.close().unwrap();
+    path
+}
+
+#[test]
+fn parquet_nulls_outside_the_query_are_ignored() {
+    // Session 8 has a null heart rate in the same row group as session 7's rows
+    let rows = [(7, 0, Some(60.0)), (8, 1, None), (7, 2, Some(62.0)), (7, 20, None)];
+    let path = write_nullable_hr("decode-other-session", &rows, 1024);
+    let path_str = path.to_str().unwrap();
+
+    let strict = query_sensor_data_range_parquet(path_str, 7, 0, 10).unwrap();
+    assert_eq!(strict.iter().map(|r| r.ts_unix_sec).collect::<Vec<_>>(), vec![0, 2]);
+    let lenient = query_sensor_data_range_parquet_with_mode(path_str, 8, 0, 10, DecodeMode::Lenient).unwrap();
+    assert_eq!((lenient.rows.len(), lenient.skipped), (0, 1));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn parquet_decode_errors_report_file_row_numbers() {
+    // Two row groups of three rows; the query prunes the first
+    let rows = [
+        (7, 0, Some(60.0)),
+        (7, 1, Some(60.0)),
+        (7, 2, Some(60.0)),
+        (7, 10, Some(60.0)),
+        (7, 11, None),
+        (7, 12, Some(60.0)),
+    ];
+    let path = write_nullable_hr("decode-row-groups", &rows, 3);
+
+    match query_sensor_data_range_parquet(path.to_str().unwrap(), 7, 10, 20) {
+        Err(DataLayerError::Decode { row, reason, .. }) => {
+            assert_eq!(row, 4);
+            assert_eq!(reason, "column hr is null");
+        }
+        other => panic!("expected decode error, got {:?}", other.map(|r| r.len())),
+    }
+    std::fs::remove_file(&path).unwrap();
+}
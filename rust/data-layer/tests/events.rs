@@ -0,0 +1,94 @@
+use data_layer::parquet::{read_events_parquet, write_events_parquet};
+use data_layer::query::{query_events, EventRow};
+use data_layer::scenario::{Anomaly, Scenario, ScenarioError};
+use data_layer::sqlite;
+use rusqlite::Connection;
+
+const SCENARIO: &str = r#"
+    name = "flu"
+    [[events]]
+    kind = "fever"
+    start_min = 60
+    duration_min = 120
+    peak_c = 1.2
+    [[events]]
+    kind = "dropout"
+    start_min = 10
+    duration_min = 5
+    [[events]]
+    kind = "tachycardia"
+    start_min = 30
+    duration_min = 10
+"#;
+
+fn sample_events() -> Vec<EventRow> {
+    Scenario::from_toml_str(SCENARIO).unwrap().event_rows(1_000_000)
+}
+
+#[test]
+fn scenario_parses_with_defaults_and_sorts_rows() {
+    let scenario = Scenario::from_toml_str(SCENARIO).unwrap();
+    assert_eq!(scenario.events[2].anomaly, Anomaly::Tachycardia { extra_bpm: 40.0 });
+
+    let rows = scenario.event_rows(1_000_000);
+    let kinds: Vec<_> = rows.iter().map(|r| r.kind.as_str()).collect();
+    assert_eq!(kinds, ["dropout", "tachycardia", "fever"]);
+    assert_eq!(rows[0], EventRow { kind: "dropout".into(), start_ts: 1_000_600, end_ts: 1_000_900, magnitude: None });
+    assert_eq!(rows[2].magnitude, Some(1.2));
+
+    let json = serde_json::to_string(&scenario).unwrap();
+    assert_eq!(Scenario::from_json_str(&json).unwrap(), scenario);
+}
+
+#[test]
+fn scenario_validation_reports_every_problem() {
+    let bad = r#"
+        [[events]]
+        kind = "fever"
+        start_min = 0
+        duration_min = 0
+        peak_c = 9.0
+        [[events]]
+        kind = "meteor_strike"
+        start_min = 0
+        duration_min = 1
+    "#;
+    assert!(matches!(Scenario::from_toml_str(bad), Err(ScenarioError::Parse(_))));
+
+    let bad = r#"
+        [[events]]
+        kind = "fever"
+        start_min = 0
+        duration_min = 0
+        peak_c = 9.0
+    "#;
+    match Scenario::from_toml_str(bad) {
+        Err(ScenarioError::Invalid(problems)) => assert_eq!(problems.len(), 2, "{problems:?}"),
+        other => panic!("expected invalid scenario, got {other:?}"),
+    }
+}
+
+#[test]
+fn events_round_trip_through_sqlite() {
+    let mut conn = Connection::open_in_memory().unwrap();
+    sqlite::init_db(&conn).unwrap();
+    let session = sqlite::insert_user_session(&conn, "u", "p", "2025-12-19", 1).unwrap();
+    let events = sample_events();
+    sqlite::insert_events(&mut conn, session, &events).unwrap();
+
+    assert_eq!(query_events(&conn, session, 0, i64::MAX).unwrap(), events);
+    // Only the tachycardia window [1_001_800, 1_002_400) overlaps
+    let overlapping = query_events(&conn, session, 1_002_000, 1_002_100).unwrap();
+    assert_eq!(overlapping.len(), 1);
+    assert_eq!(overlapping[0].kind, "tachycardia");
+}
+
+#[test]
+fn events_round_trip_through_parquet() {
+    let path = std::env::temp_dir().join(format!("chrona-events-{}.parquet", std::process::id()));
+    let events = sample_events();
+    write_events_parquet(&path, 7, &events).unwrap();
+    assert_eq!(read_events_parquet(&path, 7).unwrap(), events);
+    assert!(read_events_parquet(&path, 8).unwrap().is_empty());
+    std::fs::remove_file(&path).unwrap();
+}
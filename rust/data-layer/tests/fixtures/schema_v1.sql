@@ -0,0 +1,30 @@
+-- Schema and sample rows as written by the original single-batch init_db (schema v1).
+CREATE TABLE user_sessions (
+    session_id INTEGER PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    profile_name TEXT,
+    simulation_date TEXT,
+    seed INTEGER,
+    created_at TIMESTAMP
+);
+CREATE TABLE sensor_data (
+    id INTEGER PRIMARY KEY,
+    session_id INTEGER,
+    ts_unix_sec INTEGER,
+    hr REAL, hrv_rmssd REAL, eda_mus REAL, temp_c REAL, accel_mag_g REAL,
+    activity TEXT,
+    stress_level REAL,
+    exercise_flag BOOLEAN,
+    created_at TIMESTAMP,
+    FOREIGN KEY(session_id) REFERENCES user_sessions(session_id)
+);
+CREATE INDEX idx_session_ts ON sensor_data(session_id, ts_unix_sec);
+
+INSERT INTO user_sessions (session_id, user_id, profile_name, simulation_date, seed, created_at)
+VALUES (1, 'BusinessProfessional', 'BusinessProfessional', '2025-12-19', 42, '2025-12-19 00:00:00');
+
+INSERT INTO sensor_data (session_id, ts_unix_sec, hr, hrv_rmssd, eda_mus, temp_c, accel_mag_g, activity, stress_level, exercise_flag, created_at)
+VALUES
+    (1, 1, 68.4, 44.1, 2.51, 36.19, 0.14, 'sitting', 0.25, 0, '2025-12-19 00:00:01'),
+    (1, 2, 67.9, 46.0, 2.49, 36.21, 0.17, 'sitting', 0.25, 0, '2025-12-19 00:00:02'),
+    (1, 3, 69.2, 43.7, 2.53, 36.18, 0.12, 'sitting', 0.25, 0, '2025-12-19 00:00:03');
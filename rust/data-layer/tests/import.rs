@@ -0,0 +1,194 @@
+use data_layer::import::{import_dir, parse_ibi, Channel, ImportError, LabelScheme, WristRecording};
+use std::f64::consts::TAU;
+use std::fmt::Write;
+
+const START: i64 = 1_766_102_400;
+
+/// A signal file in the E4 layout: start time and rate per column, then samples.
+fn e4_csv(start: f64, rate: f64, samples: impl IntoIterator<Item = Vec<f64>>) -> String {
+    let mut samples = samples.into_iter().peekable();
+    let width = samples.peek().map_or(1, Vec::len);
+    let header = |v: f64| vec![format!("{:.6}", v); width].join(", ");
+    let mut text = format!("{}\n{}\n", header(start), header(rate));
+    for sample in samples {
+        let fields: Vec<String> = sample.iter().map(|v| v.to_string()).collect();
+        writeln!(text, "{}", fields.join(",")).unwrap();
+    }
+    text
+}
+
+fn channel(start: f64, rate: f64, seconds: f64, f: impl Fn(f64) -> Vec<f64>) -> Channel {
+    let n = (seconds * rate) as usize;
+    let text = e4_csv(start, rate, (0..n).map(|i| f(i as f64 / rate)));
+    Channel::parse("test.csv", &text).unwrap()
+}
+
+/// 60 s of wrist data: a 72 bpm pulse, EDA rising 0.1 μS/s from 2 s in, 33 °C, 1 g at
+/// rest, and WESAD labels switching from baseline to stress halfway.
+fn recording() -> WristRecording {
+    let start = START as f64;
+    WristRecording {
+        bvp: Some(channel(start, 64.0, 60.0, |t| vec![50.0 * (TAU * 1.2 * t).sin()])),
+        eda: Some(channel(start + 2.0, 4.0, 58.0, |t| vec![1.0 + 0.1 * t])),
+        temp: Some(channel(start, 4.0, 60.0, |_| vec![33.0])),
+        acc: Some(channel(start, 32.0, 60.0, |_| vec![0.0, 0.0, 64.0])),
+        labels: Some(channel(start, 4.0, 60.0, |t| vec![if t < 30.0 { 1.0 } else { 2.0 }])),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn parses_e4_channels() {
+    let text = "1766102400.000000, 1766102400.000000, 1766102400.000000\n32.000000, 32.000000, 32.000000\n-13,45,52\n-12,44,53\n";
+    let acc = Channel::parse("ACC.csv", text).unwrap();
+    assert_eq!((acc.start_ts, acc.rate_hz, acc.width, acc.len()), (START as f64, 32.0, 3, 2));
+    assert_eq!(acc.sample(1), &[-12.0, 44.0, 53.0]);
+    assert_eq!(acc.end_ts(), START as f64 + 2.0 / 32.0);
+
+    let err = Channel::parse("EDA.csv", "1766102400.0\n4.0\n0.31\nabc\n").unwrap_err();
+    assert!(matches!(err, ImportError::Parse { line: 4, .. }), "{}", err);
+    assert!(err.to_string().starts_with("EDA.csv line 4"));
+    let err = Channel::parse("ACC.csv", "1766102400.0, 1766102400.0, 1766102400.0\n32.0, 32.0, 32.0\n1,2\n").unwrap_err();
+    assert!(err.to_string().contains("expected 3 value(s), found 2"), "{}", err);
+    assert!(Channel::parse("TEMP.csv", "1766102400.0\n").is_err());
+    assert!(Channel::parse("TEMP.csv", "1766102400.0\n0\n33.1\n").is_err());
+}
+
+#[test]
+fn aligns_signals_on_their_common_seconds() {
+    let rows = recording().to_rows(LabelScheme::Wesad).unwrap();
+    // EDA starts 2 s late
+    assert_eq!(rows.len(), 58);
+    assert_eq!(rows[0].ts_unix_sec, START + 2);
+    assert_eq!(rows.last().unwrap().ts_unix_sec, START + 59);
+    assert!(rows.windows(2).all(|w| w[1].ts_unix_sec == w[0].ts_unix_sec + 1));
+
+    // Mean of the four EDA samples in each second, 0.1 μS/s from 1.0
+    let eda_first = 1.0 + 0.1 * (0.0 + 0.25 + 0.5 + 0.75) / 4.0;
+    assert!((rows[0].eda_mus - eda_first as f32).abs() < 1e-5);
+    assert!((rows[10].eda_mus - rows[0].eda_mus - 1.0).abs() < 1e-4);
+    assert!(rows.iter().all(|r| r.temp_c == 33.0 && r.accel_mag_g == 1.0 && r.sleep_stage.is_none()));
+}
+
+#[test]
+fn derives_heart_rate_and_hrv_from_bvp() {
+    let rows = recording().to_rows(LabelScheme::Wesad).unwrap();
+    for row in &rows[5..] {
+        assert!((row.hr - 72.0).abs() < 2.0, "hr {} at {}", row.hr, row.ts_unix_sec);
+        // Peaks fall on 64 Hz samples, so intervals jitter by one sample at most
+        assert!(row.hrv_rmssd < 20.0, "rmssd {}", row.hrv_rmssd);
+    }
+}
+
+#[test]
+fn prefers_device_hr_and_ibi() {
+    let mut rec = recording();
+    rec.hr = Some(channel(START as f64 + 10.0, 1.0, 50.0, |t| vec![80.0 + t]));
+    // Intervals alternating 0.8 / 0.9 s: successive differences of 100 ms
+    let mut ibi_text = String::from("1766102400.000000, IBI\n");
+    let mut t = 0.0;
+    for i in 0..70 {
+        let ibi = if i % 2 == 0 { 0.8 } else { 0.9 };
+        t += ibi;
+        writeln!(ibi_text, "{:.6},{:.6}", t, ibi).unwrap();
+    }
+    rec.ibi = parse_ibi("IBI.csv", &ibi_text).unwrap();
+    assert_eq!(rec.ibi[0], (START as f64 + 0.8, 0.8));
+
+    let rows = rec.to_rows(LabelScheme::Wesad).unwrap();
+    assert_eq!(rows[0].ts_unix_sec, START + 10);
+    assert_eq!(rows[0].hr, 80.0);
+    assert_eq!(rows[5].hr, 85.0);
+    assert!(rows.iter().all(|r| (r.hrv_rmssd - 100.0).abs() < 0.1), "{}", rows[0].hrv_rmssd);
+}
+
+#[test]
+fn leading_seconds_without_beats_are_dropped() {
+    let mut rec = recording();
+    // Device HR from the start, but the first interval only 20 s in
+    rec.hr = Some(channel(START as f64, 1.0, 60.0, |_| vec![75.0]));
+    let mut ibi_text = String::from("1766102400.000000, IBI\n");
+    for i in 0..40 {
+        writeln!(ibi_text, "{:.6},{:.6}", 20.0 + i as f64 * 0.8, 0.8).unwrap();
+    }
+    rec.ibi = parse_ibi("IBI.csv", &ibi_text).unwrap();
+    let rows = rec.to_rows(LabelScheme::Wesad).unwrap();
+    // Beats at 20.0, 20.8 and 21.6 s give the first two differences in second 21
+    assert_eq!(rows[0].ts_unix_sec, START + 21);
+    assert!(rows.iter().all(|r| r.hr == 75.0 && r.hrv_rmssd == 0.0));
+
+    rec.ibi = parse_ibi("IBI.csv", "1766102400.000000, IBI\n1.0,0.8\n").unwrap();
+    assert!(matches!(rec.to_rows(LabelScheme::Wesad), Err(ImportError::NoBeats)));
+}
+
+#[test]
+fn rmssd_skips_differences_across_missed_beats() {
+    let mut rec = recording();
+    rec.hr = Some(channel(START as f64, 1.0, 60.0, |_| vec![75.0]));
+    // Alternating 0.8 / 0.9 s intervals with every fifth beat missing from the file
+    let mut ibi_text = String::from("1766102400.000000, IBI\n");
+    let mut t = 0.0;
+    for i in 0..70 {
+        let ibi = if i % 2 == 0 { 0.8 } else { 0.9 };
+        t += ibi;
+        if i % 5 != 4 {
+            writeln!(ibi_text, "{:.6},{:.6}", t, ibi).unwrap();
+        }
+    }
+    rec.ibi = parse_ibi("IBI.csv", &ibi_text).unwrap();
+    let rows = rec.to_rows(LabelScheme::Wesad).unwrap();
+    // Pairing intervals across a gap would add differences of 0 and 200 ms
+    assert!(rows.iter().all(|r| (r.hrv_rmssd - 100.0).abs() < 0.1), "{}", rows[0].hrv_rmssd);
+}
+
+#[test]
+fn maps_labels_to_activity_and_stress() {
+    let rows = recording().to_rows(LabelScheme::Wesad).unwrap();
+    let at = |ts: i64| rows.iter().find(|r| r.ts_unix_sec == START + ts).unwrap();
+    assert_eq!((at(29).activity.as_str(), at(29).stress_level), ("sitting", 0.1));
+    assert_eq!((at(30).activity.as_str(), at(30).stress_level), ("standing", 0.8));
+    assert!(rows.iter().all(|r| !r.exercise_flag));
+
+    let dalia = LabelScheme::from_name("PPG-DaLiA").unwrap();
+    assert_eq!(dalia, LabelScheme::Dalia);
+    assert_eq!(dalia.activity(4), "cycling_easy");
+    assert!(dalia.is_exercise(4) && !dalia.is_exercise(7));
+    assert_eq!(dalia.activity(0), "transient");
+    assert_eq!(LabelScheme::Wesad.activity(6), "transient");
+    assert_eq!(LabelScheme::Wesad.stress_level(6), 0.0);
+}
+
+#[test]
+fn imports_a_recording_directory() {
+    let dir = std::env::temp_dir().join(format!("chrona-import-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let start = START as f64;
+    let write = |name: &str, rate: f64, f: &dyn Fn(f64) -> Vec<f64>| {
+        let n = (20.0 * rate) as usize;
+        std::fs::write(dir.join(name), e4_csv(start, rate, (0..n).map(|i| f(i as f64 / rate)))).unwrap();
+    };
+    write("BVP.csv", 64.0, &|t| vec![50.0 * (TAU * t).sin()]);
+    write("TEMP.csv", 4.0, &|_| vec![32.5]);
+    write("ACC.csv", 32.0, &|_| vec![0.0, 64.0, 0.0]);
+    write("labels.csv", 4.0, &|_| vec![7.0]);
+    let err = import_dir(&dir, LabelScheme::Dalia).unwrap_err();
+    assert!(matches!(err, ImportError::Missing("EDA.csv")), "{}", err);
+
+    write("EDA.csv", 4.0, &|_| vec![0.4]);
+    let rows = import_dir(&dir, LabelScheme::Dalia).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    // At 60 bpm the third beat, and with it the first RMSSD, arrives in second 3
+    assert_eq!((rows.len(), rows[0].ts_unix_sec), (17, START + 3));
+    assert!(rows.iter().all(|r| r.activity == "walking_slow" && r.eda_mus == 0.4 && r.temp_c == 32.5));
+    assert!(rows.iter().all(|r| r.hr > 0.0));
+    assert!((rows[10].hr - 60.0).abs() < 2.0, "{}", rows[10].hr);
+}
+
+#[test]
+fn rejects_recordings_without_overlap() {
+    let mut rec = recording();
+    rec.temp = Some(channel(START as f64 + 100.0, 4.0, 10.0, |_| vec![33.0]));
+    assert!(matches!(rec.to_rows(LabelScheme::Wesad), Err(ImportError::NoOverlap)));
+    rec.bvp = None;
+    assert!(matches!(rec.to_rows(LabelScheme::Wesad), Err(ImportError::Missing("HR.csv or BVP.csv"))));
+}
@@ -0,0 +1,75 @@
+use data_layer::migrations::{self, MigrationError, CURRENT_VERSION};
+use data_layer::query::query_sensor_data_range;
+use rusqlite::Connection;
+
+const SCHEMA_V1: &str = include_str!("fixtures/schema_v1.sql");
+
+fn fixture_v1() -> Connection {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(SCHEMA_V1).unwrap();
+    conn.pragma_update(None, "user_version", 1).unwrap();
+    conn
+}
+
+fn table_exists(conn: &Connection, name: &str) -> bool {
+    conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?1",
+        [name],
+        |row| row.get::<_, i64>(0),
+    )
+    .unwrap()
+        == 1
+}
+
+#[test]
+fn fresh_database_reaches_current_version() {
+    let conn = Connection::open_in_memory().unwrap();
+    assert_eq!(migrations::schema_version(&conn).unwrap(), 0);
+    assert_eq!(migrations::migrate(&conn).unwrap(), CURRENT_VERSION);
+    assert!(table_exists(&conn, "sensor_data"));
+    assert!(table_exists(&conn, "samples"));
+    assert!(table_exists(&conn, "events"));
+}
+
+#[test]
+fn upgrades_v1_fixture_and_keeps_rows() {
+    let conn = fixture_v1();
+    assert!(!table_exists(&conn, "samples"));
+
+    assert_eq!(migrations::migrate(&conn).unwrap(), CURRENT_VERSION);
+    assert!(table_exists(&conn, "samples"));
+
+    let rows = query_sensor_data_range(&conn, 1, 0, 10).unwrap();
+    assert_eq!(rows.len(), 3);
+    assert_eq!(rows[0].ts_unix_sec, 1);
+}
+
+#[test]
+fn legacy_unversioned_database_is_adopted() {
+    // Databases created before migrations existed have the v1 tables but user_version 0.
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(SCHEMA_V1).unwrap();
+    assert_eq!(migrations::migrate(&conn).unwrap(), CURRENT_VERSION);
+    assert_eq!(query_sensor_data_range(&conn, 1, 0, 10).unwrap().len(), 3);
+}
+
+#[test]
+fn migrate_is_idempotent() {
+    let conn = fixture_v1();
+    migrations::migrate(&conn).unwrap();
+    assert_eq!(migrations::migrate(&conn).unwrap(), CURRENT_VERSION);
+}
+
+#[test]
+fn refuses_database_newer_than_binary() {
+    let conn = fixture_v1();
+    conn.pragma_update(None, "user_version", CURRENT_VERSION + 1).unwrap();
+    match migrations::migrate(&conn) {
+        Err(MigrationError::TooNew { found, supported }) => {
+            assert_eq!(found, CURRENT_VERSION + 1);
+            assert_eq!(supported, CURRENT_VERSION);
+        }
+        other => panic!("expected TooNew, got {:?}", other),
+    }
+    assert!(!table_exists(&conn, "samples"));
+}
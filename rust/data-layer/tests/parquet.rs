@@ -0,0 +1,160 @@
+use arrow::array::{ArrayRef, BooleanArray, Float32Array, Int64Array, StringArray};
+use arrow::datatypes::{Field, Schema};
+use arrow::record_batch::RecordBatch;
+use data_layer::parquet::{
+    query_latest_sensor_data_parquet, query_latest_sensor_data_parquet_with_mode, query_sensor_data_range_parquet,
+    query_sensor_data_range_parquet_with_mode, sensor_data_schema, SensorParquetOptions, SensorParquetWriter,
+};
+use data_layer::query::{DecodeMode, SensorDataRow};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::ArrowWriter;
+use parquet::file::metadata::ParquetMetaData;
+use parquet::file::properties::WriterProperties;
+use parquet::file::statistics::Statistics;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+fn row(ts: i64) -> SensorDataRow {
+    SensorDataRow {
+        ts_unix_sec: ts,
+        hr: 60.0 + (ts % 30) as f32,
+        hrv_rmssd: 45.0,
+        eda_mus: 1.5,
+        temp_c: 33.2,
+        accel_mag_g: 1.0,
+        activity: if ts % 2 == 0 { "sitting" } else { "walking" }.into(),
+        stress_level: 0.3,
+        exercise_flag: false,
+        sleep_stage: None,
+    }
+}
+
+/// Comparable view of decoded rows.
+fn keys(rows: &[SensorDataRow]) -> Vec<(i64, f32, &str)> {
+    rows.iter().map(|r| (r.ts_unix_sec, r.hr, r.activity.as_str())).collect()
+}
+
+fn temp_file(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("chrona-{}-{}.parquet", name, std::process::id()))
+}
+
+fn metadata(path: &Path) -> ParquetMetaData {
+    ParquetRecordBatchReaderBuilder::try_new(File::open(path).unwrap()).unwrap().metadata().as_ref().clone()
+}
+
+/// `(min, max)` of Int64 column `column` in each row group.
+fn i64_stats(meta: &ParquetMetaData, column: usize) -> Vec<(i64, i64)> {
+    meta.row_groups()
+        .iter()
+        .map(|rg| match rg.column(column).statistics() {
+            Some(Statistics::Int64(s)) => (*s.min_opt().unwrap(), *s.max_opt().unwrap()),
+            other => panic!("no Int64 statistics: {:?}", other),
+        })
+        .collect()
+}
+
+#[test]
+fn writer_rolls_over_row_groups_at_row_group_size() {
+    let path = temp_file("row-groups");
+    let options = SensorParquetOptions { row_group_size: 100, ..Default::default() };
+    let mut writer = SensorParquetWriter::create_with_options(&path, options).unwrap();
+    for ts in 0..99 {
+        writer.write(1, &row(ts)).unwrap();
+    }
+    assert_eq!(writer.rows_written(), 0);
+    writer.write(1, &row(99)).unwrap();
+    assert_eq!(writer.rows_written(), 100);
+    // Session 2 starts mid-group
+    writer.write_all(1, &(100..150).map(row).collect::<Vec<_>>()).unwrap();
+    writer.write_all(2, &(150..250).map(row).collect::<Vec<_>>()).unwrap();
+    assert_eq!(writer.rows_written(), 200);
+    assert_eq!(writer.close().unwrap(), 250);
+
+    let meta = metadata(&path);
+    let counts: Vec<i64> = meta.row_groups().iter().map(|rg| rg.num_rows()).collect();
+    assert_eq!(counts, [100, 100, 50]);
+    assert_eq!(i64_stats(&meta, 0), [(1, 1), (1, 2), (2, 2)]);
+    assert_eq!(i64_stats(&meta, 1), [(0, 99), (100, 199), (200, 249)]);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn pruned_queries_match_a_full_scan() {
+    let path = temp_file("pruning");
+    let options = SensorParquetOptions { row_group_size: 64, ..Default::default() };
+    let mut writer = SensorParquetWriter::create_with_options(&path, options).unwrap();
+    writer.write_all(1, &(0..200).map(row).collect::<Vec<_>>()).unwrap();
+    writer.write_all(2, &(100..300).map(row).collect::<Vec<_>>()).unwrap();
+    writer.write_all(1, &(500..520).map(row).collect::<Vec<_>>()).unwrap();
+    writer.close().unwrap();
+    assert_eq!(metadata(&path).num_row_groups(), 7);
+    let path_str = path.to_str().unwrap();
+
+    for session in [1, 2, 3] {
+        let all = query_sensor_data_range_parquet(path_str, session, i64::MIN, i64::MAX).unwrap();
+        for (start, end) in [(0, 10), (60, 70), (150, 260), (190, 510), (300, 499), (519, 600)] {
+            let expected: Vec<_> =
+                all.iter().filter(|r| r.ts_unix_sec >= start && r.ts_unix_sec <= end).cloned().collect();
+            let pruned = query_sensor_data_range_parquet(path_str, session, start, end).unwrap();
+            assert_eq!(keys(&pruned), keys(&expected), "session {} [{}, {}]", session, start, end);
+        }
+        for n in [0, 1, 30, 1000] {
+            let mut expected = all.clone();
+            expected.reverse();
+            expected.truncate(n);
+            let latest = query_latest_sensor_data_parquet(path_str, session, n).unwrap();
+            assert_eq!(keys(&latest), keys(&expected), "latest {}", n);
+        }
+    }
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn pruning_skips_row_groups_outside_the_query() {
+    // A foreign writer left a null timestamp in the first row group. Such a row can't be
+    // filtered, so it is reported whenever its row group is decoded.
+    let schema = Arc::new(Schema::new(
+        sensor_data_schema()
+            .fields()
+            .iter()
+            .map(|f| Field::new(f.name(), f.data_type().clone(), true))
+            .collect::<Vec<_>>(),
+    ));
+    let floats = |v: f32| Arc::new(Float32Array::from(vec![v; 6])) as ArrayRef;
+    let batch = RecordBatch::try_new(
+        schema.clone(),
+        vec![
+            Arc::new(Int64Array::from(vec![7; 6])),
+            Arc::new(Int64Array::from(vec![Some(0), None, Some(2), Some(10), Some(11), Some(12)])),
+            floats(60.0),
+            floats(50.0),
+            floats(1.0),
+            floats(33.0),
+            floats(0.1),
+            Arc::new(StringArray::from(vec!["sitting"; 6])),
+            floats(0.2),
+            Arc::new(BooleanArray::from(vec![false; 6])),
+            Arc::new(StringArray::from(vec![None::<&str>; 6])),
+        ],
+    )
+    .unwrap();
+    let path = temp_file("pruning-skips");
+    let props = WriterProperties::builder().set_max_row_group_size(3).build();
+    let mut writer = ArrowWriter::try_new(File::create(&path).unwrap(), schema, Some(props)).unwrap();
+    writer.write(&batch).unwrap();
+    writer.close().unwrap();
+    let path_str = path.to_str().unwrap();
+
+    let full = query_sensor_data_range_parquet_with_mode(path_str, 7, 0, 20, DecodeMode::Lenient).unwrap();
+    assert_eq!((full.rows.len(), full.skipped), (5, 1));
+    let pruned = query_sensor_data_range_parquet_with_mode(path_str, 7, 10, 20, DecodeMode::Lenient).unwrap();
+    assert_eq!((pruned.rows.len(), pruned.skipped), (3, 0));
+    assert_eq!(keys(&query_sensor_data_range_parquet(path_str, 7, 10, 20).unwrap()), keys(&pruned.rows));
+
+    // The newest group alone satisfies the latest query, so the older one is never read
+    let latest = query_latest_sensor_data_parquet_with_mode(path_str, 7, 2, DecodeMode::Lenient).unwrap();
+    assert_eq!(latest.rows.iter().map(|r| r.ts_unix_sec).collect::<Vec<_>>(), [12, 11]);
+    assert_eq!(latest.skipped, 0);
+    std::fs::remove_file(&path).unwrap();
+}
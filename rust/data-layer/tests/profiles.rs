@@ -0,0 +1,97 @@
+use data_layer::profile::{presets, ProfileError, UserProfile};
+
+#[test]
+fn every_embedded_preset_loads() {
+    for (name, _) in presets::FILES {
+        let profile = presets::by_name(name).unwrap();
+        profile.validate().unwrap();
+    }
+    assert!(presets::by_name("astronaut").is_none());
+}
+
+#[test]
+fn round_trips_through_toml_and_json() {
+    let profile = presets::shift_worker();
+    let from_toml = UserProfile::from_toml_str(&profile.to_toml_string().unwrap()).unwrap();
+    let from_json = UserProfile::from_json_str(&profile.to_json_string().unwrap()).unwrap();
+    for loaded in [from_toml, from_json] {
+        assert_eq!(loaded.name, profile.name);
+        assert_eq!(loaded.age, profile.age);
+        assert_eq!(loaded.resting_hr, profile.resting_hr);
+        assert_eq!(loaded.activity_schedule.weekday.blocks.len(), profile.activity_schedule.weekday.blocks.len());
+        assert_eq!(loaded.activity_schedule.activity_at(900 * 60), profile.activity_schedule.activity_at(900 * 60));
+    }
+}
+
+#[test]
+fn custom_cohort_loads_from_file() {
+    let toml = r#"
+        name = "Night-shift nurse"
+        profile_type = "shift_worker"
+        age = 52
+        fitness_level = "low"
+        resting_hr = 72.0
+        max_hr = 168.0
+        hrv_baseline = 32.0
+        baseline_temp = 36.4
+        baseline_eda = 2.9
+        typical_sleep_start_min = 480
+        typical_sleep_duration_min = 420
+        stress_sensitivity = 0.75
+        exercise_types = ["brisk_walking"]
+        exercise_frequency_per_week = 2
+        exercise_intensity_pref = { low = 2.0, moderate = 1.0, high = 1.0 }
+        [activity_schedule]
+        weekday = [
+            { start_minute = 0, duration_min = 480, kind = "walking_slow" },
+            { start_minute = 480, duration_min = 420, kind = "sleep" },
+            { start_minute = 900, duration_min = 540, kind = "sitting" },
+        ]
+    "#;
+    let path = std::env::temp_dir().join(format!("chrona-profile-{}.toml", std::process::id()));
+    std::fs::write(&path, toml).unwrap();
+    let profile = UserProfile::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(profile.name.as_deref(), Some("Night-shift nurse"));
+    assert_eq!(profile.age, 52);
+    assert!((profile.exercise_intensity_pref.low - 0.5).abs() < 1e-6);
+}
+
+#[test]
+fn reports_every_validation_problem() {
+    let mut json: serde_json::Value = serde_json::from_str(&presets::pro_athlete().to_json_string().unwrap()).unwrap();
+    json["resting_hr"] = 250.0.into();
+    json["stress_sensitivity"] = 1.5.into();
+    match UserProfile::from_json_str(&json.to_string()) {
+        Err(ProfileError::Invalid(problems)) => {
+            assert_eq!(problems.len(), 2, "{:?}", problems);
+        }
+        other => panic!("expected validation error, got {:?}", other.map(|p| p.age)),
+    }
+}
+
+#[test]
+fn caps_exercise_frequency() {
+    let mut profile = presets::pro_athlete();
+    profile.exercise_frequency_per_week = 14;
+    assert!(profile.validate().is_ok());
+    profile.exercise_frequency_per_week = 1_000_000;
+    match profile.validate() {
+        Err(ProfileError::Invalid(problems)) => {
+            assert_eq!(problems, ["exercise_frequency_per_week (1000000) must be at most 14"]);
+        }
+        other => panic!("expected validation error, got {:?}", other),
+    }
+}
+
+#[test]
+fn rejects_unknown_fields_and_formats() {
+    let toml = presets::remote_worker().to_toml_string().unwrap();
+    let typo = toml.replacen("resting_hr", "resting_heart_rate", 1);
+    assert!(matches!(UserProfile::from_toml_str(&typo), Err(ProfileError::Parse(_))));
+    assert!(matches!(
+        presets::remote_worker().save("profile.yaml"),
+        Err(ProfileError::UnsupportedFormat(ext)) if ext == "yaml"
+    ));
+}
@@ -0,0 +1,202 @@
+use core_types::{Sample, Tier};
+use data_layer::parquet::dataset::date_of;
+use data_layer::parquet::SensorDataset;
+use data_layer::query::SensorDataRow;
+use data_layer::retention::{Eviction, ExpiredData, RetentionManager, RetentionPolicy};
+use data_layer::sqlite::{self, SensorBatchWriter};
+use rusqlite::Connection;
+use std::path::PathBuf;
+
+/// 2025-12-19T00:00:00Z
+const DAY0: i64 = 1_766_102_400;
+const DAY: i64 = 86_400;
+
+fn row(ts: i64, hr: f32, activity: &str) -> SensorDataRow {
+    SensorDataRow {
+        ts_unix_sec: ts,
+        hr,
+        hrv_rmssd: 40.0,
+        eda_mus: 2.0,
+        temp_c: 33.0,
+        accel_mag_g: 1.0,
+        activity: activity.into(),
+        stress_level: 0.2,
+        exercise_flag: false,
+        sleep_stage: None,
+    }
+}
+
+fn policy(raw_retention_days: u32, expired: ExpiredData) -> RetentionPolicy {
+    RetentionPolicy { raw_retention_days, max_bytes: u64::MAX, expired }
+}
+
+fn session_db() -> (Connection, i64) {
+    let conn = Connection::open_in_memory().unwrap();
+    sqlite::init_db(&conn).unwrap();
+    let session = sqlite::insert_user_session(&conn, "u1", "test", "2025-12-19", 1).unwrap();
+    (conn, session)
+}
+
+fn sensor_rows(conn: &Connection) -> Vec<(i64, f32, String)> {
+    let mut stmt = conn.prepare("SELECT ts_unix_sec, hr, activity FROM sensor_data ORDER BY ts_unix_sec").unwrap();
+    stmt.query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?))).unwrap().map(Result::unwrap).collect()
+}
+
+fn temp_root(name: &str) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("chrona-retention-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    root
+}
+
+#[test]
+fn tier_policies_scale_with_the_device() {
+    let mini = RetentionPolicy::for_tier(Tier::Mini8);
+    assert_eq!((mini.raw_retention_days, mini.expired), (7, ExpiredData::Delete));
+    let standard = RetentionPolicy::for_tier(Tier::Standard16);
+    assert_eq!(standard.raw_retention_days, 30);
+    assert_eq!(standard.expired, ExpiredData::Downsample { bucket_secs: 60 });
+    let pro = RetentionPolicy::for_tier(Tier::Pro32);
+    assert_eq!(pro.raw_retention_days, 180);
+    assert!(mini.max_bytes < standard.max_bytes && standard.max_bytes < pro.max_bytes);
+    assert_eq!(RetentionManager::new(Tier::Pro32).policy().max_bytes, pro.max_bytes);
+}
+
+#[test]
+fn sqlite_delete_drops_rows_before_the_window() {
+    let (mut conn, session) = session_db();
+    let rows: Vec<_> = (0..4).map(|d| row(DAY0 + d * DAY, 60.0, "sitting")).collect();
+    SensorBatchWriter::new(&mut conn, session).write(&rows).unwrap();
+    sqlite::insert_sample(&conn, "hr", &Sample { ts_ms: DAY0 * 1000, value: 60.0 }).unwrap();
+    sqlite::insert_sample(&conn, "hr", &Sample { ts_ms: (DAY0 + 3 * DAY) * 1000, value: 61.0 }).unwrap();
+
+    // Two days kept as of day 3 noon: the cutoff is day 1 noon
+    let now = DAY0 + 3 * DAY + DAY / 2;
+    let report = RetentionManager::with_policy(policy(2, ExpiredData::Delete)).sqlite(&conn).enforce(now).unwrap();
+    let summary: Vec<_> = report
+        .evictions
+        .iter()
+        .map(|e| match e {
+            Eviction::SqliteDeleted { table, before_ts, rows } => (*table, *before_ts, *rows),
+            other => panic!("unexpected eviction {:?}", other),
+        })
+        .collect();
+    let cutoff = DAY0 + DAY + DAY / 2;
+    assert_eq!(summary, [("sensor_data", cutoff, 2), ("samples", cutoff, 1)]);
+    assert_eq!(sensor_rows(&conn).iter().map(|r| r.0).collect::<Vec<_>>(), [DAY0 + 2 * DAY, DAY0 + 3 * DAY]);
+    assert!(report.before.sqlite_bytes > 0 && report.after.parquet_bytes == 0);
+}
+
+#[test]
+fn sqlite_downsample_averages_expired_buckets() {
+    let (mut conn, session) = session_db();
+    // Two expired minutes of 10 s rows, then one recent row
+    let mut rows: Vec<_> =
+        (0..6).map(|i| row(DAY0 + i * 10, 60.0 + i as f32, if i < 4 { "walking" } else { "sitting" })).collect();
+    rows.extend((0..6).map(|i| row(DAY0 + 60 + i * 10, 70.0, "sitting")));
+    rows.push(row(DAY0 + 5 * DAY, 80.0, "sitting"));
+    SensorBatchWriter::new(&mut conn, session).write(&rows).unwrap();
+    for (i, value) in [1.0, 2.0, 6.0].into_iter().enumerate() {
+        sqlite::insert_sample(&conn, "eda", &Sample { ts_ms: DAY0 * 1000 + i as i64 * 20_000, value }).unwrap();
+    }
+
+    let manager = RetentionManager::with_policy(policy(1, ExpiredData::Downsample { bucket_secs: 60 })).sqlite(&conn);
+    let report = manager.enforce(DAY0 + 5 * DAY).unwrap();
+    let summary: Vec<_> = report
+        .evictions
+        .iter()
+        .map(|e| match e {
+            Eviction::SqliteDownsampled { table, rows_in, rows_out, .. } => (*table, *rows_in, *rows_out),
+            other => panic!("unexpected eviction {:?}", other),
+        })
+        .collect();
+    assert_eq!(summary, [("sensor_data", 12, 2), ("samples", 3, 1)]);
+
+    let stored = sensor_rows(&conn);
+    assert_eq!(stored.len(), 3);
+    assert_eq!((stored[0].0, stored[0].1, stored[0].2.as_str()), (DAY0, 62.5, "walking"));
+    assert_eq!((stored[1].0, stored[1].1, stored[1].2.as_str()), (DAY0 + 60, 70.0, "sitting"));
+    assert_eq!(stored[2].1, 80.0);
+    let eda: f32 = conn.query_row("SELECT value FROM samples WHERE stream = 'eda'", [], |r| r.get(0)).unwrap();
+    assert_eq!(eda, 3.0);
+
+    // Already-downsampled buckets are left alone
+    assert!(manager.enforce(DAY0 + 5 * DAY).unwrap().evictions.is_empty());
+    assert_eq!(sensor_rows(&conn).len(), 3);
+}
+
+#[test]
+fn parquet_partitions_expire_by_date() {
+    let root = temp_root("parquet");
+    let dataset = SensorDataset::open(&root).unwrap();
+    for day in 0..4 {
+        let rows: Vec<_> =
+            (0..120).map(|i| row(DAY0 + day * DAY + i * 5, 60.0 + (i % 12) as f32, "sitting")).collect();
+        dataset.write("alice", 1, &rows).unwrap();
+    }
+    // As of day 3 noon with two days kept the cutoff is day 1 noon; only day 0 lies
+    // wholly before it
+    let now = DAY0 + 3 * DAY + DAY / 2;
+
+    let downsample =
+        RetentionManager::with_policy(policy(2, ExpiredData::Downsample { bucket_secs: 60 })).dataset(&dataset);
+    let report = downsample.enforce(now).unwrap();
+    match report.evictions.as_slice() {
+        [Eviction::PartitionDownsampled { user_id, date, rows_in: 120, rows_out: 10 }] => {
+            assert_eq!((user_id.as_str(), *date), ("alice", date_of(DAY0).unwrap()));
+        }
+        other => panic!("unexpected evictions {:?}", other),
+    }
+    let day0 = dataset.query_range("alice", None, DAY0, DAY0 + DAY - 1).unwrap();
+    assert_eq!(day0.len(), 10);
+    assert!(day0.iter().all(|r| r.hr == 65.5 && r.ts_unix_sec % 60 == 0));
+    assert!(downsample.enforce(now).unwrap().evictions.is_empty());
+
+    let delete = RetentionManager::with_policy(policy(2, ExpiredData::Delete)).dataset(&dataset);
+    let report = delete.enforce(now).unwrap();
+    assert!(matches!(report.evictions.as_slice(), [Eviction::PartitionDeleted { bytes, .. }] if *bytes > 0));
+    assert!(report.after.parquet_bytes < report.before.parquet_bytes);
+    let dates: Vec<_> = dataset.partitions().unwrap().into_iter().map(|p| p.date).collect();
+    assert_eq!(dates, (1..4).map(|d| date_of(DAY0 + d * DAY).unwrap()).collect::<Vec<_>>());
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn budget_evicts_oldest_days_first_across_backends() {
+    let root = temp_root("budget");
+    let dataset = SensorDataset::open(&root).unwrap();
+    let (mut conn, session) = session_db();
+    let day_rows = |day: i64| -> Vec<SensorDataRow> {
+        (0..50).map(|i| row(DAY0 + day * DAY + i, 60.0, "sitting")).collect()
+    };
+    dataset.write("alice", 1, &day_rows(0)).unwrap();
+    SensorBatchWriter::new(&mut conn, session).write(&day_rows(1)).unwrap();
+    dataset.write("alice", 1, &day_rows(2)).unwrap();
+    SensorBatchWriter::new(&mut conn, session).write(&day_rows(3)).unwrap();
+    let now = DAY0 + 4 * DAY;
+    let keep_all = |max_bytes| RetentionPolicy { raw_retention_days: 365, max_bytes, expired: ExpiredData::Delete };
+
+    // One byte over budget: only the oldest day goes
+    let usage = RetentionManager::with_policy(keep_all(0)).sqlite(&conn).dataset(&dataset).usage().unwrap();
+    let report =
+        RetentionManager::with_policy(keep_all(usage.total() - 1)).sqlite(&conn).dataset(&dataset).enforce(now).unwrap();
+    match report.evictions.as_slice() {
+        [Eviction::PartitionDeleted { date, .. }] => assert_eq!(*date, date_of(DAY0).unwrap()),
+        other => panic!("unexpected evictions {:?}", other),
+    }
+
+    // No budget at all: every remaining day goes, alternating backends by age
+    let report = RetentionManager::with_policy(keep_all(0)).sqlite(&conn).dataset(&dataset).enforce(now).unwrap();
+    let order: Vec<_> = report
+        .evictions
+        .iter()
+        .map(|e| match e {
+            Eviction::PartitionDeleted { date, .. } => (date.midnight().assume_utc().unix_timestamp() - DAY0) / DAY,
+            Eviction::SqliteDeleted { table: "sensor_data", before_ts, .. } => (before_ts - DAY0) / DAY - 1,
+            other => panic!("unexpected eviction {:?}", other),
+        })
+        .collect();
+    assert_eq!(order, [1, 2, 3]);
+    assert!(dataset.partitions().unwrap().is_empty());
+    assert!(sensor_rows(&conn).is_empty());
+    std::fs::remove_dir_all(&root).unwrap();
+}
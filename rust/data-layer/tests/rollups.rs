@@ -0,0 +1,130 @@
+use data_layer::query::{query_activity_minutes, query_rollups, SensorDataRow};
+use data_layer::rollup::{refresh_rollups, Resolution, RollupRow, SignalStats};
+use data_layer::sqlite::{self, SensorBatchWriter};
+use rusqlite::Connection;
+
+/// 2025-12-19T00:00:00Z
+const DAY0: i64 = 1_766_102_400;
+const HOUR: i64 = 3_600;
+
+/// A row every 10 s: heart rate cycles through 50..150, temperature rises slowly.
+fn row(i: i64) -> SensorDataRow {
+    SensorDataRow {
+        ts_unix_sec: DAY0 + i * 10,
+        hr: 50.0 + (i % 100) as f32,
+        hrv_rmssd: 40.0,
+        eda_mus: 2.0,
+        temp_c: 33.0 + i as f32 * 0.001,
+        accel_mag_g: 1.0,
+        activity: if i < 360 { "sitting" } else { "walking" }.into(),
+        stress_level: 0.2,
+        exercise_flag: false,
+        sleep_stage: None,
+    }
+}
+
+fn session_db() -> (Connection, i64) {
+    let conn = Connection::open_in_memory().unwrap();
+    sqlite::init_db(&conn).unwrap();
+    let session = sqlite::insert_user_session(&conn, "u1", "test", "2025-12-19", 1).unwrap();
+    (conn, session)
+}
+
+fn stats(rows: &[SensorDataRow], f: fn(&SensorDataRow) -> f32) -> SignalStats {
+    SignalStats::from_values(&mut rows.iter().map(f).collect::<Vec<_>>()).unwrap()
+}
+
+fn rollups(conn: &Connection, session: i64, resolution: Resolution) -> Vec<RollupRow> {
+    query_rollups(conn, session, resolution, i64::MIN, i64::MAX).unwrap()
+}
+
+#[test]
+fn rollups_summarize_each_resolution() {
+    let (mut conn, session) = session_db();
+    let rows: Vec<_> = (0..720).map(row).collect();
+    SensorBatchWriter::new(&mut conn, session).write(&rows).unwrap();
+
+    let minutes = rollups(&conn, session, Resolution::Minute);
+    assert_eq!(minutes.len(), 120);
+    for (minute, chunk) in minutes.iter().zip(rows.chunks(6)) {
+        assert_eq!((minute.bucket_start, minute.sample_count), (chunk[0].ts_unix_sec, 6));
+        assert_eq!(minute.hr, stats(chunk, |r| r.hr));
+    }
+    assert_eq!(minutes[1].hr, SignalStats { min: 56.0, mean: 58.5, max: 61.0, p95: 61.0 });
+
+    let hours = rollups(&conn, session, Resolution::Hour);
+    let buckets: Vec<_> = hours.iter().map(|h| (h.bucket_start, h.sample_count)).collect();
+    assert_eq!(buckets, [(DAY0, 360), (DAY0 + HOUR, 360)]);
+    for (hour, chunk) in hours.iter().zip(rows.chunks(360)) {
+        assert_eq!(hour.hr, stats(chunk, |r| r.hr));
+        assert_eq!(hour.temp_c, stats(chunk, |r| r.temp_c));
+    }
+
+    let days = rollups(&conn, session, Resolution::Day);
+    assert_eq!(days.len(), 1);
+    let (day, exact) = (&days[0], stats(&rows, |r| r.hr));
+    assert_eq!((day.bucket_start, day.sample_count), (DAY0, 720));
+    assert_eq!((day.hr.min, day.hr.max), (exact.min, exact.max));
+    assert!((day.hr.mean - exact.mean).abs() < 1e-4);
+    assert!((day.temp_c.mean - stats(&rows, |r| r.temp_c).mean).abs() < 1e-4);
+    // Merged from the hourly p95s: with equal weights the 95% rank falls in the higher one
+    assert_eq!(day.hr.p95, hours[0].hr.p95.max(hours[1].hr.p95));
+    assert!((day.hr.p95 - exact.p95).abs() <= 1.0, "{} vs {}", day.hr.p95, exact.p95);
+
+    let activity = query_activity_minutes(&conn, session, Resolution::Day, DAY0, DAY0).unwrap();
+    let activity: Vec<_> = activity.iter().map(|a| (a.activity.as_str(), a.minutes)).collect();
+    assert_eq!(activity, [("sitting", 60), ("walking", 60)]);
+}
+
+#[test]
+fn day_p95_is_weighted_by_hourly_sample_counts() {
+    let (mut conn, session) = session_db();
+    // A busy resting hour and a short burst of exercise in the next
+    let mut rows: Vec<_> = (0..360).map(|i| SensorDataRow { hr: 60.0 + (i % 10) as f32, ..row(i) }).collect();
+    rows.extend((360..372).map(|i| SensorDataRow { hr: 160.0, ..row(i) }));
+    SensorBatchWriter::new(&mut conn, session).write(&rows).unwrap();
+
+    let day = &rollups(&conn, session, Resolution::Day)[0];
+    assert_eq!((day.sample_count, day.hr.min, day.hr.max), (372, 60.0, 160.0));
+    // 95% of the samples fall in the resting hour, so its p95 stands for the day
+    assert_eq!(day.hr.p95, 69.0);
+}
+
+#[test]
+fn reingesting_an_overlapping_range_refreshes_only_touched_buckets() {
+    let (mut conn, session) = session_db();
+    SensorBatchWriter::new(&mut conn, session).write(&(0..360).map(row).collect::<Vec<_>>()).unwrap();
+    let before = rollups(&conn, session, Resolution::Minute);
+
+    // The last half hour again, plus the next half hour
+    let second: Vec<_> = (180..540).map(row).collect();
+    SensorBatchWriter::new(&mut conn, session).write(&second).unwrap();
+
+    let minutes = rollups(&conn, session, Resolution::Minute);
+    assert_eq!(minutes.len(), 90);
+    let key = |rows: &[RollupRow]| rows.iter().map(|m| (m.bucket_start, m.sample_count, m.hr)).collect::<Vec<_>>();
+    assert_eq!(key(&minutes[..30]), key(&before[..30]));
+    assert!(minutes[30..60].iter().all(|m| m.sample_count == 12));
+    assert!(minutes[60..].iter().all(|m| m.sample_count == 6));
+    // Each overlapping row was ingested twice, which leaves its minute's statistics alone
+    assert_eq!(minutes[30].hr, before[30].hr);
+
+    let hours = rollups(&conn, session, Resolution::Hour);
+    assert_eq!(hours.iter().map(|h| h.sample_count).collect::<Vec<_>>(), [540, 180]);
+    let day = &rollups(&conn, session, Resolution::Day)[0];
+    assert_eq!(day.sample_count, 720);
+    assert_eq!(minutes.iter().map(|m| m.sample_count).sum::<usize>(), 720);
+
+    let hour_activity = query_activity_minutes(&conn, session, Resolution::Hour, DAY0, DAY0 + HOUR).unwrap();
+    let hour_activity: Vec<_> =
+        hour_activity.iter().map(|a| (a.bucket_start, a.activity.as_str(), a.minutes)).collect();
+    assert_eq!(hour_activity, [(DAY0, "sitting", 60), (DAY0 + HOUR, "walking", 30)]);
+
+    // A later day leaves this one alone, and refreshing an unchanged range is idempotent
+    SensorBatchWriter::new(&mut conn, session).write(&[row(8640 + 5)]).unwrap();
+    let days: Vec<_> = rollups(&conn, session, Resolution::Day).iter().map(|d| d.sample_count).collect();
+    assert_eq!(days, [720, 1]);
+    let written = refresh_rollups(&conn, session, DAY0, DAY0 + 2 * HOUR - 1).unwrap();
+    assert_eq!(written, 90 + 2 + 1);
+    assert_eq!(rollups(&conn, session, Resolution::Minute).len(), 91);
+}
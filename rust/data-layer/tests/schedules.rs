@@ -0,0 +1,75 @@
+use data_layer::profile::{presets, ActivityKind, DailySchedule, ScheduleBlock, ScheduleIssue, WeeklySchedule};
+use time::Weekday;
+
+fn schedule(blocks: &[(u16, u16, ActivityKind)]) -> DailySchedule {
+    DailySchedule {
+        blocks: blocks
+            .iter()
+            .map(|&(start_minute, duration_min, kind)| ScheduleBlock { start_minute, duration_min, kind, fixed: false })
+            .collect(),
+    }
+}
+
+#[test]
+fn full_day_has_no_issues() {
+    let day = schedule(&[(0, 480, ActivityKind::Sleep), (480, 960, ActivityKind::Sitting)]);
+    assert!(day.issues().is_empty());
+}
+
+#[test]
+fn flags_gaps_overlaps_and_blocks_past_midnight() {
+    let day = schedule(&[
+        (0, 360, ActivityKind::Sleep),
+        (400, 100, ActivityKind::Sitting),
+        (450, 100, ActivityKind::Standing),
+        (1320, 180, ActivityKind::Sleep),
+    ]);
+    assert_eq!(
+        day.issues(),
+        vec![
+            ScheduleIssue::Gap { start: 360, end: 400 },
+            ScheduleIssue::Overlap { first: 1, second: 2, start: 450, end: 500 },
+            ScheduleIssue::Gap { start: 550, end: 1320 },
+            ScheduleIssue::OutOfRange { block: 3, start: 1320, end: 1500 },
+        ]
+    );
+}
+
+#[test]
+fn fill_gaps_covers_the_day() {
+    let mut day = schedule(&[(60, 60, ActivityKind::WalkingBrisk), (600, 240, ActivityKind::Sitting)]);
+    assert_eq!(day.fill_gaps(ActivityKind::Standing), 1440 - 300);
+    assert!(day.issues().is_empty());
+    assert_eq!(day.activity_at_minute(0), ActivityKind::Standing);
+    assert_eq!(day.activity_at_minute(90), ActivityKind::WalkingBrisk);
+    assert_eq!(day.activity_at_minute(1439), ActivityKind::Standing);
+}
+
+#[test]
+fn picks_weekend_template_from_timestamp() {
+    let week = WeeklySchedule {
+        weekday: schedule(&[(0, 1440, ActivityKind::Sitting)]),
+        weekend: Some(schedule(&[(0, 1440, ActivityKind::WalkingSlow)])),
+    };
+    // 2025-12-19 is a Friday, 2025-12-20 a Saturday.
+    let friday_noon = 1_766_145_600;
+    assert_eq!(week.activity_at(friday_noon), ActivityKind::Sitting);
+    assert_eq!(week.activity_at(friday_noon + 86_400), ActivityKind::WalkingSlow);
+    assert_eq!(week.activity_at(friday_noon + 3 * 86_400), ActivityKind::Sitting);
+    assert_eq!(week.for_weekday(Weekday::Sunday).blocks[0].kind, ActivityKind::WalkingSlow);
+
+    let same = WeeklySchedule::every_day(schedule(&[(0, 1440, ActivityKind::Sitting)]));
+    assert_eq!(same.activity_at(friday_noon + 86_400), ActivityKind::Sitting);
+}
+
+#[test]
+fn shift_worker_schedule_sleeps_through_typical_window() {
+    let profile = presets::shift_worker();
+    let start = profile.typical_sleep_start_min;
+    let end = start + profile.typical_sleep_duration_min;
+    let day = &profile.activity_schedule.weekday;
+    assert_eq!(day.activity_at_minute(start), ActivityKind::Sleep);
+    assert_eq!(day.activity_at_minute(end - 1), ActivityKind::Sleep);
+    let asleep = (start..end).filter(|&m| day.activity_at_minute(m) == ActivityKind::Sleep).count();
+    assert!(asleep >= 300, "only {} of the typical sleep minutes are Sleep blocks", asleep);
+}
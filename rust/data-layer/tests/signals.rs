@@ -0,0 +1,44 @@
+use core_types::{Reading, SignalKind};
+use data_layer::query::SensorDataRow;
+use data_layer::{MemoryStore, TimeSeriesStore};
+
+#[test]
+fn readings_are_stored_under_their_signal_stream() {
+    let mut store = MemoryStore::new();
+    store
+        .write_readings(&[
+            Reading::new(SignalKind::HeartRate, 1000, 61.0),
+            Reading::new(SignalKind::Eda, 1000, 2.5),
+            Reading::new(SignalKind::HeartRate, 2000, 63.0),
+        ])
+        .unwrap();
+    let hr = store.read_range("heart_rate", 0, 5000).unwrap();
+    assert_eq!(hr.iter().map(|s| s.value).collect::<Vec<_>>(), [61.0, 63.0]);
+    assert_eq!(store.read_latest("eda", 5).unwrap().len(), 1);
+}
+
+#[test]
+fn sensor_rows_map_columns_to_signals() {
+    let mut row = SensorDataRow {
+        ts_unix_sec: 10,
+        hr: 0.0,
+        hrv_rmssd: 0.0,
+        eda_mus: 0.0,
+        temp_c: 0.0,
+        accel_mag_g: 0.0,
+        activity: "sitting".into(),
+        stress_level: 0.1,
+        exercise_flag: false,
+        sleep_stage: None,
+    };
+    for (i, kind) in SensorDataRow::SIGNALS.into_iter().enumerate() {
+        assert!(row.set_value(kind, i as f32 + 1.0));
+    }
+    assert!(!row.set_value(SignalKind::SpO2, 97.0));
+    assert_eq!((row.hr, row.temp_c, row.accel_mag_g), (1.0, 4.0, 5.0));
+    assert_eq!(row.value(SignalKind::Steps), None);
+
+    let readings = row.readings();
+    assert_eq!(readings.len(), 5);
+    assert_eq!(readings[1], Reading::new(SignalKind::HrvRmssd, 10_000, 2.0));
+}
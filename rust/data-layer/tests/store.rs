@@ -0,0 +1,105 @@
+use core_types::{Reading, Sample, SignalKind};
+use data_layer::{MemoryStore, ParquetStore, SqliteStore, TimeSeriesStore};
+use rusqlite::Connection;
+
+fn samples(range: std::ops::Range<i64>) -> Vec<Sample> {
+    range.map(|i| Sample { ts_ms: i * 1000, value: i as f32 }).collect()
+}
+
+fn values(samples: &[Sample]) -> Vec<f32> {
+    samples.iter().map(|s| s.value).collect()
+}
+
+/// Shared contract: batches and single writes land in their own stream, ranges are
+/// inclusive and oldest first, latest is newest first.
+fn check_store(store: &mut dyn TimeSeriesStore) {
+    store.write_batch("hr", &samples(0..10)).unwrap();
+    store.write("hr", Sample { ts_ms: 10_000, value: 10.0 }).unwrap();
+    store.write_batch("eda", &samples(0..3)).unwrap();
+
+    assert_eq!(values(&store.read_range("hr", 2000, 5000).unwrap()), [2.0, 3.0, 4.0, 5.0]);
+    assert_eq!(values(&store.read_latest("hr", 3).unwrap()), [10.0, 9.0, 8.0]);
+    assert_eq!(store.read_range("eda", 0, 60_000).unwrap().len(), 3);
+    assert!(store.read_range("missing", 0, 60_000).unwrap().is_empty());
+    assert!(store.read_latest("missing", 5).unwrap().is_empty());
+
+    // Tagged readings go to the stream named after their signal
+    let readings: Vec<_> = (0..4)
+        .flat_map(|i| {
+            let ts = 20_000 + i * 1000;
+            [Reading::new(SignalKind::HeartRate, ts, 60.0 + i as f32), Reading::new(SignalKind::Eda, ts, 2.0)]
+        })
+        .collect();
+    store.write_readings(&readings).unwrap();
+    assert_eq!(values(&store.read_range("heart_rate", 20_000, 30_000).unwrap()), [60.0, 61.0, 62.0, 63.0]);
+    assert_eq!(values(&store.read_latest("eda", 1).unwrap()), [2.0]);
+}
+
+#[test]
+fn memory_store_reads_back_writes() {
+    check_store(&mut MemoryStore::new());
+}
+
+#[test]
+fn sqlite_store_reads_back_writes() {
+    check_store(&mut SqliteStore::from_connection(Connection::open_in_memory().unwrap()).unwrap());
+}
+
+#[test]
+fn sqlite_store_writes_readings_atomically() {
+    let conn = Connection::open_in_memory().unwrap();
+    let mut store = SqliteStore::from_connection(conn).unwrap();
+    store
+        .connection()
+        .execute_batch(
+            "CREATE TRIGGER reject_ts_5 BEFORE INSERT ON samples WHEN NEW.ts_ms = 5000
+             BEGIN SELECT RAISE(ABORT, 'rejected'); END;",
+        )
+        .unwrap();
+    let readings: Vec<_> = (0..10)
+        .flat_map(|i| [SignalKind::HeartRate, SignalKind::SkinTemp].map(|kind| Reading::new(kind, i * 1000, i as f32)))
+        .collect();
+    assert!(store.write_readings(&readings).is_err());
+    assert!(store.read_range("heart_rate", 0, 60_000).unwrap().is_empty());
+    assert!(store.read_range("skin_temp", 0, 60_000).unwrap().is_empty());
+
+    let kept: Vec<_> = readings.into_iter().filter(|r| r.ts_ms != 5000).collect();
+    store.write_readings(&kept).unwrap();
+    assert_eq!(store.read_range("heart_rate", 0, 60_000).unwrap().len(), 9);
+    assert_eq!(values(&store.read_latest("skin_temp", 1).unwrap()), [9.0]);
+}
+
+#[test]
+fn parquet_store_reads_buffered_and_flushed_parts() {
+    let root = std::env::temp_dir().join(format!("chrona-store-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    {
+        // Small parts, so reads merge part files with the unflushed buffer
+        let mut store = ParquetStore::with_flush_every(&root, 4).unwrap();
+        check_store(&mut store);
+        assert_eq!(std::fs::read_dir(root.join("hr")).unwrap().count(), 2);
+    }
+    // Dropping flushed the buffered tails
+    assert_eq!(std::fs::read_dir(root.join("hr")).unwrap().count(), 3);
+    let store = ParquetStore::open(&root).unwrap();
+    assert_eq!(store.read_range("hr", 0, 60_000).unwrap().len(), 11);
+    assert_eq!(values(&store.read_latest("eda", 1).unwrap()), [2.0]);
+    drop(store);
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn parquet_store_rejects_path_like_stream_names() {
+    let root = std::env::temp_dir().join(format!("chrona-store-names-{}", std::process::id()));
+    let mut store = ParquetStore::open(&root).unwrap();
+    let sample = Sample { ts_ms: 0, value: 1.0 };
+    for stream in ["", ".", "..", "../escape", "a/b", "a\\b", "/abs"] {
+        assert!(store.write(stream, sample.clone()).is_err(), "{:?}", stream);
+        assert!(store.read_range(stream, 0, 1).is_err(), "{:?}", stream);
+    }
+    store.write("heart_rate.v2", sample).unwrap();
+    store.flush().unwrap();
+    assert!(root.join("heart_rate.v2").is_dir());
+    drop(store);
+    std::fs::remove_dir_all(&root).unwrap();
+}
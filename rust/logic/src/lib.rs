@@ -2,18 +2,18 @@
 
 //! Orchestration logic tying sensors, storage, ML, and LLMs together.
 
-use core_types::Tier;
-// use data_layer::{MhealthRecord, TimeSeriesStore};
+use core_types::{Reading, Sample, Tier};
+use data_layer::TimeSeriesStore;
 use llm_runtime::{LlmEngine, Prompt, Response};
 use ml_runtime::Model;
-use sensors::Sensor;
+use sensors::{ActivityClassifier, MhealthRecord, Sensor};
 
 
 /// High-level orchestrator for one tier.
 pub struct Engine<S, T, M, L>
 where
     S: Sensor,
-    // T: TimeSeriesStore,
+    T: TimeSeriesStore,
     M: Model,
     L: LlmEngine,
 {
@@ -32,23 +32,29 @@ where
 impl<S, T, M, L> Engine<S, T, M, L>
 where
     S: Sensor,
-    // T: TimeSeriesStore,
+    T: TimeSeriesStore,
     M: Model,
     L: LlmEngine,
 {
     /// Poll sensor, store data, run model, and send a lightweight prompt.
-    pub fn step(&mut self) -> Response {
-        if let Some(sample) = self.sensor.poll() {
-            // self.store.write(self.sensor.name(), sample.clone());
-            let _score = self.model.infer(&[sample.clone()]);
+    ///
+    /// Fails without running the model if the readings could not be stored.
+    pub fn step(&mut self) -> anyhow::Result<Response> {
+        let readings = self.sensor.poll();
+        if !readings.is_empty() {
+            self.store
+                .write_readings(&readings)
+                .map_err(|e| e.context(format!("failed to persist readings for {}", self.sensor.name())))?;
+            let samples: Vec<Sample> = readings.iter().map(Reading::sample).collect();
+            let _score = self.model.infer(&samples);
             let prompt = Prompt {
                 tier: self.tier,
                 user: "Explain the latest score",
                 system: "Keep it short",
             };
-            self.llm.run(prompt)
+            Ok(self.llm.run(prompt))
         } else {
-            Response { model: self.llm.model_id().to_string(), text: String::from("No data") }
+            Ok(Response { model: self.llm.model_id().to_string(), text: String::from("No data") })
         }
     }
 }
@@ -70,24 +76,31 @@ pub struct ActivityContext {
 
 impl ActivityContext {
     /// Create context from MHEALTH record and resting HR.
-        // /// Create context from MHEALTH record and resting HR.
-        // pub fn from_record(record: &MhealthRecord, resting_hr: u32) -> Self {
-        //     let (activity, confidence) = ActivityClassifier::classify(&[record.clone()]);
-        //     let intensity = record.intensity();
-        //     
-        //     // Predict HR: resting + (max_hr - resting) * intensity
-        //     // Assume max HR ~= 180 for typical person
-        //     let max_hr = 180;
-        //     let predicted_hr = (resting_hr as f32 + (max_hr - resting_hr) as f32 * intensity) as u32;
-        //
-        //     ActivityContext {
-        //         activity,
-        //         activity_name: record.activity_name().to_string(),
-        //         confidence,
-        //         predicted_hr,
-        //         intensity,
-        //     }
-        // }
+    pub fn from_record(record: &MhealthRecord, resting_hr: u32) -> Self {
+        let (activity, confidence) = ActivityClassifier::classify(std::slice::from_ref(record));
+        let intensity = record.intensity();
+
+        // Predict HR: resting + (max_hr - resting) * intensity
+        // Assume max HR ~= 180 for typical person
+        let max_hr: u32 = 180;
+        let predicted_hr = (resting_hr as f32 + max_hr.saturating_sub(resting_hr) as f32 * intensity) as u32;
+
+        ActivityContext {
+            activity,
+            activity_name: record.activity_name().to_string(),
+            confidence,
+            predicted_hr,
+            intensity,
+        }
+    }
+
+    /// Create context from a window of MHEALTH records: the window's majority activity,
+    /// with its share of the window as confidence. `None` if nothing is labelled.
+    pub fn from_window(window: &[MhealthRecord], resting_hr: u32) -> Option<Self> {
+        let (activity, confidence) = ActivityClassifier::classify(window);
+        let record = window.iter().find(|r| r.label == activity && activity != 0)?;
+        Some(ActivityContext { confidence, ..Self::from_record(record, resting_hr) })
+    }
 
     /// Generate LLM prompt for 8GB tier.
     /// Returns a context string for the LLM about current activity and HR.
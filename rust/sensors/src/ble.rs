@@ -0,0 +1,313 @@
+//! Bluetooth Heart Rate Measurement (characteristic 0x2A37) decoding and replay.
+//!
+//! A notification payload is a flags byte followed by the fields it enables:
+//!
+//! | Bit | Meaning when set |
+//! | --- | --- |
+//! | 0 | heart rate is `u16` instead of `u8` |
+//! | 1 | skin contact detected |
+//! | 2 | skin contact status is supported |
+//! | 3 | energy expended (`u16`, kJ) follows the heart rate |
+//! | 4 | RR intervals (`u16` each, 1/1024 s) fill the rest of the payload |
+//!
+//! Multi-byte fields are little-endian.
+
+use crate::Sensor;
+use core_types::{Reading, SignalKind};
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::sync::mpsc::Receiver;
+
+/// UUID of the Heart Rate Measurement characteristic.
+pub const HEART_RATE_MEASUREMENT_UUID: u16 = 0x2A37;
+
+const FLAG_HR_U16: u8 = 0x01;
+const FLAG_CONTACT_DETECTED: u8 = 0x02;
+const FLAG_CONTACT_SUPPORTED: u8 = 0x04;
+const FLAG_ENERGY_EXPENDED: u8 = 0x08;
+const FLAG_RR_INTERVALS: u8 = 0x10;
+
+/// One decoded Heart Rate Measurement notification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeartRateMeasurement {
+    /// Heart rate (bpm).
+    pub heart_rate: u16,
+    /// Skin contact, if the sensor reports it.
+    pub sensor_contact: Option<bool>,
+    /// Energy expended since the last reset (kJ), if present.
+    pub energy_expended_kj: Option<u16>,
+    /// RR intervals in 1/1024 s, oldest first.
+    pub rr_intervals: Vec<u16>,
+}
+
+/// A payload that is not a valid Heart Rate Measurement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BleDecodeError {
+    /// The payload has no flags byte.
+    Empty,
+    /// The payload ends inside `field`.
+    Truncated {
+        /// Field being read.
+        field: &'static str,
+        /// Bytes needed to read it, counted from the start of the payload.
+        needed: usize,
+        /// Payload length.
+        len: usize,
+    },
+    /// The RR interval bytes are not a whole number of `u16`s.
+    OddRrBytes(usize),
+}
+
+impl fmt::Display for BleDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BleDecodeError::Empty => write!(f, "empty heart rate measurement"),
+            BleDecodeError::Truncated { field, needed, len } => {
+                write!(f, "heart rate measurement truncated in {}: needs {} bytes, got {}", field, needed, len)
+            }
+            BleDecodeError::OddRrBytes(n) => write!(f, "{} RR interval bytes is not a whole number of intervals", n),
+        }
+    }
+}
+
+impl std::error::Error for BleDecodeError {}
+
+impl HeartRateMeasurement {
+    /// Decode a notification payload.
+    pub fn decode(payload: &[u8]) -> Result<Self, BleDecodeError> {
+        let (&flags, _) = payload.split_first().ok_or(BleDecodeError::Empty)?;
+        let mut pos = 1;
+        let mut take = |field: &'static str, n: usize| -> Result<&[u8], BleDecodeError> {
+            let bytes = payload
+                .get(pos..pos + n)
+                .ok_or(BleDecodeError::Truncated { field, needed: pos + n, len: payload.len() })?;
+            pos += n;
+            Ok(bytes)
+        };
+        let heart_rate = if flags & FLAG_HR_U16 != 0 {
+            let b = take("heart rate", 2)?;
+            u16::from_le_bytes([b[0], b[1]])
+        } else {
+            take("heart rate", 1)?[0] as u16
+        };
+        let energy_expended_kj = if flags & FLAG_ENERGY_EXPENDED != 0 {
+            let b = take("energy expended", 2)?;
+            Some(u16::from_le_bytes([b[0], b[1]]))
+        } else {
+            None
+        };
+        let rr_intervals = if flags & FLAG_RR_INTERVALS != 0 {
+            let rest = &payload[pos..];
+            if !rest.len().is_multiple_of(2) {
+                return Err(BleDecodeError::OddRrBytes(rest.len()));
+            }
+            rest.chunks_exact(2).map(|b| u16::from_le_bytes([b[0], b[1]])).collect()
+        } else {
+            Vec::new()
+        };
+        let sensor_contact = (flags & FLAG_CONTACT_SUPPORTED != 0).then_some(flags & FLAG_CONTACT_DETECTED != 0);
+        Ok(Self { heart_rate, sensor_contact, energy_expended_kj, rr_intervals })
+    }
+
+    /// Encode as a notification payload, using the smallest heart rate format that fits.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut flags = 0;
+        let mut payload = vec![0];
+        if self.heart_rate > u8::MAX as u16 {
+            flags |= FLAG_HR_U16;
+            payload.extend(self.heart_rate.to_le_bytes());
+        } else {
+            payload.push(self.heart_rate as u8);
+        }
+        match self.sensor_contact {
+            Some(true) => flags |= FLAG_CONTACT_SUPPORTED | FLAG_CONTACT_DETECTED,
+            Some(false) => flags |= FLAG_CONTACT_SUPPORTED,
+            None => {}
+        }
+        if let Some(energy) = self.energy_expended_kj {
+            flags |= FLAG_ENERGY_EXPENDED;
+            payload.extend(energy.to_le_bytes());
+        }
+        if !self.rr_intervals.is_empty() {
+            flags |= FLAG_RR_INTERVALS;
+            payload.extend(self.rr_intervals.iter().flat_map(|rr| rr.to_le_bytes()));
+        }
+        payload[0] = flags;
+        payload
+    }
+
+    /// RR intervals converted to milliseconds.
+    pub fn rr_intervals_ms(&self) -> Vec<f32> {
+        self.rr_intervals.iter().map(|&rr| rr as f32 * 1000.0 / 1024.0).collect()
+    }
+
+    /// False only when the sensor reports that it has lost skin contact.
+    pub fn has_contact(&self) -> bool {
+        self.sensor_contact != Some(false)
+    }
+}
+
+/// Append one notification to a capture stream as a length byte followed by the payload.
+pub fn write_notification(out: &mut impl Write, payload: &[u8]) -> io::Result<()> {
+    let len = u8::try_from(payload.len()).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "payload over 255 bytes"))?;
+    out.write_all(&[len])?;
+    out.write_all(payload)
+}
+
+/// Where notification payloads come from.
+enum Source {
+    /// Length-prefixed capture, as written by [`write_notification`].
+    Capture(Box<dyn Read + Send>),
+    /// Payloads forwarded by a live GATT client.
+    Channel(Receiver<Vec<u8>>),
+}
+
+/// Replays captured (or forwards live) Heart Rate Measurement notifications as readings.
+///
+/// Notifications carry no timestamps, so the clock starts at `start_ts_ms` and advances by
+/// each notification's RR intervals, or by one second when it has none. Every poll emits
+/// one `RrInterval` reading (ms) per beat, stamped at the beat's end, and a `HeartRate`
+/// reading at the end of the notification's span: its last beat, or one second after the
+/// previous notification. Notifications reporting lost skin contact yield no readings;
+/// undecodable ones are skipped and counted. A capture that breaks off inside a
+/// notification finishes the replay and leaves the failure in [`BleHeartRateSensor::error`].
+pub struct BleHeartRateSensor {
+    name: String,
+    source: Source,
+    ts_ms: i64,
+    finished: bool,
+    skipped: usize,
+    error: Option<io::Error>,
+    last: Option<HeartRateMeasurement>,
+}
+
+impl BleHeartRateSensor {
+    /// Channels this sensor can produce.
+    pub const CHANNELS: [SignalKind; 2] = [SignalKind::HeartRate, SignalKind::RrInterval];
+
+    fn new(name: impl Into<String>, source: Source, start_ts_ms: i64) -> Self {
+        Self { name: name.into(), source, ts_ms: start_ts_ms, finished: false, skipped: 0, error: None, last: None }
+    }
+
+    /// Replay a length-prefixed capture (see [`write_notification`]), one notification per poll.
+    pub fn from_reader(reader: impl Read + Send + 'static, start_ts_ms: i64) -> Self {
+        Self::new("ble_hr_capture", Source::Capture(Box::new(reader)), start_ts_ms)
+    }
+
+    /// Replay a capture file.
+    pub fn open(path: impl AsRef<std::path::Path>, start_ts_ms: i64) -> io::Result<Self> {
+        let file = std::fs::File::open(path)?;
+        Ok(Self::from_reader(io::BufReader::new(file), start_ts_ms))
+    }
+
+    /// Consume payloads sent by a GATT client; each poll drains every pending one.
+    pub fn from_channel(receiver: Receiver<Vec<u8>>, start_ts_ms: i64) -> Self {
+        Self::new("ble_hr", Source::Channel(receiver), start_ts_ms)
+    }
+
+    /// True once the capture has ended or the channel's sender has hung up.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Notifications that failed to decode so far.
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+
+    /// Why the capture ended early, if it was truncated or failed to read rather than
+    /// ending cleanly between notifications.
+    pub fn error(&self) -> Option<&io::Error> {
+        self.error.as_ref()
+    }
+
+    /// The most recently decoded measurement.
+    pub fn last_measurement(&self) -> Option<&HeartRateMeasurement> {
+        self.last.as_ref()
+    }
+
+    /// Next raw payload, or `None` if nothing is available now.
+    fn next_payload(&mut self) -> Option<Vec<u8>> {
+        if self.finished {
+            return None;
+        }
+        let payload = match &mut self.source {
+            Source::Capture(reader) => match read_notification(reader.as_mut()) {
+                Ok(payload) => payload,
+                Err(e) => {
+                    self.error = Some(e);
+                    None
+                }
+            },
+            Source::Channel(receiver) => match receiver.try_recv() {
+                Ok(payload) => Some(payload),
+                Err(std::sync::mpsc::TryRecvError::Empty) => return None,
+                Err(std::sync::mpsc::TryRecvError::Disconnected) => None,
+            },
+        };
+        self.finished = payload.is_none();
+        payload
+    }
+
+    /// Decode one payload into readings, advancing the clock.
+    fn readings_for(&mut self, payload: &[u8], readings: &mut Vec<Reading>) {
+        let measurement = match HeartRateMeasurement::decode(payload) {
+            Ok(m) => m,
+            Err(_) => {
+                self.skipped += 1;
+                return;
+            }
+        };
+        let rr_ms = measurement.rr_intervals_ms();
+        let start = self.ts_ms;
+        self.ts_ms += if rr_ms.is_empty() { 1000 } else { rr_ms.iter().map(|&rr| rr as f64).sum::<f64>().round() as i64 };
+        if measurement.has_contact() {
+            // Offsets within the notification, so millisecond timestamps keep full precision
+            let mut offset = 0f64;
+            for rr in rr_ms {
+                offset += rr as f64;
+                readings.push(Reading::new(SignalKind::RrInterval, start + offset.round() as i64, rr));
+            }
+            readings.push(Reading::new(SignalKind::HeartRate, self.ts_ms, measurement.heart_rate as f32));
+        }
+        self.last = Some(measurement);
+    }
+}
+
+/// Read one length-prefixed payload; `Ok(None)` at a clean end of stream.
+fn read_notification(reader: &mut dyn Read) -> io::Result<Option<Vec<u8>>> {
+    let mut len = [0u8];
+    if reader.read(&mut len)? == 0 {
+        return Ok(None);
+    }
+    let mut payload = vec![0; len[0] as usize];
+    reader.read_exact(&mut payload)?;
+    Ok(Some(payload))
+}
+
+impl Sensor for BleHeartRateSensor {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn channels(&self) -> &[SignalKind] {
+        &Self::CHANNELS
+    }
+
+    fn poll(&mut self) -> Vec<Reading> {
+        let mut readings = Vec::new();
+        match self.source {
+            Source::Capture(_) => {
+                if let Some(payload) = self.next_payload() {
+                    self.readings_for(&payload, &mut readings);
+                }
+            }
+            Source::Channel(_) => {
+                while let Some(payload) = self.next_payload() {
+                    self.readings_for(&payload, &mut readings);
+                }
+            }
+        }
+        readings
+    }
+}
@@ -1,45 +1,77 @@
 
 
 
-use core_types::Sample;
-use rand::Rng;
+pub mod ble;
+pub mod mhealth;
+mod replay;
 
-/// Generic sensor interface.
+pub use ble::{BleHeartRateSensor, HeartRateMeasurement};
+pub use mhealth::{ActivityClassifier, MhealthRecord, MhealthStreamingSensor};
+pub use replay::{ReplaySensor, ReplaySpeed};
+
+use core_types::{Reading, SignalKind};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+/// Generic multi-channel sensor interface.
 pub trait Sensor {
     /// Human-readable name.
     fn name(&self) -> &str;
-    /// Poll for the next sample (non-blocking).
-    fn poll(&mut self) -> Option<Sample>;
+    /// Signals this sensor can produce.
+    fn channels(&self) -> &[SignalKind];
+    /// Poll for the readings of the next time step (non-blocking).
+    ///
+    /// Each reading is tagged with one of [`Sensor::channels`]; a step may cover only
+    /// some channels, and an empty vector means no data is available.
+    fn poll(&mut self) -> Vec<Reading>;
 }
 
 
 /// Simple synthetic heart-rate-like generator.
-pub struct SyntheticHeartRate {
+///
+/// Jitter is drawn from `rng`; inject a seeded generator to reproduce a trace exactly.
+pub struct SyntheticHeartRate<R = ChaCha8Rng> {
     /// Baseline value in bpm.
     pub baseline: f32,
     /// Random jitter range (+/-).
     pub jitter: f32,
     /// Current timestamp in milliseconds.
     pub ts_ms: i64,
+    rng: R,
 }
 
 impl SyntheticHeartRate {
-    /// Create a new generator.
+    /// Create a new generator seeded from OS entropy.
     pub fn new(baseline: f32, jitter: f32, start_ts_ms: i64) -> Self {
-        Self { baseline, jitter, ts_ms: start_ts_ms }
+        Self::with_rng(baseline, jitter, start_ts_ms, ChaCha8Rng::from_entropy())
+    }
+
+    /// Create a generator whose output is fully determined by `seed`.
+    pub fn seeded(baseline: f32, jitter: f32, start_ts_ms: i64, seed: u64) -> Self {
+        Self::with_rng(baseline, jitter, start_ts_ms, ChaCha8Rng::seed_from_u64(seed))
     }
 }
 
-impl Sensor for SyntheticHeartRate {
+impl<R: Rng> SyntheticHeartRate<R> {
+    /// Create a generator drawing jitter from `rng`.
+    pub fn with_rng(baseline: f32, jitter: f32, start_ts_ms: i64, rng: R) -> Self {
+        Self { baseline, jitter, ts_ms: start_ts_ms, rng }
+    }
+}
+
+impl<R: Rng> Sensor for SyntheticHeartRate<R> {
     fn name(&self) -> &str {
         "synthetic_hr"
     }
 
-    fn poll(&mut self) -> Option<Sample> {
-        let mut rng = rand::thread_rng();
-        let delta = rng.gen_range(-self.jitter..self.jitter);
+    fn channels(&self) -> &[SignalKind] {
+        &[SignalKind::HeartRate]
+    }
+
+    fn poll(&mut self) -> Vec<Reading> {
+        let delta = self.rng.gen_range(-self.jitter..self.jitter);
         self.ts_ms += 1000;
-        Some(Sample { ts_ms: self.ts_ms, value: self.baseline + delta })
+        vec![Reading::new(SignalKind::HeartRate, self.ts_ms, self.baseline + delta)]
     }
 }
 
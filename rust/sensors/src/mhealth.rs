@@ -0,0 +1,287 @@
+//! UCI MHEALTH dataset: log parser and windowed streaming sensor.
+//!
+//! Each line of an `mHealth_subjectN.log` file holds one 50 Hz sample: 23 sensor columns
+//! followed by the activity label, separated by tabs. Commas and spaces are accepted too, so
+//! CSV exports of the logs parse the same way.
+//!
+//! | Columns | Signal |
+//! | --- | --- |
+//! | 1–3 | chest acceleration X/Y/Z (m/s²) |
+//! | 4–5 | ECG leads 1 and 2 (mV) |
+//! | 6–8, 9–11, 12–14 | left-ankle acceleration (m/s²), gyroscope (deg/s), magnetometer (local) |
+//! | 15–17, 18–20, 21–23 | right-lower-arm acceleration, gyroscope, magnetometer |
+//! | 24 | label: 0 = none, 1–12 = L1–L12 |
+
+use crate::Sensor;
+use core_types::{Reading, SignalKind};
+use std::fmt;
+use std::path::Path;
+
+/// Sampling rate of the MHEALTH recordings.
+pub const SAMPLE_RATE_HZ: u32 = 50;
+/// Sensor columns per line, before the label.
+pub const SIGNAL_COLUMNS: usize = 23;
+/// Standard gravity, for converting m/s² to g.
+const G: f32 = 9.806_65;
+
+/// Activity names for labels L1–L12.
+const ACTIVITY_NAMES: [&str; 12] = [
+    "Standing still",
+    "Sitting and relaxing",
+    "Lying down",
+    "Walking",
+    "Climbing stairs",
+    "Waist bends forward",
+    "Frontal elevation of arms",
+    "Knees bending (crouching)",
+    "Cycling",
+    "Jogging",
+    "Running",
+    "Jump front & back",
+];
+
+/// Relative exertion of L1–L12 in [0, 1].
+const ACTIVITY_INTENSITY: [f32; 12] = [0.1, 0.05, 0.0, 0.35, 0.6, 0.3, 0.25, 0.45, 0.55, 0.75, 0.9, 0.85];
+
+/// One 50 Hz MHEALTH sample.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MhealthRecord {
+    /// Chest acceleration X/Y/Z (m/s²).
+    pub chest_accel: [f32; 3],
+    /// ECG leads 1 and 2 (mV).
+    pub ecg: [f32; 2],
+    /// Left-ankle acceleration X/Y/Z (m/s²).
+    pub ankle_accel: [f32; 3],
+    /// Left-ankle gyroscope X/Y/Z (deg/s).
+    pub ankle_gyro: [f32; 3],
+    /// Left-ankle magnetometer X/Y/Z (local units).
+    pub ankle_mag: [f32; 3],
+    /// Right-lower-arm acceleration X/Y/Z (m/s²).
+    pub arm_accel: [f32; 3],
+    /// Right-lower-arm gyroscope X/Y/Z (deg/s).
+    pub arm_gyro: [f32; 3],
+    /// Right-lower-arm magnetometer X/Y/Z (local units).
+    pub arm_mag: [f32; 3],
+    /// Activity label: 0 = none, 1–12 = L1–L12.
+    pub label: u8,
+}
+
+impl MhealthRecord {
+    /// Parse one log line.
+    pub fn parse_line(line: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = line
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|f| !f.is_empty())
+            .collect();
+        if fields.len() != SIGNAL_COLUMNS + 1 {
+            return Err(format!("expected {} columns, found {}", SIGNAL_COLUMNS + 1, fields.len()));
+        }
+        let mut v = [0f32; SIGNAL_COLUMNS];
+        for (i, field) in fields[..SIGNAL_COLUMNS].iter().enumerate() {
+            v[i] = field.parse().map_err(|_| format!("column {}: invalid number {:?}", i + 1, field))?;
+        }
+        let label = fields[SIGNAL_COLUMNS]
+            .parse::<f32>()
+            .ok()
+            .filter(|l| l.fract() == 0.0 && (0.0..=12.0).contains(l))
+            .ok_or_else(|| format!("invalid activity label {:?}", fields[SIGNAL_COLUMNS]))? as u8;
+        let xyz = |i: usize| [v[i], v[i + 1], v[i + 2]];
+        Ok(Self {
+            chest_accel: xyz(0),
+            ecg: [v[3], v[4]],
+            ankle_accel: xyz(5),
+            ankle_gyro: xyz(8),
+            ankle_mag: xyz(11),
+            arm_accel: xyz(14),
+            arm_gyro: xyz(17),
+            arm_mag: xyz(20),
+            label,
+        })
+    }
+
+    /// Name of the labelled activity, or `"Unlabelled"` for label 0.
+    pub fn activity_name(&self) -> &'static str {
+        match self.label {
+            1..=12 => ACTIVITY_NAMES[self.label as usize - 1],
+            _ => "Unlabelled",
+        }
+    }
+
+    /// Relative exertion of the labelled activity in [0, 1]; 0 when unlabelled.
+    pub fn intensity(&self) -> f32 {
+        match self.label {
+            1..=12 => ACTIVITY_INTENSITY[self.label as usize - 1],
+            _ => 0.0,
+        }
+    }
+
+    /// Chest acceleration magnitude (g).
+    pub fn chest_accel_mag_g(&self) -> f32 {
+        self.chest_accel.iter().map(|a| a * a).sum::<f32>().sqrt() / G
+    }
+}
+
+/// A malformed line in an MHEALTH log.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MhealthParseError {
+    /// 1-based line number.
+    pub line: usize,
+    /// What was wrong with it.
+    pub reason: String,
+}
+
+impl fmt::Display for MhealthParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "MHEALTH line {}: {}", self.line, self.reason)
+    }
+}
+
+impl std::error::Error for MhealthParseError {}
+
+/// Parse a whole MHEALTH log, skipping blank lines.
+pub fn parse_log(content: &str) -> Result<Vec<MhealthRecord>, MhealthParseError> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| MhealthRecord::parse_line(line).map_err(|reason| MhealthParseError { line: i + 1, reason }))
+        .collect()
+}
+
+/// Labels windows of MHEALTH records.
+pub struct ActivityClassifier;
+
+impl ActivityClassifier {
+    /// Majority activity label among the window's labelled records, with the share of
+    /// records that carry it as confidence. Returns `(0, 0.0)` if nothing is labelled.
+    ///
+    /// This reads the dataset's annotations, so it is exact on MHEALTH recordings and
+    /// serves as the reference for learned classifiers.
+    pub fn classify(window: &[MhealthRecord]) -> (u8, f32) {
+        let mut counts = [0usize; 13];
+        for record in window {
+            counts[record.label as usize] += 1;
+        }
+        let labelled: usize = counts[1..].iter().sum();
+        if labelled == 0 {
+            return (0, 0.0);
+        }
+        // Ties go to the lower label
+        let (label, count) = (1..=12).map(|l| (l, counts[l])).fold((0, 0), |best, c| if c.1 > best.1 { c } else { best });
+        (label as u8, count as f32 / labelled as f32)
+    }
+}
+
+/// Streams an MHEALTH recording one window at a time.
+///
+/// Each poll emits the next window of samples (one second by default) as chest
+/// accelerometer readings in g (`AccelX/Y/Z` and `AccelMag`) and ECG lead 1 in mV.
+/// Timestamps are spaced 20 ms apart from `start_ts_ms`.
+pub struct MhealthStreamingSensor {
+    records: Vec<MhealthRecord>,
+    cursor: usize,
+    window_len: usize,
+    start_ts_ms: i64,
+    looping: bool,
+    /// Completed passes, so timestamps keep increasing when looping.
+    passes: i64,
+    window: Vec<MhealthRecord>,
+}
+
+impl MhealthStreamingSensor {
+    /// Channels emitted on every poll.
+    pub const CHANNELS: [SignalKind; 5] =
+        [SignalKind::AccelX, SignalKind::AccelY, SignalKind::AccelZ, SignalKind::AccelMag, SignalKind::Ecg];
+
+    /// Stream `records`, starting at `start_ts_ms`, one second per poll, without looping.
+    pub fn new(records: Vec<MhealthRecord>, start_ts_ms: i64) -> Self {
+        Self {
+            records,
+            cursor: 0,
+            window_len: SAMPLE_RATE_HZ as usize,
+            start_ts_ms,
+            looping: false,
+            passes: 0,
+            window: Vec::new(),
+        }
+    }
+
+    /// Parse `content` as an MHEALTH log and stream it.
+    pub fn from_log(content: &str, start_ts_ms: i64) -> Result<Self, MhealthParseError> {
+        Ok(Self::new(parse_log(content)?, start_ts_ms))
+    }
+
+    /// Read and stream an MHEALTH log file.
+    pub fn open(path: impl AsRef<Path>, start_ts_ms: i64) -> anyhow::Result<Self> {
+        Ok(Self::from_log(&std::fs::read_to_string(path)?, start_ts_ms)?)
+    }
+
+    /// Emit `samples` records per poll (at least one).
+    pub fn with_window(mut self, samples: usize) -> Self {
+        self.window_len = samples.max(1);
+        self
+    }
+
+    /// Start again from the first record after the last one instead of stopping.
+    pub fn looping(mut self, looping: bool) -> Self {
+        self.looping = looping;
+        self
+    }
+
+    /// Number of records in the recording.
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// True if the recording has no records.
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Records emitted by the most recent poll.
+    pub fn window(&self) -> &[MhealthRecord] {
+        &self.window
+    }
+
+    fn ts_ms(&self, index: usize) -> i64 {
+        let sample = self.passes * self.records.len() as i64 + index as i64;
+        self.start_ts_ms + sample * 1000 / SAMPLE_RATE_HZ as i64
+    }
+}
+
+impl Sensor for MhealthStreamingSensor {
+    fn name(&self) -> &str {
+        "mhealth"
+    }
+
+    fn channels(&self) -> &[SignalKind] {
+        &Self::CHANNELS
+    }
+
+    fn poll(&mut self) -> Vec<Reading> {
+        self.window.clear();
+        let mut readings = Vec::with_capacity(self.window_len * Self::CHANNELS.len());
+        while self.window.len() < self.window_len {
+            if self.cursor == self.records.len() {
+                if !self.looping || self.records.is_empty() {
+                    break;
+                }
+                self.cursor = 0;
+                self.passes += 1;
+            }
+            let record = self.records[self.cursor].clone();
+            let ts = self.ts_ms(self.cursor);
+            let [x, y, z] = record.chest_accel.map(|a| a / G);
+            readings.extend([
+                Reading::new(SignalKind::AccelX, ts, x),
+                Reading::new(SignalKind::AccelY, ts, y),
+                Reading::new(SignalKind::AccelZ, ts, z),
+                Reading::new(SignalKind::AccelMag, ts, record.chest_accel_mag_g()),
+                Reading::new(SignalKind::Ecg, ts, record.ecg[0]),
+            ]);
+            self.window.push(record);
+            self.cursor += 1;
+        }
+        readings
+    }
+}
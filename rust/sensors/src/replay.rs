@@ -0,0 +1,200 @@
+//! Playback of recorded sessions as a live sensor.
+
+use crate::Sensor;
+use core_types::{Reading, SignalKind};
+use data_layer::parquet::query_sensor_data_range_parquet;
+use data_layer::query::{query_sensor_data_range, SensorDataRow};
+use rusqlite::{Connection, OpenFlags};
+use std::path::Path;
+use std::time::Instant;
+
+/// How fast a [`ReplaySensor`] moves through its recording.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplaySpeed {
+    /// Recording time advances with wall-clock time (same as `Accelerated(1.0)`).
+    RealTime,
+    /// Recording time advances this many times faster than wall-clock time. The factor
+    /// must be finite and positive; [`ReplaySensor::with_speed`] replaces others with `RealTime`.
+    Accelerated(f64),
+    /// Each poll returns exactly the next recorded row, regardless of time.
+    Stepped,
+}
+
+impl ReplaySpeed {
+    fn factor(self) -> Option<f64> {
+        match self {
+            ReplaySpeed::RealTime => Some(1.0),
+            ReplaySpeed::Accelerated(factor) => Some(factor),
+            ReplaySpeed::Stepped => None,
+        }
+    }
+}
+
+/// Streams the rows of a recorded session as tagged readings.
+///
+/// Readings keep their recorded timestamps. When looping, each pass is shifted by the
+/// recording's length so timestamps keep increasing.
+pub struct ReplaySensor {
+    name: String,
+    rows: Vec<SensorDataRow>,
+    cursor: usize,
+    speed: ReplaySpeed,
+    looping: bool,
+    /// Completed passes through the recording.
+    passes: i64,
+    /// Wall-clock instant and virtual recording time (ms) that playback is anchored to.
+    anchor: Option<(Instant, f64)>,
+}
+
+impl ReplaySensor {
+    /// Replay `rows` (sorted by timestamp here) in real time, without looping.
+    pub fn from_rows(name: impl Into<String>, mut rows: Vec<SensorDataRow>) -> Self {
+        rows.sort_by_key(|r| r.ts_unix_sec);
+        Self {
+            name: name.into(),
+            rows,
+            cursor: 0,
+            speed: ReplaySpeed::RealTime,
+            looping: false,
+            passes: 0,
+            anchor: None,
+        }
+    }
+
+    /// Load every row of `session_id` from a SQLite database written by `chrona-ingest`.
+    pub fn from_sqlite(path: impl AsRef<Path>, session_id: i64) -> anyhow::Result<Self> {
+        let conn = Connection::open_with_flags(path.as_ref(), OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        let rows = query_sensor_data_range(&conn, session_id, i64::MIN, i64::MAX)?;
+        Ok(Self::from_rows(format!("replay_sqlite_{}", session_id), rows))
+    }
+
+    /// Load every row of `session_id` from a sensor_data Parquet file.
+    pub fn from_parquet(path: impl AsRef<Path>, session_id: i64) -> anyhow::Result<Self> {
+        let path = path.as_ref().to_string_lossy();
+        let rows = query_sensor_data_range_parquet(&path, session_id, i64::MIN, i64::MAX)?;
+        Ok(Self::from_rows(format!("replay_parquet_{}", session_id), rows))
+    }
+
+    /// Load from SQLite or Parquet depending on the file extension.
+    pub fn open(path: impl AsRef<Path>, session_id: i64) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("parquet") => Self::from_parquet(path, session_id),
+            _ => Self::from_sqlite(path, session_id),
+        }
+    }
+
+    /// Set the playback speed.
+    ///
+    /// A non-finite or non-positive `Accelerated` factor would stall or rewind playback,
+    /// so it falls back to `RealTime`; compare [`speed`](Self::speed) to detect this.
+    pub fn with_speed(mut self, speed: ReplaySpeed) -> Self {
+        self.speed = match speed {
+            ReplaySpeed::Accelerated(factor) if !(factor.is_finite() && factor > 0.0) => ReplaySpeed::RealTime,
+            speed => speed,
+        };
+        self.anchor = None;
+        self
+    }
+
+    /// Playback speed in effect.
+    pub fn speed(&self) -> ReplaySpeed {
+        self.speed
+    }
+
+    /// Start again from the first row after the last one instead of stopping.
+    pub fn looping(mut self, looping: bool) -> Self {
+        self.looping = looping;
+        self
+    }
+
+    /// Number of recorded rows.
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// True if the recording has no rows.
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// True once a non-looping replay has emitted every row.
+    pub fn is_finished(&self) -> bool {
+        self.cursor >= self.rows.len()
+    }
+
+    /// Recorded timestamp (Unix seconds) of the next row to be emitted.
+    pub fn position(&self) -> Option<i64> {
+        self.rows.get(self.cursor).map(|r| r.ts_unix_sec)
+    }
+
+    /// Jump to the first row at or after `ts_unix_sec` (recorded time).
+    ///
+    /// Timed playback continues from there at the current speed.
+    pub fn seek(&mut self, ts_unix_sec: i64) {
+        self.cursor = self.rows.partition_point(|r| r.ts_unix_sec < ts_unix_sec);
+        self.anchor = None;
+    }
+
+    /// Poll as if the wall clock read `now`; [`Sensor::poll`] uses [`Instant::now`].
+    pub fn poll_at(&mut self, now: Instant) -> Vec<Reading> {
+        let Some(factor) = self.speed.factor() else {
+            return self.next_row().unwrap_or_default();
+        };
+        let Some(start) = self.virtual_ms() else {
+            return Vec::new();
+        };
+        let (anchor_at, anchor_ms) = *self.anchor.get_or_insert((now, start as f64));
+        let target = anchor_ms + now.saturating_duration_since(anchor_at).as_secs_f64() * 1000.0 * factor;
+        let mut readings = Vec::new();
+        while self.virtual_ms().is_some_and(|ms| ms as f64 <= target) {
+            readings.extend(self.next_row().unwrap_or_default());
+        }
+        readings
+    }
+
+    /// Length of one pass in ms: first to last row plus one row interval.
+    fn period_ms(&self) -> i64 {
+        let (Some(first), Some(last)) = (self.rows.first(), self.rows.last()) else {
+            return 0;
+        };
+        let step = self.rows.get(1).map_or(1, |r| (r.ts_unix_sec - first.ts_unix_sec).max(1));
+        (last.ts_unix_sec - first.ts_unix_sec + step) * 1000
+    }
+
+    /// Timestamp (ms) of the next row, shifted by completed loop passes.
+    fn virtual_ms(&self) -> Option<i64> {
+        self.position().map(|ts| ts * 1000 + self.passes * self.period_ms())
+    }
+
+    /// Readings of the next row, advancing (and wrapping, when looping) the cursor.
+    fn next_row(&mut self) -> Option<Vec<Reading>> {
+        let offset = self.passes * self.period_ms();
+        let row = self.rows.get(self.cursor)?;
+        let readings = row
+            .readings()
+            .into_iter()
+            .map(|r| Reading { ts_ms: r.ts_ms + offset, ..r })
+            .collect();
+        self.cursor += 1;
+        if self.looping && self.cursor == self.rows.len() {
+            self.cursor = 0;
+            self.passes += 1;
+        }
+        Some(readings)
+    }
+}
+
+impl Sensor for ReplaySensor {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn channels(&self) -> &[SignalKind] {
+        &SensorDataRow::SIGNALS
+    }
+
+    fn poll(&mut self) -> Vec<Reading> {
+        self.poll_at(Instant::now())
+    }
+}
@@ -0,0 +1,132 @@
+use core_types::{Reading, SignalKind};
+use sensors::ble::{write_notification, BleDecodeError};
+use sensors::{BleHeartRateSensor, HeartRateMeasurement, Sensor};
+use std::io::Cursor;
+use std::sync::mpsc;
+
+const START_MS: i64 = 1_766_102_400_000;
+
+fn values(readings: &[Reading], kind: SignalKind) -> Vec<(i64, f32)> {
+    readings.iter().filter(|r| r.kind == kind).map(|r| (r.ts_ms, r.value)).collect()
+}
+
+#[test]
+fn decodes_u8_heart_rate() {
+    let m = HeartRateMeasurement::decode(&[0x00, 72]).unwrap();
+    assert_eq!(m.heart_rate, 72);
+    assert_eq!(m.sensor_contact, None);
+    assert_eq!(m.energy_expended_kj, None);
+    assert!(m.rr_intervals.is_empty());
+    assert!(m.has_contact());
+}
+
+#[test]
+fn decodes_u16_heart_rate_energy_and_rr() {
+    // u16 HR 300, contact supported + detected, energy 0x0102 kJ, RR 1024 and 512
+    let payload = [0x1F, 0x2C, 0x01, 0x02, 0x01, 0x00, 0x04, 0x00, 0x02];
+    let m = HeartRateMeasurement::decode(&payload).unwrap();
+    assert_eq!(m.heart_rate, 300);
+    assert_eq!(m.sensor_contact, Some(true));
+    assert_eq!(m.energy_expended_kj, Some(258));
+    assert_eq!(m.rr_intervals, [1024, 512]);
+    assert_eq!(m.rr_intervals_ms(), [1000.0, 500.0]);
+    assert_eq!(m.encode(), payload);
+}
+
+#[test]
+fn decodes_rr_without_energy_and_lost_contact() {
+    // u8 HR 64, contact supported but not detected, RR 0x0320 (800/1024 s)
+    let m = HeartRateMeasurement::decode(&[0x14, 0x40, 0x20, 0x03]).unwrap();
+    assert_eq!(m.heart_rate, 64);
+    assert_eq!(m.sensor_contact, Some(false));
+    assert!(!m.has_contact());
+    assert_eq!(m.rr_intervals, [800]);
+    assert_eq!(m.rr_intervals_ms(), [781.25]);
+    // RR flag with no intervals is valid
+    assert!(HeartRateMeasurement::decode(&[0x10, 60]).unwrap().rr_intervals.is_empty());
+}
+
+#[test]
+fn rejects_malformed_payloads() {
+    assert_eq!(HeartRateMeasurement::decode(&[]), Err(BleDecodeError::Empty));
+    assert_eq!(
+        HeartRateMeasurement::decode(&[0x00]),
+        Err(BleDecodeError::Truncated { field: "heart rate", needed: 2, len: 1 })
+    );
+    assert_eq!(
+        HeartRateMeasurement::decode(&[0x01, 0x48]),
+        Err(BleDecodeError::Truncated { field: "heart rate", needed: 3, len: 2 })
+    );
+    let err = HeartRateMeasurement::decode(&[0x08, 0x48, 0x10]).unwrap_err();
+    assert_eq!(err, BleDecodeError::Truncated { field: "energy expended", needed: 4, len: 3 });
+    assert_eq!(err.to_string(), "heart rate measurement truncated in energy expended: needs 4 bytes, got 3");
+    assert_eq!(HeartRateMeasurement::decode(&[0x10, 0x48, 0x00, 0x04, 0x00]), Err(BleDecodeError::OddRrBytes(3)));
+}
+
+#[test]
+fn replays_a_capture_one_notification_per_poll() {
+    let mut capture = Vec::new();
+    for payload in [
+        &[0x10, 60, 0x00, 0x04][..],          // 60 bpm, one 1000 ms beat
+        &[0x00, 61],                          // no RR: clock advances 1 s
+        &[0x01, 0x02],                        // truncated: skipped
+        &[0x14, 62, 0x00, 0x02],              // lost contact: no readings
+        &[0x16, 120, 0x00, 0x02, 0x00, 0x02], // 120 bpm, two 500 ms beats
+    ] {
+        write_notification(&mut capture, payload).unwrap();
+    }
+    let mut sensor = BleHeartRateSensor::from_reader(Cursor::new(capture), START_MS);
+    assert_eq!(sensor.channels(), &BleHeartRateSensor::CHANNELS);
+
+    let first = sensor.poll();
+    assert_eq!(values(&first, SignalKind::RrInterval), [(START_MS + 1000, 1000.0)]);
+    assert_eq!(values(&first, SignalKind::HeartRate), [(START_MS + 1000, 60.0)]);
+
+    assert_eq!(values(&sensor.poll(), SignalKind::HeartRate), [(START_MS + 2000, 61.0)]);
+    assert!(sensor.poll().is_empty());
+    assert_eq!(sensor.skipped(), 1);
+    assert!(sensor.poll().is_empty());
+    assert_eq!(sensor.last_measurement().unwrap().sensor_contact, Some(false));
+
+    let last = sensor.poll();
+    assert_eq!(values(&last, SignalKind::RrInterval), [(START_MS + 3000, 500.0), (START_MS + 3500, 500.0)]);
+    assert_eq!(values(&last, SignalKind::HeartRate), [(START_MS + 3500, 120.0)]);
+    assert!(!sensor.is_finished());
+    assert!(sensor.poll().is_empty());
+    assert!(sensor.is_finished());
+    assert!(sensor.error().is_none());
+}
+
+#[test]
+fn reports_a_capture_truncated_mid_notification() {
+    let mut capture = Vec::new();
+    write_notification(&mut capture, &[0x00, 60]).unwrap();
+    write_notification(&mut capture, &[0x10, 61, 0x00, 0x04]).unwrap();
+    capture.truncate(capture.len() - 2);
+    let mut sensor = BleHeartRateSensor::from_reader(Cursor::new(capture), START_MS);
+
+    assert_eq!(values(&sensor.poll(), SignalKind::HeartRate), [(START_MS + 1000, 60.0)]);
+    assert!(sensor.error().is_none());
+    assert!(sensor.poll().is_empty());
+    assert!(sensor.is_finished());
+    assert_eq!(sensor.error().map(|e| e.kind()), Some(std::io::ErrorKind::UnexpectedEof));
+    // The broken frame never reached the decoder
+    assert_eq!(sensor.skipped(), 0);
+}
+
+#[test]
+fn forwards_channel_notifications() {
+    let (tx, rx) = mpsc::channel();
+    let mut sensor = BleHeartRateSensor::from_channel(rx, START_MS);
+    assert!(sensor.poll().is_empty());
+    assert!(!sensor.is_finished());
+
+    tx.send(vec![0x00, 70]).unwrap();
+    tx.send(vec![0x00, 71]).unwrap();
+    let readings = sensor.poll();
+    assert_eq!(values(&readings, SignalKind::HeartRate), [(START_MS + 1000, 70.0), (START_MS + 2000, 71.0)]);
+
+    drop(tx);
+    assert!(sensor.poll().is_empty());
+    assert!(sensor.is_finished());
+}
@@ -0,0 +1,116 @@
+use core_types::{Reading, SignalKind};
+use sensors::mhealth::{parse_log, MhealthParseError};
+use sensors::{ActivityClassifier, MhealthRecord, MhealthStreamingSensor, Sensor};
+
+/// 100 samples (2 s): 10 unlabelled, 30 standing (L1), 40 walking (L4), 20 running (L11).
+const EXCERPT: &str = include_str!("fixtures/mhealth_excerpt.log");
+const START_MS: i64 = 1_766_102_400_000;
+
+fn values(readings: &[Reading], kind: SignalKind) -> Vec<f32> {
+    readings.iter().filter(|r| r.kind == kind).map(|r| r.value).collect()
+}
+
+#[test]
+fn parses_fixture_columns_and_labels() {
+    let records = parse_log(EXCERPT).unwrap();
+    assert_eq!(records.len(), 100);
+
+    let first = &records[0];
+    assert_eq!(first.chest_accel, [-9.7328, 0.14557, 0.2787]);
+    assert_eq!(first.ecg, [-0.0073507, 0.022352]);
+    assert_eq!(first.ankle_accel, [2.1687, -9.4788, 0.83241]);
+    assert_eq!(first.arm_mag, [0.71163, -0.32377, -10.794]);
+    assert_eq!(first.label, 0);
+    assert!((first.chest_accel_mag_g() - 1.0).abs() < 0.02);
+
+    let labels: Vec<u8> = [0, 10, 40, 80, 99].iter().map(|&i| records[i].label).collect();
+    assert_eq!(labels, [0, 1, 4, 11, 11]);
+}
+
+#[test]
+fn csv_lines_parse_like_tab_separated_ones() {
+    let line = EXCERPT.lines().nth(20).unwrap();
+    let csv = line.split('\t').collect::<Vec<_>>().join(",");
+    assert_eq!(MhealthRecord::parse_line(&csv), MhealthRecord::parse_line(line));
+}
+
+#[test]
+fn bad_lines_report_their_line_number() {
+    let mut lines: Vec<&str> = EXCERPT.lines().take(5).collect();
+    let truncated = lines[2].rsplit_once('\t').unwrap().0.to_string();
+    lines[2] = &truncated;
+    let err = parse_log(&lines.join("\n")).unwrap_err();
+    assert_eq!(err.line, 3);
+    assert!(err.reason.contains("expected 24 columns"), "{}", err);
+
+    let bad_label = format!("{}\t13", EXCERPT.lines().next().unwrap().rsplit_once('\t').unwrap().0);
+    let err: MhealthParseError = parse_log(&bad_label).unwrap_err();
+    assert!(err.reason.contains("label"), "{}", err);
+
+    let bad_number = EXCERPT.lines().next().unwrap().replacen("-9.7328", "n/a", 1);
+    assert!(parse_log(&bad_number).unwrap_err().reason.contains("column 1"));
+}
+
+#[test]
+fn activity_names_and_intensity() {
+    let mut record = parse_log(EXCERPT).unwrap().remove(0);
+    assert_eq!(record.activity_name(), "Unlabelled");
+    assert_eq!(record.intensity(), 0.0);
+
+    let mut previous = None;
+    for (label, name) in [(3, "Lying down"), (1, "Standing still"), (4, "Walking"), (10, "Jogging"), (11, "Running")] {
+        record.label = label;
+        assert_eq!(record.activity_name(), name);
+        // Listed from least to most strenuous
+        assert!(previous.is_none_or(|p| record.intensity() > p), "{}", name);
+        assert!((0.0..=1.0).contains(&record.intensity()));
+        previous = Some(record.intensity());
+    }
+    record.label = 12;
+    assert_eq!(record.activity_name(), "Jump front & back");
+}
+
+#[test]
+fn classifier_votes_over_labelled_records() {
+    let records = parse_log(EXCERPT).unwrap();
+    assert_eq!(ActivityClassifier::classify(&records[..10]), (0, 0.0));
+    assert_eq!(ActivityClassifier::classify(&records[10..40]), (1, 1.0));
+    // 10 unlabelled are ignored; 30 standing vs 10 walking
+    assert_eq!(ActivityClassifier::classify(&records[..50]), (1, 0.75));
+    assert_eq!(ActivityClassifier::classify(&records[50..]), (4, 0.6));
+}
+
+#[test]
+fn streams_one_second_windows() {
+    let mut sensor = MhealthStreamingSensor::from_log(EXCERPT, START_MS).unwrap();
+    assert_eq!(sensor.channels(), &MhealthStreamingSensor::CHANNELS);
+
+    let first = sensor.poll();
+    assert_eq!(sensor.window().len(), 50);
+    assert_eq!(first.len(), 50 * MhealthStreamingSensor::CHANNELS.len());
+    let ts: Vec<i64> = first.iter().filter(|r| r.kind == SignalKind::Ecg).map(|r| r.ts_ms).collect();
+    assert_eq!(ts[0], START_MS);
+    assert_eq!(ts[49], START_MS + 49 * 20);
+    assert_eq!(values(&first, SignalKind::Ecg)[0], -0.0073507);
+    assert!(values(&first, SignalKind::AccelX).iter().all(|x| (-1.3..-0.7).contains(x)));
+    assert_eq!(ActivityClassifier::classify(sensor.window()).0, 1);
+
+    let second = sensor.poll();
+    assert_eq!(second[0].ts_ms, START_MS + 1000);
+    assert_eq!(ActivityClassifier::classify(sensor.window()).0, 4);
+
+    assert!(sensor.poll().is_empty());
+    assert!(sensor.window().is_empty());
+}
+
+#[test]
+fn looping_keeps_timestamps_increasing() {
+    let mut sensor = MhealthStreamingSensor::from_log(EXCERPT, START_MS).unwrap().with_window(60).looping(true);
+    sensor.poll();
+    let wrapped = sensor.poll();
+    assert_eq!(sensor.window().len(), 60);
+    let ts: Vec<i64> = wrapped.iter().filter(|r| r.kind == SignalKind::AccelMag).map(|r| r.ts_ms).collect();
+    assert!(ts.windows(2).all(|w| w[1] - w[0] == 20));
+    assert_eq!(*ts.last().unwrap(), START_MS + 119 * 20);
+    assert_eq!(sensor.window()[40], parse_log(EXCERPT).unwrap()[0]);
+}
@@ -0,0 +1,120 @@
+use core_types::{Reading, SignalKind};
+use data_layer::parquet::SensorParquetWriter;
+use data_layer::query::SensorDataRow;
+use data_layer::sqlite::{self, SensorBatchWriter};
+use rusqlite::Connection;
+use sensors::{ReplaySensor, ReplaySpeed, Sensor};
+use std::time::{Duration, Instant};
+
+const START: i64 = 1_766_102_400;
+
+/// `n` one-second rows whose HR equals their index.
+fn rows(n: usize) -> Vec<SensorDataRow> {
+    (0..n)
+        .map(|i| SensorDataRow {
+            ts_unix_sec: START + i as i64,
+            hr: i as f32,
+            hrv_rmssd: 40.0,
+            eda_mus: 2.0,
+            temp_c: 33.0,
+            accel_mag_g: 0.1,
+            activity: "sitting".into(),
+            stress_level: 0.2,
+            exercise_flag: false,
+            sleep_stage: None,
+        })
+        .collect()
+}
+
+fn heart_rates(readings: &[Reading]) -> Vec<f32> {
+    readings.iter().filter(|r| r.kind == SignalKind::HeartRate).map(|r| r.value).collect()
+}
+
+#[test]
+fn stepped_replay_emits_one_row_per_poll_and_stops() {
+    let mut sensor = ReplaySensor::from_rows("test", rows(3)).with_speed(ReplaySpeed::Stepped);
+    assert_eq!(sensor.channels().len(), 5);
+    let first = sensor.poll();
+    assert_eq!(first.len(), 5);
+    assert_eq!(first[0], Reading::new(SignalKind::HeartRate, START * 1000, 0.0));
+    assert_eq!(heart_rates(&sensor.poll()), [1.0]);
+    assert_eq!(heart_rates(&sensor.poll()), [2.0]);
+    assert!(sensor.is_finished());
+    assert!(sensor.poll().is_empty());
+}
+
+#[test]
+fn timed_replay_follows_the_wall_clock() {
+    let t0 = Instant::now();
+    let at = |secs: f64| t0 + Duration::from_secs_f64(secs);
+
+    let mut realtime = ReplaySensor::from_rows("test", rows(100));
+    assert_eq!(heart_rates(&realtime.poll_at(at(0.0))), [0.0]);
+    assert!(realtime.poll_at(at(0.5)).is_empty());
+    assert_eq!(heart_rates(&realtime.poll_at(at(3.2))), [1.0, 2.0, 3.0]);
+
+    let mut fast = ReplaySensor::from_rows("test", rows(100)).with_speed(ReplaySpeed::Accelerated(10.0));
+    assert_eq!(fast.speed(), ReplaySpeed::Accelerated(10.0));
+    fast.poll_at(at(0.0));
+    assert_eq!(heart_rates(&fast.poll_at(at(1.0))).len(), 10);
+    assert_eq!(fast.position(), Some(START + 11));
+}
+
+#[test]
+fn looping_replay_keeps_timestamps_increasing() {
+    let mut sensor = ReplaySensor::from_rows("test", rows(3)).with_speed(ReplaySpeed::Stepped).looping(true);
+    let ts: Vec<i64> = (0..7).map(|_| sensor.poll()[0].ts_ms).collect();
+    assert!(ts.windows(2).all(|w| w[1] - w[0] == 1000), "{ts:?}");
+    assert!(!sensor.is_finished());
+}
+
+#[test]
+fn seek_jumps_to_recorded_time() {
+    let t0 = Instant::now();
+    let mut sensor = ReplaySensor::from_rows("test", rows(3600)).with_speed(ReplaySpeed::Accelerated(100.0));
+    sensor.poll_at(t0);
+    sensor.seek(START + 1800);
+    assert_eq!(heart_rates(&sensor.poll_at(t0 + Duration::from_secs(5))), [1800.0]);
+    assert_eq!(heart_rates(&sensor.poll_at(t0 + Duration::from_secs(6))).len(), 100);
+    sensor.seek(START + 10_000);
+    assert!(sensor.is_finished());
+}
+
+#[test]
+fn replays_sessions_from_sqlite_and_parquet() {
+    let dir = std::env::temp_dir().join(format!("chrona-replay-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let db = dir.join("sessions.sqlite3");
+    let mut conn = Connection::open(&db).unwrap();
+    sqlite::init_db(&conn).unwrap();
+    let other = sqlite::insert_user_session(&conn, "a", "p", "2025-12-19", 1).unwrap();
+    let session = sqlite::insert_user_session(&conn, "b", "p", "2025-12-19", 2).unwrap();
+    SensorBatchWriter::new(&mut conn, other).write(&rows(2)).unwrap();
+    SensorBatchWriter::new(&mut conn, session).write(&rows(5)).unwrap();
+    drop(conn);
+
+    let parquet = dir.join("session.parquet");
+    let mut writer = SensorParquetWriter::create(&parquet).unwrap();
+    writer.write_all(session, &rows(5)).unwrap();
+    writer.close().unwrap();
+
+    for path in [&db, &parquet] {
+        let mut sensor = ReplaySensor::open(path, session).unwrap().with_speed(ReplaySpeed::Stepped);
+        assert_eq!(sensor.len(), 5, "{}", path.display());
+        let hr: Vec<f32> = (0..5).flat_map(|_| heart_rates(&sensor.poll())).collect();
+        assert_eq!(hr, [0.0, 1.0, 2.0, 3.0, 4.0]);
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn invalid_speed_factors_fall_back_to_real_time() {
+    let t0 = Instant::now();
+    for factor in [0.0, -10.0, f64::NAN, f64::INFINITY] {
+        let mut sensor = ReplaySensor::from_rows("test", rows(10)).with_speed(ReplaySpeed::Accelerated(factor));
+        assert_eq!(sensor.speed(), ReplaySpeed::RealTime);
+        assert_eq!(heart_rates(&sensor.poll_at(t0)), [0.0]);
+        assert_eq!(heart_rates(&sensor.poll_at(t0 + Duration::from_secs(2))), [1.0, 2.0], "factor {factor}");
+    }
+}
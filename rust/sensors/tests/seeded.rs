@@ -0,0 +1,22 @@
+use core_types::SignalKind;
+use sensors::{Sensor, SyntheticHeartRate};
+
+fn trace(seed: u64) -> Vec<f32> {
+    let mut sensor = SyntheticHeartRate::seeded(70.0, 5.0, 0, seed);
+    (0..100)
+        .map(|_| {
+            let readings = sensor.poll();
+            assert_eq!(readings.len(), 1);
+            assert_eq!(readings[0].kind, SignalKind::HeartRate);
+            readings[0].value
+        })
+        .collect()
+}
+
+#[test]
+fn seeded_heart_rate_is_reproducible() {
+    let a = trace(7);
+    assert_eq!(a, trace(7));
+    assert_ne!(a, trace(8));
+    assert_eq!(a[..3], [66.40003, 66.57796, 66.82055]);
+}
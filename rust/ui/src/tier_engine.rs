@@ -1,27 +1,28 @@
 //! Tier-specific engine instances for UI state.
 
-use core_types::Tier;
-// use data_layer::{NoopStore, TimeSeriesStore};
+use core_types::{SignalKind, Tier};
+use data_layer::retention::RetentionManager;
+use data_layer::{MemoryStore, SqliteStore, TimeSeriesStore};
 use llm_runtime::{LlmEngine, NoopLlm, Prompt, RealLlm};
 use ml_runtime::{Model, NoopModel};
 use logic::ActivityContext;
 use std::fs;
 use sensors::Sensor;
-use sensors::SyntheticHeartRate;
+use sensors::{MhealthStreamingSensor, ReplaySensor, ReplaySpeed, SyntheticHeartRate};
 
 /// Wrapper holding all runtime components for one tier.
 pub struct TierEngine {
     pub tier: Tier,
     pub sensor: Box<dyn Sensor>,
-    // pub store: Box<dyn TimeSeriesStore>,
+    pub store: Box<dyn TimeSeriesStore>,
     pub model: Box<dyn Model>,
     pub llm: Box<dyn LlmEngine>,
     pub last_value: Option<f32>,
     // Mini 8GB: MHEALTH activity context
     pub activity_context: Option<ActivityContext>,
     pub baseline_hr: u32,
-    // For Mini tier: keep reference to streaming sensor for activity window
-    // mhealth_sensor: Option<MhealthStreamingSensor>,
+    // For Mini tier: MHEALTH stream whose windows drive the activity context
+    mhealth_sensor: Option<MhealthStreamingSensor>,
 }
 
 impl TierEngine {
@@ -85,65 +86,57 @@ impl TierEngine {
             Tier::Pro32 => Box::new(NoopLlm),      // TODO: add 32GB model
         };
         
-        // Load MHEALTH sensor for Mini tier
-        // let (sensor, mhealth_sensor_copy): (Box<dyn Sensor>, Option<MhealthStreamingSensor>) = match tier {
-        //     Tier::Mini8 => {
-        //         let possible_paths = vec![
-        //             "C:\\Users\\legot\\Chrona\\data\\mini\\mhealth_raw_data.csv",
-        //             "data\\mini\\mhealth_raw_data.csv",
-        //             "..\\..\\data\\mini\\mhealth_raw_data.csv",
-        //         ];
-        //         if let Some(content) = possible_paths.iter().find_map(|p| fs::read_to_string(p).ok()) {
-        //             let mhealth = MhealthStreamingSensor::from_csv(&content);
-        //             // Clone for window access
-        //             let mhealth_copy = MhealthStreamingSensor::from_csv(&content);
-        //             (Box::new(mhealth), Some(mhealth_copy))
-        //         } else {
-        //             (Box::new(SyntheticHeartRate::new(baseline, 5.0, start_ts)), None)
-        //         }
-        //     }
-        //     _ => (Box::new(SyntheticHeartRate::new(baseline, 5.0, start_ts)), None),
-        // };
+        // Persist polled samples per tier, falling back to memory if the DB can't be opened
+        let store = open_tier_store(tier);
+
+        // Mini tier streams an MHEALTH recording alongside HR for activity context
+        let mhealth_sensor = match tier {
+            Tier::Mini8 => open_mhealth(start_ts),
+            _ => None,
+        };
 
         Self {
             tier,
-            sensor: Box::new(SyntheticHeartRate::new(baseline, 5.0, start_ts)),
+            sensor: open_sensor(tier, baseline, start_ts),
+            store,
             model,
             llm,
             last_value: None,
             activity_context: None,
             baseline_hr: baseline as u32,
-            // mhealth_sensor: mhealth_sensor_copy,
+            mhealth_sensor,
         }
     }
 
     /// Poll sensor and update state.
     pub fn poll(&mut self) {
-        if let Some(sample) = self.sensor.poll() {
-            // self.store.write(self.sensor.name(), sample.clone());
-            self.last_value = Some(sample.value);
+        let readings = self.sensor.poll();
+        if let Err(e) = self.store.write_readings(&readings) {
+            println!("✗ Failed to persist readings for {:?}: {}", self.tier, e);
+        }
+        // A replay can deliver several rows per poll; show the latest
+        if let Some(hr) = readings.iter().rev().find(|r| r.kind == SignalKind::HeartRate) {
+            self.last_value = Some(hr.value);
         }
 
         // Update activity from MHEALTH sensor (Mini tier only)
-        if matches!(self.tier, Tier::Mini8) {
-            // if let Some(ref mhealth) = self.mhealth_sensor {
-                // let window = mhealth.get_window();
-                // if !window.is_empty() {
-                //     // let (_label, _conf) = ActivityClassifier::classify(&window);
-                //     if let Some(first) = window.first() {
-                //         // let ctx = ActivityContext::from_record(first, self.baseline_hr);
-                //         // self.activity_context = Some(ctx);
-                //     }
-                // }
-            // }
+        if let Some(mhealth) = &mut self.mhealth_sensor {
+            let readings = mhealth.poll();
+            if let Err(e) = self.store.write_readings(&readings) {
+                println!("✗ Failed to persist MHEALTH readings for {:?}: {}", self.tier, e);
+            }
+            if let Some(ctx) = ActivityContext::from_window(mhealth.window(), self.baseline_hr) {
+                self.activity_context = Some(ctx);
+            }
         }
     }
 
     /// Get current sensor value or placeholder.
     pub fn current_value(&self) -> String {
+        let unit = SignalKind::HeartRate.unit();
         self.last_value
-            .map(|v| format!("{:.0} bpm", v))
-            .unwrap_or_else(|| "-- bpm".to_string())
+            .map(|v| format!("{:.0} {}", v, unit))
+            .unwrap_or_else(|| format!("-- {}", unit))
     }
 
     /// Ask LLM a question with tier context.
@@ -162,3 +155,110 @@ impl TierEngine {
         self.llm.run(prompt).text
     }
 }
+
+/// Replay a recorded session when `CHRONA_REPLAY` names a SQLite or Parquet file written by
+/// chrona-ingest, otherwise generate jitter around `baseline`.
+///
+/// `CHRONA_REPLAY_SESSION` picks the session (default 1) and `CHRONA_REPLAY_SPEED` the
+/// speed: a positive factor such as `10` or `100` (default `1`, real time) or `step` for one
+/// row per poll. Replays loop.
+fn open_sensor(tier: Tier, baseline: f32, start_ts: i64) -> Box<dyn Sensor> {
+    let synthetic = || -> Box<dyn Sensor> { Box::new(SyntheticHeartRate::new(baseline, 5.0, start_ts)) };
+    let Ok(path) = std::env::var("CHRONA_REPLAY") else {
+        return synthetic();
+    };
+    let session_id = std::env::var("CHRONA_REPLAY_SESSION")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(1);
+    let speed = match std::env::var("CHRONA_REPLAY_SPEED").as_deref() {
+        Ok("step") => ReplaySpeed::Stepped,
+        Ok(factor) => match factor.parse::<f64>() {
+            Ok(f) if f.is_finite() && f > 0.0 => ReplaySpeed::Accelerated(f),
+            _ => {
+                println!("⚠ Invalid CHRONA_REPLAY_SPEED {:?}; replaying in real time", factor);
+                ReplaySpeed::RealTime
+            }
+        },
+        Err(_) => ReplaySpeed::RealTime,
+    };
+    match ReplaySensor::open(&path, session_id) {
+        Ok(replay) if !replay.is_empty() => {
+            println!("✓ Replaying session {} from {} for {:?} ({:?})", session_id, path, tier, speed);
+            Box::new(replay.with_speed(speed).looping(true))
+        }
+        Ok(_) => {
+            println!("⚠ Session {} in {} has no rows; using synthetic HR", session_id, path);
+            synthetic()
+        }
+        Err(e) => {
+            println!("⚠ Failed to open replay {}: {}; using synthetic HR", path, e);
+            synthetic()
+        }
+    }
+}
+
+/// Load the Mini tier's MHEALTH recording (a UCI `mHealth_subjectN.log` or a CSV export of
+/// one) from `CHRONA_MHEALTH` or `data/mini/`, looping one second per poll.
+fn open_mhealth(start_ts: i64) -> Option<MhealthStreamingSensor> {
+    let possible_paths: Vec<String> = std::env::var("CHRONA_MHEALTH")
+        .into_iter()
+        .chain(
+            [
+                "data/mini/mHealth_subject1.log",
+                "data/mini/mhealth_raw_data.csv",
+                "../data/mini/mHealth_subject1.log",
+                "../data/mini/mhealth_raw_data.csv",
+            ]
+            .map(String::from),
+        )
+        .collect();
+    let path = possible_paths.iter().find(|p| std::path::Path::new(p).is_file())?;
+    match MhealthStreamingSensor::open(path, start_ts) {
+        Ok(sensor) if !sensor.is_empty() => {
+            println!("✓ Streaming MHEALTH activity from: {}", path);
+            Some(sensor.looping(true))
+        }
+        Ok(_) => {
+            println!("⚠ MHEALTH recording {} is empty; no activity context", path);
+            None
+        }
+        Err(e) => {
+            println!("⚠ Failed to load MHEALTH recording {}: {}", path, e);
+            None
+        }
+    }
+}
+
+/// Open the SQLite sample store under `data/<tier>/`, applying the tier's retention policy,
+/// or an in-memory store on failure.
+fn open_tier_store(tier: Tier) -> Box<dyn TimeSeriesStore> {
+    let dir = std::path::Path::new("data").join(tier.data_dir_name());
+    let path = dir.join("samples.sqlite3");
+    if let Err(e) = std::fs::create_dir_all(&dir) {
+        println!("⚠ Falling back to MemoryStore ({}): {}", dir.display(), e);
+        return Box::new(MemoryStore::new());
+    }
+    match SqliteStore::open(&path) {
+        Ok(store) => {
+            println!("✓ Persisting samples to: {}", path.display());
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs() as i64)
+                .unwrap_or(0);
+            match RetentionManager::new(tier).sqlite(store.connection()).enforce(now) {
+                Ok(report) => {
+                    for eviction in &report.evictions {
+                        println!("✓ Retention ({:?}): {}", tier, eviction);
+                    }
+                }
+                Err(e) => println!("⚠ Retention enforcement failed for {:?}: {}", tier, e),
+            }
+            Box::new(store)
+        }
+        Err(e) => {
+            println!("⚠ Falling back to MemoryStore ({}): {}", path.display(), e);
+            Box::new(MemoryStore::new())
+        }
+    }
+}